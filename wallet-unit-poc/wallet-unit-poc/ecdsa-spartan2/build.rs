@@ -1,7 +1,82 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
 use rust_witness::transpile::transpile_wasm;
+use sha2::{Digest, Sha256};
+
+/// Used when neither `L8ZK_CIRCOM_BUILD` nor `[package.metadata.l8zk] circom-build` is set
+const DEFAULT_CIRCOM_BUILD: &str = "../circom/build/";
 
 fn main() {
-    // // Transpile WASM files from the circom build directory to C
-    // // This will transpile both ECDSA and JWT circuits
-    transpile_wasm("../circom/build/".to_string());
+    println!("cargo:rerun-if-env-changed=L8ZK_CIRCOM_BUILD");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+
+    let circom_build = circom_build_dir();
+    // The circuits look for their compiled R1CS under the same directory at runtime
+    println!("cargo:rustc-env=L8ZK_CIRCOM_BUILD={circom_build}");
+
+    // Part of `CircuitVersion`: keys and proofs made with other circom artifacts are rejected
+    println!("cargo:rerun-if-changed={circom_build}");
+    println!(
+        "cargo:rustc-env=L8ZK_CIRCOM_ARTIFACTS_SHA256={}",
+        hash_circom_artifacts(Path::new(&circom_build))
+    );
+
+    // Transpile WASM files from the circom build directory to C
+    // This will transpile both ECDSA and JWT circuits
+    transpile_wasm(circom_build);
+}
+
+/// The circom build directory, relative to the crate directory unless absolute
+fn circom_build_dir() -> String {
+    if let Ok(dir) = env::var("L8ZK_CIRCOM_BUILD") {
+        return dir;
+    }
+
+    let manifest =
+        fs::read_to_string("Cargo.toml").expect("build scripts run in the crate directory");
+    let manifest: toml::Table = manifest.parse().expect("Cargo.toml is valid TOML");
+    manifest
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get("l8zk"))
+        .and_then(|l8zk| l8zk.get("circom-build"))
+        .and_then(|dir| dir.as_str())
+        .unwrap_or(DEFAULT_CIRCOM_BUILD)
+        .to_string()
+}
+
+/// SHA-256 over the path and contents of every `.r1cs` and `.wasm` file under `dir`, in path
+/// order. The witness generators are transpiled from the `.wasm` files, so this covers the
+/// compiled constraints and the witness generation built into the binary.
+fn hash_circom_artifacts(dir: &Path) -> String {
+    let mut files = Vec::new();
+    collect_circom_artifacts(dir, &mut files);
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        let relative = file.strip_prefix(dir).unwrap_or(&file);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update(fs::read(&file).expect("circom artifacts are readable"));
+    }
+    hex::encode(hasher.finalize())
+}
+
+fn collect_circom_artifacts(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            collect_circom_artifacts(&path, files);
+        } else if matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("r1cs" | "wasm")
+        ) {
+            files.push(path);
+        }
+    }
 }
@@ -0,0 +1,302 @@
+//! Synthesis and allocation shared by the Prepare and Show circuits
+
+use crate::{
+    errors::{InputError, L8zkError},
+    metrics::SynthesisCounters,
+    utils::{calculate_jwt_output_indices, pack_shared_scalars, MultiClaimLayout},
+    Scalar,
+};
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError};
+use circom_scotia::{r1cs::R1CS, reader::load_r1cs};
+use rust_witness::BigInt;
+use std::{
+    any::Any,
+    borrow::Cow,
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::Path,
+};
+
+/// Run a rust-witness generator for `circuit`, turning a panic into
+/// `InputError::WitnessGenFailed` instead of unwinding through the caller. A witness that
+/// does not hold `expected_len` values, e.g. `witness_len` of the compiled circuit, fails the
+/// same way, since a generator built from another circom build would otherwise only show up
+/// as an unsatisfied proof.
+///
+/// Only panics are caught. A generator that aborts the process (e.g. from C code) still
+/// takes it down; run untrusted inputs in a separate process to contain that.
+pub fn guarded_witness(
+    circuit: &'static str,
+    expected_len: usize,
+    generate: impl FnOnce() -> Vec<BigInt>,
+) -> Result<Vec<BigInt>, InputError> {
+    let witness =
+        catch_unwind(AssertUnwindSafe(generate)).map_err(|panic| InputError::WitnessGenFailed {
+            circuit,
+            reason: panic_message(panic.as_ref()),
+        })?;
+    if witness.is_empty() {
+        return Err(InputError::WitnessGenFailed {
+            circuit,
+            reason: "the generator returned an empty witness".to_string(),
+        });
+    }
+    if witness.len() != expected_len {
+        return Err(InputError::WitnessGenFailed {
+            circuit,
+            reason: format!(
+                "the generator returned {} values but the circuit has {expected_len} wires",
+                witness.len()
+            ),
+        });
+    }
+    Ok(witness)
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+        (Some(message), _) => format!("generator panicked: {message}"),
+        (_, Some(message)) => format!("generator panicked: {message}"),
+        _ => "generator panicked".to_string(),
+    }
+}
+
+/// Load the compiled circuit at `path`, failing with `MissingArtifact` when it does not exist
+/// instead of letting `load_r1cs` panic
+pub fn load_r1cs_file(path: &Path) -> Result<R1CS<Scalar>, L8zkError> {
+    require_r1cs(path)?;
+    Ok(load_r1cs(path))
+}
+
+fn require_r1cs(path: &Path) -> Result<(), L8zkError> {
+    if path.exists() {
+        return Ok(());
+    }
+    Err(L8zkError::MissingArtifact {
+        path: path.display().to_string(),
+        hint: "compile the circom circuits, or point `L8ZK_CIRCOM_BUILD` at their build \
+               directory when building"
+            .to_string(),
+    })
+}
+
+/// `SynthesisError` for an R1CS that could not be read, e.g. from `load_r1cs_file`
+fn r1cs_unavailable(e: L8zkError) -> SynthesisError {
+    match e {
+        L8zkError::Io(e) => SynthesisError::IoError(e),
+        e => SynthesisError::IoError(io::Error::new(io::ErrorKind::NotFound, e.to_string())),
+    }
+}
+
+/// Number of witness values a circom witness for `r1cs` holds: one per wire, including the
+/// constant
+pub fn expected_witness_len(r1cs: &R1CS<Scalar>) -> usize {
+    r1cs.num_variables
+}
+
+/// R1CS section holding the field, wire and constraint counts
+const R1CS_HEADER_SECTION: u32 = 1;
+
+/// `expected_witness_len` of the compiled circuit at `path`, read from its header section
+/// alone so the constraints are not loaded just to check a witness
+pub fn r1cs_wire_count(path: &Path) -> Result<usize, L8zkError> {
+    require_r1cs(path)?;
+    let mut reader = BufReader::new(File::open(path)?);
+    let invalid = |reason: &str| {
+        L8zkError::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not an R1CS file: {reason}", path.display()),
+        ))
+    };
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"r1cs" {
+        return Err(invalid("bad magic"));
+    }
+    let _version = read_u32(&mut reader)?;
+    for _ in 0..read_u32(&mut reader)? {
+        let section = read_u32(&mut reader)?;
+        let size = read_u64(&mut reader)?;
+        if section != R1CS_HEADER_SECTION {
+            let size = i64::try_from(size).map_err(|_| invalid("section too large"))?;
+            reader.seek(SeekFrom::Current(size))?;
+            continue;
+        }
+        // Field element size and prime, then the wire count
+        let field_size = read_u32(&mut reader)?;
+        reader.seek(SeekFrom::Current(i64::from(field_size)))?;
+        return Ok(read_u32(&mut reader)? as usize);
+    }
+    Err(invalid("no header section"))
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Witness length for `guarded_witness`: from the `preloaded` R1CS when there is one,
+/// otherwise from the header of the one at `r1cs_path`
+pub fn witness_len(
+    preloaded: Option<&R1CS<Scalar>>,
+    r1cs_path: &Path,
+) -> Result<usize, SynthesisError> {
+    match preloaded {
+        Some(r1cs) => Ok(expected_witness_len(r1cs)),
+        None => r1cs_wire_count(r1cs_path).map_err(r1cs_unavailable),
+    }
+}
+
+/// Public outputs of `jwt.circom` after `ageClaim`: `KeyBindingX` and `KeyBindingY`
+const JWT_KEYBINDING_OUTPUTS: usize = 2;
+
+/// `ageClaim` length from `calculate_jwt_output_indices` next to the one the compiled
+/// `jwt.circom` R1CS actually has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputLayoutCheck {
+    pub computed: usize,
+    pub inferred: usize,
+}
+
+impl OutputLayoutCheck {
+    pub fn matches(&self) -> bool {
+        self.computed == self.inferred
+    }
+}
+
+/// Compare the decoded claim length computed for `max_claims_length` with the size of the
+/// `ageClaim` output region of the compiled `jwt.circom` in `r1cs`.
+///
+/// `jwt.circom` has no public inputs, so its public wires are the constant, `ageClaim` and the
+/// two key binding coordinates; the region size is what remains after the other three.
+/// Fails with `UnalignedClaimLength` when `max_claims_length` is not a multiple of 4.
+pub fn check_jwt_output_layout(
+    r1cs: &R1CS<Scalar>,
+    max_claims_length: usize,
+) -> Result<OutputLayoutCheck, InputError> {
+    Ok(OutputLayoutCheck {
+        computed: calculate_jwt_output_indices(0, max_claims_length)?.age_claim_len,
+        inferred: r1cs.num_inputs.saturating_sub(1 + JWT_KEYBINDING_OUTPUTS),
+    })
+}
+
+/// Load the compiled circuit at `r1cs_path` and synthesize it into `cs`, counting its
+/// constraints and variables. A `preloaded` R1CS is used instead of reading `r1cs_path`; a
+/// missing `r1cs_path` fails with `SynthesisError::IoError` rather than panicking.
+/// `witness` is `None` during setup, when only the constraint structure is needed; otherwise
+/// it must hold `expected_witness_len` values. A `preloaded` R1CS is borrowed, not copied.
+pub fn synthesize_r1cs<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    circuit: &'static str,
+    r1cs_path: &Path,
+    preloaded: Option<&R1CS<Scalar>>,
+    witness: Option<Vec<Scalar>>,
+    counters: &SynthesisCounters,
+) -> Result<(), SynthesisError> {
+    let r1cs = match preloaded {
+        Some(r1cs) => Cow::Borrowed(r1cs),
+        None => Cow::Owned(load_r1cs_file(r1cs_path).map_err(r1cs_unavailable)?),
+    };
+    if let Some(witness) = &witness {
+        let expected = expected_witness_len(&r1cs);
+        if witness.len() != expected {
+            return Err(InputError::WitnessGenFailed {
+                circuit,
+                reason: format!(
+                    "witness has {} values but the circuit has {expected} wires",
+                    witness.len()
+                ),
+            }
+            .into());
+        }
+    }
+    counters.add_constraints(r1cs.constraints.len());
+    counters.add_variables(r1cs.num_variables);
+    enforce_r1cs(cs, &r1cs, witness.as_deref())
+}
+
+/// `circom_scotia::synthesize` over a borrowed R1CS: allocate every wire but the constant
+/// (public ones first, like circom numbers them) and enforce each constraint over them
+fn enforce_r1cs<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    r1cs: &R1CS<Scalar>,
+    witness: Option<&[Scalar]>,
+) -> Result<(), SynthesisError> {
+    let value = |wire: usize| {
+        witness
+            .map(|witness| witness[wire])
+            .ok_or(SynthesisError::AssignmentMissing)
+    };
+
+    let mut wires = Vec::with_capacity(r1cs.num_variables.saturating_sub(1));
+    for wire in 1..r1cs.num_inputs {
+        wires.push(AllocatedNum::alloc(
+            cs.namespace(|| format!("public_{wire}")),
+            || value(wire),
+        )?);
+    }
+    for aux in 0..r1cs.num_aux {
+        let wire = r1cs.num_inputs + aux;
+        wires.push(AllocatedNum::alloc(
+            cs.namespace(|| format!("aux_{aux}")),
+            || value(wire),
+        )?);
+    }
+
+    let lc = |terms: &[(usize, Scalar)]| {
+        terms
+            .iter()
+            .fold(LinearCombination::zero(), |lc, &(wire, coeff)| match wire {
+                0 => lc + (coeff, CS::one()),
+                _ => lc + (coeff, wires[wire - 1].get_variable()),
+            })
+    };
+    for (i, (a, b, c)) in r1cs.constraints.iter().enumerate() {
+        cs.enforce(
+            || format!("constraint {i}"),
+            |_| lc(a),
+            |_| lc(b),
+            |_| lc(c),
+        );
+    }
+    Ok(())
+}
+
+/// Allocate the values committed to `comm_W_shared` in the order of `MultiClaimLayout`:
+/// `KeyBindingX`, `KeyBindingY`, then one `Claim{idx}` per scalar of the disclosed `claims`.
+///
+/// Both circuits must allocate these in the same order for their shared commitments to link,
+/// so they go through this single function.
+pub fn alloc_shared_values<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    keybinding_x: Scalar,
+    keybinding_y: Scalar,
+    claims: &[&[Scalar]],
+    counters: &SynthesisCounters,
+) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+    let (packed, _layout) = pack_shared_scalars(keybinding_x, keybinding_y, claims);
+
+    let mut shared_values = Vec::with_capacity(packed.len());
+    for (index, &value) in packed.iter().enumerate() {
+        let name = match index {
+            MultiClaimLayout::KEYBINDING_X_INDEX => "KeyBindingX".to_string(),
+            MultiClaimLayout::KEYBINDING_Y_INDEX => "KeyBindingY".to_string(),
+            _ => format!("Claim{}", index - MultiClaimLayout::CLAIMS_START),
+        };
+        shared_values.push(AllocatedNum::alloc(cs.namespace(|| name), move || {
+            Ok(value)
+        })?);
+    }
+
+    counters.add_shared_values(shared_values.len());
+    Ok(shared_values)
+}
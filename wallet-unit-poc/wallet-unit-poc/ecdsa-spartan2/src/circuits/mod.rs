@@ -1,2 +1,3 @@
+pub mod common;
 pub mod prepare_circuit;
 pub mod show_circuit;
@@ -1,42 +1,153 @@
 use crate::{
-    prover::generate_prepare_witness,
-    utils::{compute_prepare_shared_scalars, PrepareSharedScalars},
+    circuits::common::{
+        alloc_shared_values, check_jwt_output_layout, load_r1cs_file, synthesize_r1cs, witness_len,
+        OutputLayoutCheck,
+    },
+    errors::{InputError, L8zkError},
+    metrics::SynthesisCounters,
+    prover::generate_prepare_witness_from_json,
+    secret::SecretScalars,
+    utils::{
+        compute_prepare_shared_scalars, InputSource, PrepareSharedScalars, DEFAULT_MAX_INPUT_SIZE,
+    },
     Scalar, E,
 };
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
-use circom_scotia::{reader::load_r1cs, synthesize};
+use circom_scotia::r1cs::R1CS;
 use serde_json::Value;
 use spartan2::traits::circuit::SpartanCircuit;
-use std::{any::type_name, env::current_dir, fs::File, path::PathBuf};
+use std::{
+    env::current_dir,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
 
 rust_witness::witness!(jwt);
 
+/// Set to any value to have the CLI run `PrepareCircuit::check_output_layout` before using
+/// the Prepare circuit
+pub const CHECK_LAYOUT_ENV: &str = "L8ZK_CHECK_LAYOUT";
+
 // jwt.circom
 #[derive(Debug, Clone, Default)]
 pub struct PrepareCircuit {
-    input_path: Option<PathBuf>,
+    // Falls back to the default Prepare input file when unset
+    input: Option<InputSource>,
+    // Falls back to `DEFAULT_MAX_INPUT_SIZE` when unset
+    max_input_size: Option<u64>,
+    // Reject input JSON that repeats a key
+    strict_json: bool,
+    // Shared between clones so the witness is generated once, possibly ahead of proving
+    witness: Arc<OnceLock<SecretScalars>>,
+    // Used instead of reading `r1cs_path` on every synthesis when set
+    r1cs: Option<Arc<R1CS<Scalar>>>,
+    // Shared between clones so counts from every phase land in one place
+    counters: Arc<SynthesisCounters>,
 }
 
 impl PrepareCircuit {
     pub fn new<P: Into<Option<PathBuf>>>(path: P) -> Self {
+        Self::from_input(path.into().map(InputSource::Path))
+    }
+
+    /// Read the input from `input`, e.g. inline JSON, or the default input file when `None`
+    pub fn from_input(input: Option<InputSource>) -> Self {
         Self {
-            input_path: path.into(),
+            input,
+            max_input_size: None,
+            strict_json: false,
+            witness: Arc::default(),
+            r1cs: None,
+            counters: Arc::default(),
         }
     }
 
-    fn input_path_absolute(&self, cwd: &PathBuf) -> Option<PathBuf> {
-        self.input_path.as_ref().map(|p| {
-            if p.is_absolute() {
-                p.clone()
-            } else {
-                cwd.join(p)
+    /// Reject input JSON files larger than `limit` bytes instead of the default limit
+    pub fn with_max_input_size(mut self, limit: u64) -> Self {
+        self.max_input_size = Some(limit);
+        self
+    }
+
+    /// Fail with `InputError::DuplicateField` if the input JSON repeats a key
+    pub fn with_strict_json(mut self) -> Self {
+        self.strict_json = true;
+        self
+    }
+
+    /// Synthesize from an R1CS loaded once by the caller (e.g. from `r1cs_path` or embedded
+    /// bytes) instead of reading `jwt.r1cs` on every synthesis
+    pub fn with_r1cs(mut self, r1cs: Arc<R1CS<Scalar>>) -> Self {
+        self.r1cs = Some(r1cs);
+        self
+    }
+
+    fn load_inputs(&self, cwd: &PathBuf) -> Result<Value, InputError> {
+        let default_input = InputSource::Path(PathBuf::from("../circom/inputs/jwt/default.json"));
+        let limit = self.max_input_size.unwrap_or(DEFAULT_MAX_INPUT_SIZE);
+        self.input
+            .as_ref()
+            .unwrap_or(&default_input)
+            .read(cwd, limit, self.strict_json)
+    }
+
+    /// Location of the compiled `jwt.r1cs` in the circom build directory chosen at build time,
+    /// relative to the crate directory `cwd` unless that directory is absolute
+    pub fn r1cs_path(cwd: &Path) -> PathBuf {
+        cwd.join(env!("L8ZK_CIRCOM_BUILD"))
+            .join("jwt/jwt_js/jwt.r1cs")
+    }
+
+    /// Check `calculate_jwt_output_indices` against the compiled `jwt.r1cs`, taking
+    /// `maxClaimsLength` from the length of the input's claim rows. A recompiled circuit with
+    /// another `maxClaimsLength` than the inputs were generated for shows up as a mismatch.
+    /// Fails with `MissingArtifact` when no R1CS was preloaded and `jwt.r1cs` does not exist,
+    /// and with `UnalignedClaimLength` when the claim rows are not a multiple of 4 long.
+    pub fn check_output_layout(&self) -> Result<OutputLayoutCheck, L8zkError> {
+        let cwd = current_dir()?;
+        let json_value = self.load_inputs(&cwd)?;
+        let max_claims_length = json_value
+            .get("claims")
+            .and_then(|claims| claims.get(0))
+            .and_then(|row| row.as_array())
+            .ok_or(SynthesisError::AssignmentMissing)?
+            .len();
+
+        let check = match &self.r1cs {
+            Some(r1cs) => check_jwt_output_layout(r1cs, max_claims_length),
+            None => {
+                check_jwt_output_layout(&load_r1cs_file(&Self::r1cs_path(&cwd))?, max_claims_length)
             }
-        })
+        };
+        Ok(check?)
     }
 
-    fn resolve_input_json(&self, cwd: &PathBuf) -> PathBuf {
-        self.input_path_absolute(cwd)
-            .unwrap_or_else(|| cwd.join("../circom/inputs/jwt/default.json"))
+    /// Constraint and allocation counts from every synthesis of this circuit and its clones
+    pub fn counters(&self) -> &SynthesisCounters {
+        &self.counters
+    }
+
+    /// Generate the witness now and keep it for proving, e.g. on another thread while the
+    /// Show witness is generated
+    pub fn generate_witness(&self) -> Result<(), SynthesisError> {
+        self.witness().map(|_| ())
+    }
+
+    /// Generate the witness on first use, then reuse it
+    fn witness(&self) -> Result<&SecretScalars, SynthesisError> {
+        if let Some(witness) = self.witness.get() {
+            return Ok(witness);
+        }
+
+        let cwd = current_dir().unwrap();
+        let json_value = self.load_inputs(&cwd)?;
+        let wires = witness_len(self.r1cs.as_deref(), &Self::r1cs_path(&cwd))?;
+        let witness = generate_prepare_witness_from_json(&json_value, wires)?;
+
+        let _ = self.witness.set(witness.into());
+        Ok(self
+            .witness
+            .get()
+            .expect("prepare witness was just generated"))
     }
 }
 
@@ -49,29 +160,27 @@ impl SpartanCircuit<E> for PrepareCircuit {
         _: Option<&[Scalar]>,
     ) -> Result<(), SynthesisError> {
         let cwd = current_dir().unwrap();
-        let root = cwd.join("../circom");
-        let witness_dir = root.join("build/jwt/jwt_js");
-        let r1cs = witness_dir.join("jwt.r1cs");
+        let r1cs = Self::r1cs_path(&cwd);
 
-        // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
-        // During setup, we only need constraint structure instead of actual witness values
-        let cs_type = type_name::<CS>();
-        let is_setup_phase = cs_type.contains("ShapeCS");
+        // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment).
+        // Only witness generators carry assignments; during setup we only need the
+        // constraint structure instead of actual witness values
+        let is_setup_phase = !cs.is_witness_generator();
 
         if is_setup_phase {
-            let r1cs = load_r1cs(r1cs);
             // Pass None for witness during setup
-            synthesize(cs, r1cs, None)?;
-            return Ok(());
+            return synthesize_r1cs(cs, "jwt", &r1cs, self.r1cs.as_deref(), None, &self.counters);
         }
 
-        // Generate witness using the dedicated function
-        let input_path = self.input_path_absolute(&cwd);
-        let witness = generate_prepare_witness(input_path.as_ref().map(|p| p.as_path()))?;
-
-        let r1cs = load_r1cs(r1cs);
-        synthesize(cs, r1cs, Some(witness))?;
-        Ok(())
+        let witness = self.witness()?.to_vec();
+        synthesize_r1cs(
+            cs,
+            "jwt",
+            &r1cs,
+            self.r1cs.as_deref(),
+            Some(witness),
+            &self.counters,
+        )
     }
 
     fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
@@ -82,37 +191,22 @@ impl SpartanCircuit<E> for PrepareCircuit {
         cs: &mut CS,
     ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
         let cwd = current_dir().unwrap();
-        let json_path = self.resolve_input_json(&cwd);
-
-        let json_file = File::open(&json_path).map_err(|_| SynthesisError::AssignmentMissing)?;
-
-        let json_value: Value =
-            serde_json::from_reader(json_file).map_err(|_| SynthesisError::AssignmentMissing)?;
+        let json_value = self.load_inputs(&cwd)?;
 
         let PrepareSharedScalars {
             keybinding_x,
             keybinding_y,
             claim_scalars,
         } = compute_prepare_shared_scalars(&json_value)?;
+        let claim_scalars = SecretScalars::from(claim_scalars);
 
-        let keybinding_x_alloc =
-            AllocatedNum::alloc(cs.namespace(|| "KeyBindingX"), || Ok(keybinding_x))?;
-        let keybinding_y_alloc =
-            AllocatedNum::alloc(cs.namespace(|| "KeyBindingY"), || Ok(keybinding_y))?;
-
-        let mut shared_values = Vec::with_capacity(2 + claim_scalars.len());
-        shared_values.push(keybinding_x_alloc);
-        shared_values.push(keybinding_y_alloc);
-
-        for (idx, claim_scalar) in claim_scalars.into_iter().enumerate() {
-            let claim_alloc =
-                AllocatedNum::alloc(cs.namespace(|| format!("Claim{idx}")), move || {
-                    Ok(claim_scalar)
-                })?;
-            shared_values.push(claim_alloc);
-        }
-
-        Ok(shared_values)
+        alloc_shared_values(
+            cs,
+            keybinding_x,
+            keybinding_y,
+            &[&claim_scalars[..]],
+            &self.counters,
+        )
     }
     fn precommitted<CS: ConstraintSystem<Scalar>>(
         &self,
@@ -1,44 +1,244 @@
-use crate::{utils::*, Scalar, E};
+use crate::{
+    circuits::common::{alloc_shared_values, guarded_witness, synthesize_r1cs, witness_len},
+    metrics::SynthesisCounters,
+    secret::SecretScalars,
+    utils::*,
+    Scalar, E,
+};
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
-use circom_scotia::{reader::load_r1cs, synthesize};
+use circom_scotia::r1cs::R1CS;
+use ff::Field;
+use rust_witness::BigInt;
 use serde_json::Value;
 use spartan2::traits::circuit::SpartanCircuit;
-use std::{any::type_name, env::current_dir, fs::File, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env::current_dir,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
 use tracing::info;
 
 rust_witness::witness!(show);
 
+/// Witness index of show.circom's `ageAbove18` output, right after the constant one
+const AGE_ABOVE_18_INDEX: usize = 1;
+
+/// Earliest `(year, month, day)` in `date_range` at which show.circom's `ageAbove18` output is
+/// 1 for `show_input`, or `None` if it is 0 for every date.
+///
+/// Each date is substituted for `currentYear`/`currentMonth`/`currentDay` and only the witness
+/// is generated, so the sweep costs one witness generation per day rather than a prove. Dates
+/// are walked one calendar day at a time and the result is whatever the circuit's age logic
+/// says, including any off-by-one around the birthday.
+pub fn find_age_boundary(
+    show_input: &Value,
+    date_range: RangeInclusive<(u64, u64, u64)>,
+) -> Result<Option<(u64, u64, u64)>, SynthesisError> {
+    let mut witness_inputs = parse_show_inputs(show_input)?;
+    witness_inputs.remove(SHOW_NONCE_FIELD);
+    let wires = witness_len(None, &ShowCircuit::r1cs_path(&current_dir().unwrap()))?;
+
+    let mut date = *date_range.start();
+    while date <= *date_range.end() {
+        let (year, month, day) = date;
+        witness_inputs.insert("currentYear".to_string(), vec![BigInt::from(year)]);
+        witness_inputs.insert("currentMonth".to_string(), vec![BigInt::from(month)]);
+        witness_inputs.insert("currentDay".to_string(), vec![BigInt::from(day)]);
+
+        let inputs = witness_inputs.clone();
+        let witness = guarded_witness("show", wires, || show_witness(inputs))?;
+        if witness.get(AGE_ABOVE_18_INDEX) == Some(&BigInt::from(1u8)) {
+            return Ok(Some(date));
+        }
+        date = next_day(date);
+    }
+    Ok(None)
+}
+
+/// The Gregorian calendar day after `(year, month, day)`
+fn next_day((year, month, day): (u64, u64, u64)) -> (u64, u64, u64) {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if day < days_in_month {
+        (year, month, day + 1)
+    } else if month < 12 {
+        (year, month + 1, 1)
+    } else {
+        (year + 1, 1, 1)
+    }
+}
+
+/// Show inputs parsed once and shared by `shared` and `synthesize`
+#[derive(Debug)]
+struct ShowInputs {
+    witness_inputs: HashMap<String, Vec<BigInt>>,
+    keybinding_x: Scalar,
+    keybinding_y: Scalar,
+    claim_scalars: SecretScalars,
+    // Zero when the input has no nonce
+    nonce: Scalar,
+}
+
 // show.circom
 #[derive(Debug, Clone, Default)]
 pub struct ShowCircuit {
-    input_path: Option<PathBuf>,
+    // Falls back to the default Show input file when unset
+    input: Option<InputSource>,
+    // Falls back to `DEFAULT_MAX_INPUT_SIZE` when unset
+    max_input_size: Option<u64>,
+    // Reject input JSON that repeats a key
+    strict_json: bool,
+    // Public nonce supplied at prove time, used instead of the input's `nonce`
+    nonce: Option<Scalar>,
+    // Shared between clones so the input JSON is read and parsed once per prove
+    parsed: Arc<OnceLock<ShowInputs>>,
+    // Shared between clones so the witness is generated once, possibly ahead of proving
+    witness: Arc<OnceLock<SecretScalars>>,
+    // Used instead of reading `r1cs_path` on every synthesis when set
+    r1cs: Option<Arc<R1CS<Scalar>>>,
+    // Shared between clones so counts from every phase land in one place
+    counters: Arc<SynthesisCounters>,
 }
 
 impl ShowCircuit {
     pub fn new<P: Into<Option<PathBuf>>>(path: P) -> Self {
+        Self::from_input(path.into().map(InputSource::Path))
+    }
+
+    /// Read the input from `input`, e.g. inline JSON, or the default input file when `None`
+    pub fn from_input(input: Option<InputSource>) -> Self {
         Self {
-            input_path: path.into(),
+            input,
+            max_input_size: None,
+            strict_json: false,
+            nonce: None,
+            parsed: Arc::default(),
+            witness: Arc::default(),
+            r1cs: None,
+            counters: Arc::default(),
         }
     }
 
-    fn input_path_absolute(&self, cwd: &PathBuf) -> PathBuf {
-        self.input_path
-            .as_ref()
-            .map(|p| {
-                if p.is_absolute() {
-                    p.clone()
-                } else {
-                    cwd.join(p)
-                }
-            })
-            .unwrap_or_else(|| cwd.join("../circom/inputs/show/default.json"))
+    /// Reject input JSON files larger than `limit` bytes instead of the default limit
+    pub fn with_max_input_size(mut self, limit: u64) -> Self {
+        self.max_input_size = Some(limit);
+        self
+    }
+
+    /// Fail with `InputError::DuplicateField` if the input JSON repeats a key
+    pub fn with_strict_json(mut self) -> Self {
+        self.strict_json = true;
+        self
+    }
+
+    /// Expose `nonce` as the public input instead of the input file's `nonce`, so one input can
+    /// be proved for each verifier challenge
+    pub fn with_nonce(mut self, nonce: Scalar) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// The public nonce: the one given to `with_nonce`, else the input's (zero when unset)
+    fn nonce(&self) -> Result<Scalar, SynthesisError> {
+        match self.nonce {
+            Some(nonce) => Ok(nonce),
+            None => Ok(self.inputs()?.nonce),
+        }
+    }
+
+    /// Synthesize from an R1CS loaded once by the caller (e.g. from `r1cs_path` or embedded
+    /// bytes) instead of reading `show.r1cs` on every synthesis
+    pub fn with_r1cs(mut self, r1cs: Arc<R1CS<Scalar>>) -> Self {
+        self.r1cs = Some(r1cs);
+        self
+    }
+
+    /// Constraint and allocation counts from every synthesis of this circuit and its clones
+    pub fn counters(&self) -> &SynthesisCounters {
+        &self.counters
+    }
+
+    /// Location of the compiled `show.r1cs` in the circom build directory chosen at build time,
+    /// relative to the crate directory `cwd` unless that directory is absolute
+    pub fn r1cs_path(cwd: &Path) -> PathBuf {
+        cwd.join(env!("L8ZK_CIRCOM_BUILD"))
+            .join("show/show_js/show.r1cs")
     }
 
     fn load_inputs(&self, cwd: &PathBuf) -> Result<Value, SynthesisError> {
-        let path = self.input_path_absolute(cwd);
-        info!("Loading show inputs from {}", path.display());
-        let file = File::open(&path).map_err(|_| SynthesisError::AssignmentMissing)?;
-        serde_json::from_reader(file).map_err(|_| SynthesisError::AssignmentMissing)
+        let default_input = InputSource::Path(PathBuf::from("../circom/inputs/show/default.json"));
+        let input = self.input.as_ref().unwrap_or(&default_input);
+        info!("Loading show inputs from {}", input);
+        let limit = self.max_input_size.unwrap_or(DEFAULT_MAX_INPUT_SIZE);
+        input.read(cwd, limit, self.strict_json).map_err(Into::into)
+    }
+
+    /// Parse the inputs and derive the shared scalars on first use, then reuse them
+    fn inputs(&self) -> Result<&ShowInputs, SynthesisError> {
+        if let Some(inputs) = self.parsed.get() {
+            return Ok(inputs);
+        }
+
+        let cwd = current_dir().unwrap();
+        let json_value = self.load_inputs(&cwd)?;
+
+        // Parse inputs using declarative field definitions
+        let mut witness_inputs = parse_show_inputs(&json_value)?;
+        // The nonce is bound by this circuit, not by show.circom, so keep it out of the witness
+        let nonce = match witness_inputs.remove(SHOW_NONCE_FIELD) {
+            Some(mut values) => bigint_to_scalar(values.remove(0))?,
+            None => Scalar::ZERO,
+        };
+
+        let PrepareSharedScalars {
+            keybinding_x,
+            keybinding_y,
+            mut claim_scalars,
+        } = show_shared_scalars(&witness_inputs)?;
+        disclose_show_claim(&json_value, &mut claim_scalars)?;
+
+        let _ = self.parsed.set(ShowInputs {
+            witness_inputs,
+            keybinding_x,
+            keybinding_y,
+            claim_scalars: claim_scalars.into(),
+            nonce,
+        });
+        Ok(self
+            .parsed
+            .get()
+            .expect("show inputs were just initialised"))
+    }
+
+    /// Generate the witness now and keep it for proving, e.g. on another thread while the
+    /// Prepare witness is generated
+    pub fn generate_witness(&self) -> Result<(), SynthesisError> {
+        self.witness().map(|_| ())
+    }
+
+    /// Generate the witness with rust-witness on first use, then reuse it
+    fn witness(&self) -> Result<&SecretScalars, SynthesisError> {
+        if let Some(witness) = self.witness.get() {
+            return Ok(witness);
+        }
+
+        let witness_inputs = self.inputs()?.witness_inputs.clone();
+        let wires = witness_len(
+            self.r1cs.as_deref(),
+            &Self::r1cs_path(&current_dir().unwrap()),
+        )?;
+        let witness_bigint = guarded_witness("show", wires, || show_witness(witness_inputs))?;
+        let witness: Vec<Scalar> = convert_bigint_to_scalar(witness_bigint)?;
+
+        let _ = self.witness.set(witness.into());
+        Ok(self.witness.get().expect("show witness was just generated"))
     }
 }
 
@@ -51,74 +251,69 @@ impl SpartanCircuit<E> for ShowCircuit {
         _: Option<&[Scalar]>,
     ) -> Result<(), SynthesisError> {
         let cwd = current_dir().unwrap();
-        let root = cwd.join("../circom");
-        let witness_dir = root.join("build/show/show_js");
-        let r1cs = witness_dir.join("show.r1cs");
-        let json_value = self.load_inputs(&cwd)?;
+        let r1cs = Self::r1cs_path(&cwd);
+        let nonce_value = self.nonce()?;
 
-        // Parse inputs using declarative field definitions
-        let inputs = parse_show_inputs(&json_value)?;
-
-        // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
-        // During setup, we only need constraint structure instead of actual witness values
-        let cs_type = type_name::<CS>();
-        let is_setup_phase = cs_type.contains("ShapeCS");
+        // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment).
+        // Only witness generators carry assignments; during setup we only need the
+        // constraint structure instead of actual witness values
+        let is_setup_phase = !cs.is_witness_generator();
 
         if is_setup_phase {
-            let r1cs = load_r1cs(r1cs);
             // Pass None for witness during setup
-            synthesize(cs, r1cs, None)?;
-            return Ok(());
+            synthesize_r1cs(
+                &mut *cs,
+                "show",
+                &r1cs,
+                self.r1cs.as_deref(),
+                None,
+                &self.counters,
+            )?;
+        } else {
+            let witness = self.witness()?.to_vec();
+            synthesize_r1cs(
+                &mut *cs,
+                "show",
+                &r1cs,
+                self.r1cs.as_deref(),
+                Some(witness),
+                &self.counters,
+            )?;
         }
 
-        // Generate witness using native Rust (rust-witness)
-        let witness_bigint = show_witness(inputs);
-        let witness: Vec<Scalar> = convert_bigint_to_scalar(witness_bigint)?;
-
-        let r1cs = load_r1cs(r1cs);
-        synthesize(cs, r1cs, Some(witness))?;
+        // Expose the nonce as the public input. The constraint gives its column a non-zero
+        // entry, so the proof does not verify against any other nonce.
+        let nonce = AllocatedNum::alloc_input(cs.namespace(|| "nonce"), || Ok(nonce_value))?;
+        cs.enforce(
+            || "bind nonce",
+            |lc| lc + nonce.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + nonce.get_variable(),
+        );
+        self.counters.add_constraints(1);
         Ok(())
     }
 
     fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
-        Ok(vec![])
+        Ok(vec![self.nonce()?])
     }
     fn shared<CS: ConstraintSystem<Scalar>>(
         &self,
         cs: &mut CS,
     ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
-        let cwd = current_dir().unwrap();
-        let json_value = self.load_inputs(&cwd)?;
-
-        let inputs = parse_show_inputs(&json_value)?;
-        let keybinding_x_bigint = inputs.get("deviceKeyX").unwrap()[0].clone();
-        let keybinding_y_bigint = inputs.get("deviceKeyY").unwrap()[0].clone();
-        let claim_bigints = inputs
-            .get("claim")
-            .cloned()
-            .ok_or(SynthesisError::AssignmentMissing)?;
-
-        let keybinding_x = bigint_to_scalar(keybinding_x_bigint)?;
-        let keybinding_y = bigint_to_scalar(keybinding_y_bigint)?;
-        let claim_scalars = convert_bigint_to_scalar(claim_bigints)?;
-
-        let kb_x = AllocatedNum::alloc(cs.namespace(|| "KeyBindingX"), || Ok(keybinding_x))?;
-        let kb_y = AllocatedNum::alloc(cs.namespace(|| "KeyBindingY"), || Ok(keybinding_y))?;
-
-        let mut shared_values = Vec::with_capacity(2 + claim_scalars.len());
-        shared_values.push(kb_x);
-        shared_values.push(kb_y);
-
-        for (idx, claim_scalar) in claim_scalars.into_iter().enumerate() {
-            let claim_value = claim_scalar;
-            let claim_alloc =
-                AllocatedNum::alloc(cs.namespace(|| format!("Claim{idx}")), move || {
-                    Ok(claim_value)
-                })?;
-            shared_values.push(claim_alloc);
-        }
-
-        Ok(shared_values)
+        let ShowInputs {
+            keybinding_x,
+            keybinding_y,
+            claim_scalars,
+            ..
+        } = self.inputs()?;
+        alloc_shared_values(
+            cs,
+            *keybinding_x,
+            *keybinding_y,
+            &[&claim_scalars[..]],
+            &self.counters,
+        )
     }
     fn precommitted<CS: ConstraintSystem<Scalar>>(
         &self,
@@ -0,0 +1,935 @@
+//! Command-line parsing for the `ecdsa-spartan2` binary.
+//!
+//! Kept in the library so the command surface, including the legacy aliases, can be tested
+//! without spawning the binary.
+
+use std::{path::PathBuf, str::FromStr};
+
+use crate::{config::Config, prover::ProofNaming, utils::InputSource};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitKind {
+    Prepare,
+    Show,
+    /// `auto <action>`: the circuit is picked from the input with `detect_circuit_kind`
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitAction {
+    Run,
+    Setup,
+    SetupAndProve,
+    Prove,
+    ProveDir,
+    Verify,
+    Reblind,
+    GenerateSharedBlinds,
+    Benchmark,
+    BenchmarkVerify,
+    MinimizeProof,
+    VerifyDir,
+    InspectInstance,
+    CheckLink,
+    CheckWitness,
+    DeriveVk,
+    EstimateProof,
+    DumpShape,
+    Doctor,
+    ExportSchema,
+    ShowHelp,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Prometheus,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "prometheus" => Ok(OutputFormat::Prometheus),
+            other => Err(format!(
+                "Unknown format '{other}'. Expected one of table|json|prometheus."
+            )),
+        }
+    }
+}
+
+/// Verifications `benchmark-verify` runs without `--iterations`
+pub const DEFAULT_VERIFY_ITERATIONS: usize = 1000;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommandOptions {
+    /// `--input <path>` or `--input-json <json>`
+    pub input: Option<InputSource>,
+    pub dir: Option<PathBuf>,
+    pub vk: Option<PathBuf>,
+    pub pk: Option<PathBuf>,
+    /// `--proof`, or `--in` for minimize-proof
+    pub proof: Option<PathBuf>,
+    /// `--witness`, the saved witness to compare (check-witness)
+    pub witness: Option<PathBuf>,
+    /// `--seed <hex>` for deterministic shared blinds (generate_shared_blinds)
+    pub seed: Option<Vec<u8>>,
+    /// `--expected-nonce <decimal>`, the nonce the verifier issued (show verify)
+    pub expected_nonce: Option<String>,
+    /// `--iterations`; `DEFAULT_VERIFY_ITERATIONS` when unset
+    pub iterations: Option<usize>,
+    pub out: Option<PathBuf>,
+    pub out_dir: Option<PathBuf>,
+    pub naming: ProofNaming,
+    pub format: OutputFormat,
+    pub metrics_out: Option<PathBuf>,
+    pub keep_going: bool,
+    pub verify_after: bool,
+    pub no_save: bool,
+    /// `--estimate`: time setup only and extrapolate the other steps (benchmark)
+    pub estimate: bool,
+    pub prepare_input: Option<PathBuf>,
+    pub show_input: Option<PathBuf>,
+    pub help: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCommand {
+    pub circuit: CircuitKind,
+    pub action: CircuitAction,
+    pub options: CommandOptions,
+}
+
+/// Parse the command-line arguments (without the program name) into a command.
+/// Errors are returned as user-facing messages; nothing here exits the process.
+pub fn parse_command(args: &[String]) -> Result<ParsedCommand, String> {
+    parse_command_with_config(args, None)
+}
+
+/// Parse the command-line arguments, filling options the flags leave unset from `config`
+/// before checking that the command has every option it requires
+pub fn parse_command_with_config(
+    args: &[String],
+    config: Option<&Config>,
+) -> Result<ParsedCommand, String> {
+    if args.is_empty() {
+        return Err("No command provided".into());
+    }
+
+    let mut command = match args[0].as_str() {
+        "-h" | "--help" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare,
+            action: CircuitAction::ShowHelp,
+            options: CommandOptions::default(),
+        }),
+        "prepare" => parse_circuit_command(CircuitKind::Prepare, &args[1..]),
+        "show" => parse_circuit_command(CircuitKind::Show, &args[1..]),
+        // The circuit is detected from the input, so `check_required_options` requires one
+        "auto" => parse_circuit_command(CircuitKind::Auto, &args[1..]),
+        "benchmark" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Benchmark runs both circuits, but we need to pick one for the enum
+            action: CircuitAction::Benchmark,
+            options: parse_benchmark_options(&args[1..])?,
+        }),
+        "benchmark-verify" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // The verifying key determines the circuit
+            action: CircuitAction::BenchmarkVerify,
+            options: parse_benchmark_verify_options(&args[1..])?,
+        }),
+        "minimize-proof" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // The verifying key determines the circuit
+            action: CircuitAction::MinimizeProof,
+            options: parse_minimize_proof_options(&args[1..])?,
+        }),
+        "verify-dir" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Proofs are verified against the supplied key, regardless of circuit
+            action: CircuitAction::VerifyDir,
+            options: parse_verify_dir_options(&args[1..])?,
+        }),
+        "inspect-instance" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // The instance file determines what is inspected
+            action: CircuitAction::InspectInstance,
+            options: parse_inspect_options(&args[1..])?,
+        }),
+        "check-link" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Compares the inputs of both circuits
+            action: CircuitAction::CheckLink,
+            options: parse_check_link_options(&args[1..])?,
+        }),
+        "check-witness" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Only the Prepare witness is checked
+            action: CircuitAction::CheckWitness,
+            options: parse_check_witness_options(&args[1..])?,
+        }),
+        "derive-vk" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // The proving key determines the circuit
+            action: CircuitAction::DeriveVk,
+            options: parse_derive_vk_options(&args[1..])?,
+        }),
+        "doctor" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Checks the artifacts of both circuits
+            action: CircuitAction::Doctor,
+            options: ensure_no_options(&args[1..])?,
+        }),
+        "export-schema" => parse_export_schema_command(&args[1..]),
+        "setup_prepare" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare,
+            action: CircuitAction::Setup,
+            options: parse_options(&args[1..])?,
+        }),
+        "setup_show" => Ok(ParsedCommand {
+            circuit: CircuitKind::Show,
+            action: CircuitAction::Setup,
+            options: parse_options(&args[1..])?,
+        }),
+        "prove_prepare" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare,
+            action: CircuitAction::Prove,
+            options: parse_prove_options(&args[1..])?,
+        }),
+        "prove_show" => Ok(ParsedCommand {
+            circuit: CircuitKind::Show,
+            action: CircuitAction::Prove,
+            options: parse_prove_options(&args[1..])?,
+        }),
+        "verify_prepare" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare,
+            action: CircuitAction::Verify,
+            options: parse_output_options(&args[1..])?,
+        }),
+        "verify_show" => Ok(ParsedCommand {
+            circuit: CircuitKind::Show,
+            action: CircuitAction::Verify,
+            options: parse_output_options(&args[1..])?,
+        }),
+        "reblind_prepare" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare,
+            action: CircuitAction::Reblind,
+            options: parse_reblind_options(&args[1..])?,
+        }),
+        "reblind_show" => Ok(ParsedCommand {
+            circuit: CircuitKind::Show,
+            action: CircuitAction::Reblind,
+            options: parse_reblind_options(&args[1..])?,
+        }),
+        "generate_shared_blinds" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare,
+            action: CircuitAction::GenerateSharedBlinds,
+            options: parse_shared_blinds_options(&args[1..])?,
+        }),
+        other => Err(format!("Unknown command '{other}'")),
+    }?;
+
+    // A help flag anywhere in the options takes precedence over the command itself
+    if command.options.help {
+        command.action = CircuitAction::ShowHelp;
+        return Ok(command);
+    }
+
+    if let Some(config) = config {
+        config.apply(command.action, &mut command.options, args);
+    }
+    check_required_options(&command)?;
+
+    Ok(command)
+}
+
+/// Options a command cannot run without, which either a flag or the config file must supply
+fn check_required_options(command: &ParsedCommand) -> Result<(), String> {
+    let options = &command.options;
+    let required: &[(bool, &str)] = match command.action {
+        CircuitAction::VerifyDir => &[
+            (options.dir.is_some(), "verify-dir requires --dir <path>"),
+            (options.vk.is_some(), "verify-dir requires --vk <path>"),
+        ],
+        CircuitAction::BenchmarkVerify => &[
+            (
+                options.proof.is_some(),
+                "benchmark-verify requires --proof <path>",
+            ),
+            (
+                options.vk.is_some(),
+                "benchmark-verify requires --vk <path>",
+            ),
+        ],
+        CircuitAction::ProveDir => &[
+            (options.dir.is_some(), "prove-dir requires --dir <path>"),
+            (
+                options.out_dir.is_some(),
+                "prove-dir requires --out-dir <path>",
+            ),
+        ],
+        CircuitAction::MinimizeProof => &[
+            (
+                options.proof.is_some(),
+                "minimize-proof requires --in <path>",
+            ),
+            (
+                options.out.is_some(),
+                "minimize-proof requires --out <path>",
+            ),
+            (options.vk.is_some(), "minimize-proof requires --vk <path>"),
+        ],
+        CircuitAction::DumpShape => &[(options.out.is_some(), "dump-shape requires --out <path>")],
+        CircuitAction::CheckWitness => &[
+            (
+                options.witness.is_some(),
+                "check-witness requires --witness <path>",
+            ),
+            (
+                options.input.is_some(),
+                "check-witness requires --input <path>",
+            ),
+        ],
+        CircuitAction::CheckLink => &[
+            (
+                options.prepare_input.is_some(),
+                "check-link requires --prepare <path>",
+            ),
+            (
+                options.show_input.is_some(),
+                "check-link requires --show <path>",
+            ),
+        ],
+        CircuitAction::DeriveVk => &[
+            (options.pk.is_some(), "derive-vk requires --pk <path>"),
+            (options.out.is_some(), "derive-vk requires --out <path>"),
+        ],
+        // An instance is read from a file, so inline JSON does not count
+        CircuitAction::InspectInstance => &[(
+            matches!(options.input, Some(InputSource::Path(_))),
+            "inspect-instance requires --input <path>",
+        )],
+        _ => &[],
+    };
+    if let Some((_, message)) = required.iter().find(|(present, _)| !present) {
+        return Err(message.to_string());
+    }
+
+    if command.circuit == CircuitKind::Auto && options.input.is_none() {
+        return Err("auto detects the circuit from its input; pass --input or --input-json".into());
+    }
+    Ok(())
+}
+
+fn parse_circuit_command(circuit: CircuitKind, tail: &[String]) -> Result<ParsedCommand, String> {
+    if tail.is_empty() {
+        return Ok(ParsedCommand {
+            circuit,
+            action: CircuitAction::Run,
+            options: CommandOptions::default(),
+        });
+    }
+
+    let first = &tail[0];
+    let (action, option_start) = match first.as_str() {
+        "run" => (CircuitAction::Run, 1),
+        "setup" => (CircuitAction::Setup, 1),
+        "setup-and-prove" => (CircuitAction::SetupAndProve, 1),
+        "prove" => (CircuitAction::Prove, 1),
+        "prove-dir" => (CircuitAction::ProveDir, 1),
+        "verify" => (CircuitAction::Verify, 1),
+        "reblind" => (CircuitAction::Reblind, 1),
+        "generate_shared_blinds" => (CircuitAction::GenerateSharedBlinds, 1),
+        "benchmark" => (CircuitAction::Benchmark, 1),
+        "estimate-proof" => (CircuitAction::EstimateProof, 1),
+        "dump-shape" => (CircuitAction::DumpShape, 1),
+        s if s.starts_with('-') => (CircuitAction::Run, 0),
+        other => {
+            return Err(format!(
+                "Unknown action '{other}' for {:?}. Expected one of run|setup|setup-and-prove|prove|prove-dir|verify|reblind|generate_shared_blinds|benchmark|estimate-proof|dump-shape.",
+                circuit
+            ))
+        }
+    };
+
+    if action == CircuitAction::GenerateSharedBlinds && circuit != CircuitKind::Prepare {
+        return Err(
+            "The generate_shared_blinds action is only supported for the Prepare circuit".into(),
+        );
+    }
+
+    let options_slice = &tail[option_start..];
+    let options = match action {
+        CircuitAction::Run | CircuitAction::Setup | CircuitAction::SetupAndProve => {
+            parse_options(options_slice)?
+        }
+        CircuitAction::Benchmark => parse_benchmark_options(options_slice)?,
+        CircuitAction::Prove => parse_prove_options(options_slice)?,
+        CircuitAction::ProveDir => parse_prove_dir_options(options_slice)?,
+        CircuitAction::Verify => parse_verify_options(circuit, options_slice)?,
+        CircuitAction::EstimateProof => parse_output_options(options_slice)?,
+        CircuitAction::Reblind => parse_reblind_options(options_slice)?,
+        CircuitAction::GenerateSharedBlinds => parse_shared_blinds_options(options_slice)?,
+        CircuitAction::Doctor => ensure_no_options(options_slice)?,
+        CircuitAction::ExportSchema => parse_export_schema_options(options_slice)?,
+        CircuitAction::VerifyDir => parse_verify_dir_options(options_slice)?,
+        CircuitAction::BenchmarkVerify => parse_benchmark_verify_options(options_slice)?,
+        CircuitAction::MinimizeProof => parse_minimize_proof_options(options_slice)?,
+        CircuitAction::InspectInstance => parse_inspect_options(options_slice)?,
+        CircuitAction::CheckLink => parse_check_link_options(options_slice)?,
+        CircuitAction::CheckWitness => parse_check_witness_options(options_slice)?,
+        CircuitAction::DeriveVk => parse_derive_vk_options(options_slice)?,
+        CircuitAction::DumpShape => parse_dump_shape_options(options_slice)?,
+        CircuitAction::ShowHelp => CommandOptions::default(),
+    };
+
+    Ok(ParsedCommand {
+        circuit,
+        action,
+        options,
+    })
+}
+
+/// `export-schema prepare|show [--out <path>]`
+fn parse_export_schema_command(tail: &[String]) -> Result<ParsedCommand, String> {
+    let circuit = match tail.first().map(String::as_str) {
+        Some("prepare") => CircuitKind::Prepare,
+        Some("show") => CircuitKind::Show,
+        Some("--help" | "-h") => {
+            return Ok(ParsedCommand {
+                circuit: CircuitKind::Prepare,
+                action: CircuitAction::ExportSchema,
+                options: CommandOptions {
+                    help: true,
+                    ..CommandOptions::default()
+                },
+            })
+        }
+        Some(other) => {
+            return Err(format!(
+                "Unknown circuit '{other}' for export-schema. Expected prepare|show."
+            ))
+        }
+        None => return Err("export-schema requires a circuit: prepare|show".into()),
+    };
+
+    Ok(ParsedCommand {
+        circuit,
+        action: CircuitAction::ExportSchema,
+        options: parse_export_schema_options(&tail[1..])?,
+    })
+}
+
+fn parse_export_schema_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--out")? {
+            options.out = Some(PathBuf::from(value));
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unknown option '{arg}'"));
+        }
+        index += 1;
+    }
+
+    Ok(options)
+}
+
+/// `dump-shape --out <path>`, plus the input the circuit's `shared` allocation reads
+fn parse_dump_shape_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut rest = Vec::with_capacity(args.len());
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--out")? {
+            options.out = Some(PathBuf::from(value));
+        } else {
+            rest.push(arg.clone());
+        }
+        index += 1;
+    }
+
+    let parsed = parse_options(&rest)?;
+    if parsed.help {
+        return Ok(parsed);
+    }
+    if parsed.format != OutputFormat::Table || parsed.metrics_out.is_some() || parsed.keep_going {
+        return Err("dump-shape only takes --out and --input/--input-json".into());
+    }
+    options.input = parsed.input;
+    Ok(options)
+}
+
+fn parse_check_witness_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut rest = Vec::with_capacity(args.len());
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--witness")? {
+            options.witness = Some(PathBuf::from(value));
+        } else {
+            rest.push(arg.clone());
+        }
+        index += 1;
+    }
+
+    let parsed = parse_options(&rest)?;
+    if parsed.help {
+        return Ok(parsed);
+    }
+    if parsed.format != OutputFormat::Table || parsed.metrics_out.is_some() || parsed.keep_going {
+        return Err("check-witness only takes --witness and --input/--input-json".into());
+    }
+    options.input = parsed.input;
+    Ok(options)
+}
+
+fn parse_shared_blinds_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--seed")? {
+            options.seed = Some(parse_seed(&value)?);
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unexpected options: {}", args[index..].join(" ")));
+        }
+        index += 1;
+    }
+
+    Ok(options)
+}
+
+fn parse_seed(value: &str) -> Result<Vec<u8>, String> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    match hex::decode(digits) {
+        Ok(seed) if !seed.is_empty() => Ok(seed),
+        _ => Err(format!("Invalid --seed '{value}'; expected hex bytes")),
+    }
+}
+
+fn ensure_no_options(args: &[String]) -> Result<CommandOptions, String> {
+    match args {
+        [] => Ok(CommandOptions::default()),
+        [flag] if flag == "--help" || flag == "-h" => Ok(CommandOptions {
+            help: true,
+            ..CommandOptions::default()
+        }),
+        _ => Err(format!("Unexpected options: {}", args.join(" "))),
+    }
+}
+
+fn parse_prove_options(args: &[String]) -> Result<CommandOptions, String> {
+    let no_save = args.iter().any(|arg| arg == "--no-save");
+    let rest: Vec<String> = args
+        .iter()
+        .filter(|arg| *arg != "--no-save")
+        .cloned()
+        .collect();
+
+    let mut options = parse_options(&rest)?;
+    options.no_save = no_save;
+    if no_save && options.format != OutputFormat::Table && options.metrics_out.is_none() {
+        return Err("--no-save writes the proof to stdout; use --metrics-out with --format".into());
+    }
+    Ok(options)
+}
+
+fn parse_benchmark_options(args: &[String]) -> Result<CommandOptions, String> {
+    let estimate = args.iter().any(|arg| arg == "--estimate");
+    let rest: Vec<String> = args
+        .iter()
+        .filter(|arg| *arg != "--estimate")
+        .cloned()
+        .collect();
+
+    let mut options = parse_options(&rest)?;
+    options.estimate = estimate;
+    if estimate && (options.format == OutputFormat::Prometheus || options.keep_going) {
+        return Err("--estimate only supports --format table or json".into());
+    }
+    Ok(options)
+}
+
+fn parse_reblind_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+
+    for arg in args {
+        if arg == "--verify-after" {
+            options.verify_after = true;
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unknown option '{arg}'"));
+        }
+    }
+
+    Ok(options)
+}
+
+/// The value of `flag` when `args[*index]` is `flag <value>` or `flag=<value>`, advancing
+/// `index` past a separate value. `None` when the argument is not `flag`.
+pub fn take_value(
+    args: &[String],
+    index: &mut usize,
+    flag: &str,
+) -> Result<Option<String>, String> {
+    let arg = &args[*index];
+    let value = if arg == flag {
+        *index += 1;
+        args.get(*index).cloned()
+    } else if let Some(value) = arg
+        .strip_prefix(flag)
+        .and_then(|rest| rest.strip_prefix('='))
+    {
+        Some(value.to_string()).filter(|value| !value.is_empty())
+    } else {
+        return Ok(None);
+    };
+    value
+        .map(Some)
+        .ok_or_else(|| format!("Missing value for {flag}"))
+}
+
+fn parse_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--input")? {
+            options.input = Some(InputSource::Path(PathBuf::from(value)));
+        } else if let Some(value) = take_value(args, &mut index, "-i")? {
+            options.input = Some(InputSource::Path(PathBuf::from(value)));
+        } else if let Some(value) = take_value(args, &mut index, "--input-json")? {
+            options.input = Some(InputSource::Inline(value));
+        } else if let Some(value) = take_value(args, &mut index, "--format")? {
+            options.format = value.parse()?;
+        } else if let Some(value) = take_value(args, &mut index, "--metrics-out")? {
+            options.metrics_out = Some(PathBuf::from(value));
+        } else if arg == "--keep-going" {
+            options.keep_going = true;
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unknown option '{arg}'"));
+        }
+        index += 1;
+    }
+
+    Ok(options)
+}
+
+/// Options for commands that only report results (no circuit input)
+fn parse_output_options(args: &[String]) -> Result<CommandOptions, String> {
+    let options = parse_options(args)?;
+    if options.input.is_some() && !options.help {
+        return Err("--input is not supported for this command".into());
+    }
+    Ok(options)
+}
+
+/// `verify`, plus `--expected-nonce <decimal>` for the Show circuit
+fn parse_verify_options(circuit: CircuitKind, args: &[String]) -> Result<CommandOptions, String> {
+    let mut expected_nonce = None;
+    let mut rest = Vec::with_capacity(args.len());
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--expected-nonce")? {
+            expected_nonce = Some(parse_nonce(&value)?);
+        } else {
+            rest.push(arg.clone());
+        }
+        index += 1;
+    }
+
+    if expected_nonce.is_some() && circuit == CircuitKind::Prepare {
+        return Err("--expected-nonce is only supported for show verify".into());
+    }
+    let mut options = parse_output_options(&rest)?;
+    options.expected_nonce = expected_nonce;
+    Ok(options)
+}
+
+fn parse_nonce(value: &str) -> Result<String, String> {
+    if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "Invalid --expected-nonce '{value}'; expected a decimal integer"
+        ))
+    }
+}
+
+fn parse_inspect_options(args: &[String]) -> Result<CommandOptions, String> {
+    parse_options(args)
+}
+
+fn parse_check_link_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--prepare")? {
+            options.prepare_input = Some(PathBuf::from(value));
+        } else if let Some(value) = take_value(args, &mut index, "--show")? {
+            options.show_input = Some(PathBuf::from(value));
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unknown option '{arg}'"));
+        }
+        index += 1;
+    }
+
+    Ok(options)
+}
+
+fn parse_verify_dir_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--dir")? {
+            options.dir = Some(PathBuf::from(value));
+        } else if let Some(value) = take_value(args, &mut index, "--vk")? {
+            options.vk = Some(PathBuf::from(value));
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unknown option '{arg}'"));
+        }
+        index += 1;
+    }
+
+    Ok(options)
+}
+
+fn parse_benchmark_verify_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--proof")? {
+            options.proof = Some(PathBuf::from(value));
+        } else if let Some(value) = take_value(args, &mut index, "--vk")? {
+            options.vk = Some(PathBuf::from(value));
+        } else if let Some(value) = take_value(args, &mut index, "--iterations")? {
+            options.iterations = Some(parse_iterations(&value)?);
+        } else if let Some(value) = take_value(args, &mut index, "--format")? {
+            options.format = value.parse()?;
+        } else if let Some(value) = take_value(args, &mut index, "--metrics-out")? {
+            options.metrics_out = Some(PathBuf::from(value));
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unknown option '{arg}'"));
+        }
+        index += 1;
+    }
+
+    Ok(options)
+}
+
+fn parse_minimize_proof_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--in")? {
+            options.proof = Some(PathBuf::from(value));
+        } else if let Some(value) = take_value(args, &mut index, "--out")? {
+            options.out = Some(PathBuf::from(value));
+        } else if let Some(value) = take_value(args, &mut index, "--vk")? {
+            options.vk = Some(PathBuf::from(value));
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unknown option '{arg}'"));
+        }
+        index += 1;
+    }
+
+    Ok(options)
+}
+
+fn parse_iterations(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(iterations) if iterations > 0 => Ok(iterations),
+        _ => Err(format!(
+            "Invalid --iterations '{value}'; expected a positive integer"
+        )),
+    }
+}
+
+fn parse_prove_dir_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--dir")? {
+            options.dir = Some(PathBuf::from(value));
+        } else if let Some(value) = take_value(args, &mut index, "--out-dir")? {
+            options.out_dir = Some(PathBuf::from(value));
+        } else if let Some(value) = take_value(args, &mut index, "--naming")? {
+            options.naming = value.parse()?;
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unknown option '{arg}'"));
+        }
+        index += 1;
+    }
+
+    Ok(options)
+}
+
+fn parse_derive_vk_options(args: &[String]) -> Result<CommandOptions, String> {
+    let mut options = CommandOptions::default();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if let Some(value) = take_value(args, &mut index, "--pk")? {
+            options.pk = Some(PathBuf::from(value));
+        } else if let Some(value) = take_value(args, &mut index, "--out")? {
+            options.out = Some(PathBuf::from(value));
+        } else if arg == "--help" || arg == "-h" {
+            options.help = true;
+            return Ok(options);
+        } else {
+            return Err(format!("Unknown option '{arg}'"));
+        }
+        index += 1;
+    }
+
+    Ok(options)
+}
+
+/// Print the CLI usage text to stderr
+pub fn print_usage() {
+    eprintln!(
+        "Usage:
+  ecdsa-spartan2 <prepare|show> [run|setup|setup-and-prove|prove|verify] [options]
+  ecdsa-spartan2 prepare generate_shared_blinds [--seed <hex>]
+  ecdsa-spartan2 auto [run|setup|setup-and-prove|prove|benchmark] --input <path>
+  ecdsa-spartan2 <prepare|show> prove-dir --dir <path> --out-dir <path> [--naming hash|stem]
+  ecdsa-spartan2 benchmark [--estimate] [options]
+  ecdsa-spartan2 benchmark-verify --proof <path> --vk <path> [--iterations N]
+  ecdsa-spartan2 minimize-proof --in <path> --out <path> --vk <path>
+  ecdsa-spartan2 verify-dir --dir <path> --vk <path>
+  ecdsa-spartan2 inspect-instance --input <path>
+  ecdsa-spartan2 check-link --prepare <path> --show <path>
+  ecdsa-spartan2 check-witness --witness <path> --input <path>
+  ecdsa-spartan2 derive-vk --pk <path> --out <path>
+  ecdsa-spartan2 doctor
+  ecdsa-spartan2 export-schema <prepare|show> [--out <path>]
+
+Commands:
+  benchmark            Run complete pipeline with full metrics (setup, prove, reblind, verify)
+  benchmark-verify     Verify one proof repeatedly and report latency percentiles and throughput
+  minimize-proof       Re-encode a proof without bytes verify does not read, checking it still
+                       verifies; a proof that is already minimal is left alone
+  verify-dir           Verify every *.bin proof in a directory against one verifying key
+  check-link           Check that a Prepare and a Show input will produce linked proofs
+  check-witness        Check that a saved Prepare witness was generated from an input and
+                       report the first index where they differ
+  derive-vk            Explain why a verifying key cannot be rebuilt from a proving key
+  inspect-instance     Print a saved instance's public IO and comm_W_shared as JSON
+  doctor               Check that the saved keys, proofs, instances and witnesses belong together
+  export-schema        Print the JSON Schema of a circuit's input file
+  prepare <action>     Run action on Prepare circuit
+  show <action>        Run action on Show circuit
+  auto <action>        Run action on the circuit the input is for (message/claims: Prepare,
+                       messageHash/currentYear: Show)
+
+Actions:
+  run                  Run the complete circuit (setup, prove, verify)
+  setup                Generate proving and verifying keys
+  setup-and-prove      Generate keys and prove in one run, then save keys and proof
+  prove                Generate proof
+  prove-dir            Prove every *.json input in a directory, skipping inputs already proved
+  verify               Verify proof
+  reblind              Reblind proof
+  benchmark            Run complete benchmark pipeline
+  estimate-proof       Predict the proof size from the circuit's R1CS without proving
+  dump-shape           Synthesize the circuit as setup does and write its constraint and
+                       variable counts per namespace to --out as JSON
+
+Options:
+  --config <path>      Read option defaults from this file instead of ./l8zk.toml; flags
+                       override its values
+  --input, -i <path>   Override the circuit input JSON (run/prove/setup/setup-and-prove/benchmark),
+                       or the instance file to print (inspect-instance)
+  --input-json <json>  Pass the circuit input as a JSON string instead of a file
+  --format <fmt>       Output format: table (default), json, or prometheus (prove/verify/benchmark)
+  --metrics-out <path> Write json/prometheus output to a file instead of stdout
+  --keep-going         Record failing benchmark steps and continue instead of aborting
+  --estimate           Run setup only and extrapolate the prove/reblind/verify times from the
+                       constraint count (benchmark)
+  --no-save            Write the proof to stdout instead of saving instance/witness/proof (prove)
+  --verify-after       Verify the reblinded proof immediately and fail if it is rejected (reblind)
+  --dir <path>         Directory of proofs to verify (verify-dir) or inputs to prove (prove-dir)
+  --out-dir <path>     Directory to write the proofs to (prove-dir)
+  --naming <scheme>    Proof file names: hash (default, SHA-256 of the input) or stem (prove-dir)
+  --vk <path>          Verifying key to check the proofs against
+                       (verify-dir/benchmark-verify/minimize-proof)
+  --proof <path>       Proof to verify repeatedly (benchmark-verify)
+  --in <path>          Proof to minimize (minimize-proof)
+  --witness <path>     Saved Prepare witness to compare with --input (check-witness)
+  --seed <hex>         Derive the shared blinds from this seed instead of the OS RNG, for
+                       reproducible test runs only (generate_shared_blinds)
+  --iterations <n>     Number of verifications to time, default 1000 (benchmark-verify)
+  --expected-nonce <n> Nonce the verifier issued; the proof must carry it instead of the one
+                       in its descriptor (show verify)
+  --pk <path>          Proving key whose verifying key is wanted (derive-vk)
+  --out <path>         Where the verifying key would go (derive-vk), schema
+                       (export-schema), shape dump (dump-shape) or minimized proof
+                       (minimize-proof)
+  --prepare <path>     Prepare input JSON to compare (check-link)
+  --show <path>        Show input JSON to compare (check-link)
+
+Examples:
+  cargo run --release -- benchmark --input ../circom/inputs/jwt/generated.json
+  cargo run --release -- prepare run --input ../circom/inputs/jwt/generated.json
+  cargo run --release -- show prove --input ../circom/inputs/show/generated.json
+  cargo run --release -- show verify
+  cargo run --release -- auto prove --input ../circom/inputs/show/generated.json
+  cargo run --release -- show prove --input-json '{"deviceKeyX": "...", ...}'
+  cargo run --release -- benchmark --format prometheus --metrics-out l8zk.prom
+  cargo run --release -- benchmark --estimate
+  cargo run --release -- show prove-dir --dir inputs --out-dir proofs
+  cargo run --release -- verify-dir --dir proofs --vk keys/show_verifying.key
+  cargo run --release -- benchmark-verify --proof keys/show_proof.bin --vk keys/show_verifying.key --iterations 5000
+  cargo run --release -- inspect-instance --input keys/prepare_instance.bin
+  cargo run --release -- doctor
+  cargo run --release -- export-schema prepare --out prepare.schema.json
+  cargo run --release -- prepare dump-shape --out shape.json
+  cargo run --release -- check-link --prepare ../circom/inputs/jwt/default.json --show ../circom/inputs/show/default.json
+
+Legacy commands like `prepare`, `show`, `prove_prepare`, etc. are still supported."
+    );
+}
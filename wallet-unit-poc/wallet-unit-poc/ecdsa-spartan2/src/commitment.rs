@@ -0,0 +1,46 @@
+//! Pedersen commitments to disclosed claims, for relying parties that hold a commitment to a
+//! claim rather than the claim itself.
+//!
+//! A claim is committed as `sum(claim_scalars[i] * G_i) + blind * H` on the T256 group used by
+//! the proofs. The generators are derived from a fixed label, so any party can recompute them
+//! and open a commitment given the claim scalars and the blind.
+//!
+//! The commitment is computed outside the circuits; the Show circuit does not expose it as a
+//! public output.
+
+use ff::{derive::rand_core::OsRng, Field};
+use serde::{Deserialize, Serialize};
+use spartan2::{provider::traits::DlogGroup, traits::Engine};
+
+use crate::{Scalar, E};
+
+/// Label the claim commitment generators are derived from
+pub const CLAIM_COMMITMENT_LABEL: &[u8] = b"l8zk-claim-commitment";
+
+type GroupElement = <E as Engine>::GE;
+
+/// Pedersen commitment to a claim's scalars
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimCommitment(pub GroupElement);
+
+/// Commit to `claim_scalars` (e.g. `PrepareSharedScalars::claim_scalars`) with `blind`, drawing
+/// a fresh blind when none is given. Returns the commitment and the blind that opens it.
+pub fn commit_claim(claim_scalars: &[Scalar], blind: Option<Scalar>) -> (ClaimCommitment, Scalar) {
+    let blind = blind.unwrap_or_else(|| Scalar::random(OsRng));
+
+    // The generator after the claim generators blinds the commitment
+    let generators = GroupElement::from_label(CLAIM_COMMITMENT_LABEL, claim_scalars.len() + 1);
+    let scalars: Vec<Scalar> = claim_scalars.iter().copied().chain([blind]).collect();
+
+    let commitment = GroupElement::vartime_multiscalar_mul(&scalars, &generators);
+    (ClaimCommitment(commitment), blind)
+}
+
+/// Check that `commitment` opens to `claim_scalars` under `blind`
+pub fn open_claim_commitment(
+    commitment: &ClaimCommitment,
+    claim_scalars: &[Scalar],
+    blind: Scalar,
+) -> bool {
+    commit_claim(claim_scalars, Some(blind)).0 == *commitment
+}
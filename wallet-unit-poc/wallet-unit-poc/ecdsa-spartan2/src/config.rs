@@ -0,0 +1,189 @@
+//! `l8zk.toml`: defaults for CLI options that would otherwise be repeated on every invocation.
+//!
+//! The file is read from the working directory, or from the path given with `--config`. Its
+//! values fill in options the command line leaves out, so the precedence is built-in defaults,
+//! then the config file, then flags. A value only applies to commands that take that option;
+//! a default `input`, for example, is not passed to `verify`.
+//!
+//! ```toml
+//! input = "../circom/inputs/jwt/default.json"
+//! format = "json"
+//! metrics-out = "l8zk.json"
+//! out-dir = "proofs"
+//! naming = "stem"
+//! vk = "keys/show_verifying.key"
+//! iterations = 5000
+//! ```
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    cli::{take_value, CircuitAction, CommandOptions, OutputFormat},
+    errors::ConfigError,
+    prover::ProofNaming,
+    utils::InputSource,
+};
+
+/// Config file read from the working directory when `--config` is not given
+pub const CONFIG_FILE: &str = "l8zk.toml";
+
+/// Option defaults read from `l8zk.toml`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Circuit input for run/setup/setup-and-prove/prove/benchmark
+    pub input: Option<PathBuf>,
+    /// `table`, `json` or `prometheus`
+    pub format: Option<String>,
+    pub metrics_out: Option<PathBuf>,
+    /// Where `prove-dir` writes proofs
+    pub out_dir: Option<PathBuf>,
+    /// `hash` or `stem`, for `prove-dir`
+    pub naming: Option<String>,
+    /// Verifying key for `verify-dir`, `benchmark-verify` and `minimize-proof`
+    pub vk: Option<PathBuf>,
+    /// Verifications timed by `benchmark-verify`
+    pub iterations: Option<usize>,
+}
+
+impl Config {
+    /// Parse a config file's contents, checking `format` and `naming` up front so a typo is
+    /// reported before any command runs
+    pub fn from_toml(contents: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(contents).map_err(|e| e.message().to_string())?;
+        if let Some(format) = &config.format {
+            format.parse::<OutputFormat>()?;
+        }
+        if let Some(naming) = &config.naming {
+            naming.parse::<ProofNaming>()?;
+        }
+        if config.iterations == Some(0) {
+            return Err("iterations must be a positive integer".into());
+        }
+        Ok(config)
+    }
+
+    /// Read the config at `path`, or `l8zk.toml` in `cwd` when no path is given. A missing
+    /// default file means no config; a missing `--config` file is an error.
+    pub fn load(path: Option<&Path>, cwd: &Path) -> Result<Option<Self>, ConfigError> {
+        let (path, required) = match path {
+            Some(path) => (cwd.join(path), true),
+            None => (cwd.join(CONFIG_FILE), false),
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => Self::from_toml(&contents)
+                .map(Some)
+                .map_err(|reason| ConfigError::Invalid { path, reason }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !required => Ok(None),
+            Err(source) => Err(ConfigError::Read { path, source }),
+        }
+    }
+
+    /// Fill the options of `action` that the command line left unset. `args` are the raw
+    /// arguments, used to tell an explicit `--format table` or `--naming hash` from the default.
+    pub fn apply(&self, action: CircuitAction, options: &mut CommandOptions, args: &[String]) {
+        if takes_circuit_input(action) && options.input.is_none() {
+            options.input = self.input.clone().map(InputSource::Path);
+        }
+
+        if takes_format(action) {
+            if options.metrics_out.is_none() {
+                options.metrics_out = self.metrics_out.clone();
+            }
+            // `--no-save` writes the proof to stdout, which a json/prometheus report would corrupt
+            let stdout_taken = options.no_save && options.metrics_out.is_none();
+            if let Some(format) = &self.format {
+                if !flag_given(args, "--format") && !stdout_taken {
+                    options.format = format.parse().expect("checked in from_toml");
+                }
+            }
+        }
+
+        if action == CircuitAction::ProveDir {
+            if options.out_dir.is_none() {
+                options.out_dir = self.out_dir.clone();
+            }
+            if let Some(naming) = &self.naming {
+                if !flag_given(args, "--naming") {
+                    options.naming = naming.parse().expect("checked in from_toml");
+                }
+            }
+        }
+
+        if matches!(
+            action,
+            CircuitAction::VerifyDir
+                | CircuitAction::BenchmarkVerify
+                | CircuitAction::MinimizeProof
+        ) && options.vk.is_none()
+        {
+            options.vk = self.vk.clone();
+        }
+
+        if action == CircuitAction::BenchmarkVerify && options.iterations.is_none() {
+            options.iterations = self.iterations;
+        }
+    }
+}
+
+/// Remove `--config <path>` (or `--config=<path>`) from `args`, returning the path and the
+/// remaining arguments for `parse_command`
+pub fn take_config_flag(args: &[String]) -> Result<(Option<PathBuf>, Vec<String>), String> {
+    let mut config = None;
+    let mut rest = Vec::with_capacity(args.len());
+    let mut index = 0;
+
+    while index < args.len() {
+        if let Some(value) = take_value(args, &mut index, "--config")? {
+            config = Some(PathBuf::from(value));
+        } else {
+            rest.push(args[index].clone());
+        }
+        index += 1;
+    }
+
+    Ok((config, rest))
+}
+
+/// Actions whose `--input` is a circuit input file
+fn takes_circuit_input(action: CircuitAction) -> bool {
+    matches!(
+        action,
+        CircuitAction::Run
+            | CircuitAction::Setup
+            | CircuitAction::SetupAndProve
+            | CircuitAction::Prove
+            | CircuitAction::Benchmark
+            | CircuitAction::DumpShape
+            | CircuitAction::CheckWitness
+    )
+}
+
+/// Actions that accept `--format` and `--metrics-out`
+fn takes_format(action: CircuitAction) -> bool {
+    matches!(
+        action,
+        CircuitAction::Run
+            | CircuitAction::Setup
+            | CircuitAction::SetupAndProve
+            | CircuitAction::Prove
+            | CircuitAction::Benchmark
+            | CircuitAction::Verify
+            | CircuitAction::EstimateProof
+            | CircuitAction::BenchmarkVerify
+    )
+}
+
+fn flag_given(args: &[String], flag: &str) -> bool {
+    args.iter().any(|arg| {
+        arg == flag
+            || arg
+                .strip_prefix(flag)
+                .is_some_and(|rest| rest.starts_with('='))
+    })
+}
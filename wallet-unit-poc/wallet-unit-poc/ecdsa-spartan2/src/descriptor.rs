@@ -0,0 +1,136 @@
+//! Public-input descriptors shipped next to a proof.
+//!
+//! A verifier has to supply the public inputs a proof was made for. The descriptor records
+//! them with their labels (`nonce`, ...) in a small JSON file beside the proof, so a verifier
+//! can read them from there instead of agreeing on them out of band.
+
+use std::{fs, path::Path};
+
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+use spartan2::{traits::snark::R1CSSNARKTrait, zk_spartan::R1CSSNARK};
+
+use crate::{
+    errors::{DescriptorError, L8zkError},
+    prover::verify_with_public_inputs,
+    utils::{scalar_to_hex, SHOW_NONCE_FIELD},
+    Scalar, E,
+};
+
+/// Format version written in every descriptor
+pub const DESCRIPTOR_VERSION: u32 = 1;
+
+/// Labels of the Prepare circuit's public inputs; it has none
+pub const PREPARE_PUBLIC_INPUTS: &[&str] = &[];
+
+/// Labels of the Show circuit's public inputs, in the order its proofs carry them
+pub const SHOW_PUBLIC_INPUTS: &[&str] = &[SHOW_NONCE_FIELD];
+
+/// One public input of a proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInput {
+    pub label: String,
+    /// Canonical big-endian hex, as `scalar_to_hex` writes it
+    pub value: String,
+}
+
+/// The public inputs a proof was made for, in the order the proof carries them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInputsDescriptor {
+    pub version: u32,
+    pub inputs: Vec<PublicInput>,
+}
+
+impl PublicInputsDescriptor {
+    /// Pair each of `values` with the label at the same position, e.g. `SHOW_PUBLIC_INPUTS`
+    pub fn new(labels: &[&str], values: &[Scalar]) -> Result<Self, DescriptorError> {
+        if labels.len() != values.len() {
+            return Err(DescriptorError::LabelCountMismatch {
+                labels: labels.len(),
+                values: values.len(),
+            });
+        }
+
+        let inputs = labels
+            .iter()
+            .zip(values)
+            .map(|(label, value)| PublicInput {
+                label: label.to_string(),
+                value: scalar_to_hex(value),
+            })
+            .collect();
+        Ok(Self {
+            version: DESCRIPTOR_VERSION,
+            inputs,
+        })
+    }
+
+    /// The public inputs as scalars, ready to pass to `verify_with_public_inputs`
+    pub fn values(&self) -> Result<Vec<Scalar>, DescriptorError> {
+        if self.version != DESCRIPTOR_VERSION {
+            return Err(DescriptorError::UnsupportedVersion {
+                found: self.version,
+                expected: DESCRIPTOR_VERSION,
+            });
+        }
+        self.inputs.iter().map(parse_value).collect()
+    }
+
+    /// The value labelled `label`, if the descriptor has one
+    pub fn get(&self, label: &str) -> Option<Result<Scalar, DescriptorError>> {
+        self.inputs
+            .iter()
+            .find(|input| input.label == label)
+            .map(parse_value)
+    }
+}
+
+fn parse_value(input: &PublicInput) -> Result<Scalar, DescriptorError> {
+    let invalid = || DescriptorError::InvalidValue {
+        label: input.label.clone(),
+        value: input.value.clone(),
+    };
+
+    let mut bytes = input
+        .value
+        .strip_prefix("0x")
+        .and_then(|digits| hex::decode(digits).ok())
+        .ok_or_else(invalid)?;
+    let mut repr = <Scalar as PrimeField>::Repr::default();
+    if bytes.len() != repr.as_ref().len() {
+        return Err(invalid());
+    }
+    // The hex is big-endian; the field representation is little-endian
+    bytes.reverse();
+    repr.as_mut().copy_from_slice(&bytes);
+    Option::from(Scalar::from_repr(repr)).ok_or_else(invalid)
+}
+
+/// Where the descriptor of the proof at `proof_path` is kept: `<proof_path>.public.json`
+pub fn descriptor_path(proof_path: &str) -> String {
+    format!("{proof_path}.public.json")
+}
+
+/// Write `descriptor` as pretty-printed JSON
+pub fn save_descriptor(path: &str, descriptor: &PublicInputsDescriptor) -> Result<(), L8zkError> {
+    let json =
+        serde_json::to_string_pretty(descriptor).expect("descriptor serialization is infallible");
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Read a descriptor written by `save_descriptor`
+pub fn load_descriptor(path: &str) -> Result<PublicInputsDescriptor, L8zkError> {
+    let json = fs::read_to_string(Path::new(path))?;
+    let descriptor = serde_json::from_str(&json).map_err(DescriptorError::Decode)?;
+    Ok(descriptor)
+}
+
+/// Verify `proof` against the public inputs recorded in `descriptor`
+pub fn verify_with_descriptor(
+    proof: &R1CSSNARK<E>,
+    vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    descriptor: &PublicInputsDescriptor,
+) -> Result<(), L8zkError> {
+    verify_with_public_inputs(proof, vk, &descriptor.values()?)
+}
@@ -0,0 +1,216 @@
+//! Consistency checks across the artifacts in `keys/`.
+//!
+//! Keys, proofs, instances and witnesses written by different runs load fine on their own but
+//! fail to verify or reblind together. `doctor` loads each artifact and reports which ones
+//! disagree, so stale files can be found before verifying or reblinding.
+
+use spartan2::{
+    r1cs::SplitR1CSInstance,
+    traits::snark::{DigestHelperTrait, R1CSSNARKTrait},
+};
+
+use crate::{
+    errors::L8zkError,
+    setup::{
+        load_instance, load_proof, load_proving_key, load_shared_blinds, load_verifying_key,
+        load_witness, Paths,
+    },
+    E,
+};
+
+/// One check made by `doctor`
+#[derive(Debug)]
+pub struct DoctorCheck {
+    /// What was checked, e.g. "prepare: proof verifies against the verifying key"
+    pub description: String,
+    /// Why the check failed
+    pub result: Result<(), String>,
+}
+
+/// Outcome of every check made by `doctor`, in the order they ran
+#[derive(Debug, Default)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Whether every check passed
+    pub fn is_consistent(&self) -> bool {
+        self.checks.iter().all(|check| check.result.is_ok())
+    }
+
+    fn record(&mut self, description: String, result: Result<(), String>) {
+        self.checks.push(DoctorCheck {
+            description,
+            result,
+        });
+    }
+
+    /// Load one artifact, recording whether it loaded
+    fn load<T>(
+        &mut self,
+        description: String,
+        path: &str,
+        load: impl FnOnce(&str) -> Result<T, L8zkError>,
+    ) -> Option<T> {
+        let loaded = load(path);
+        let result = loaded
+            .as_ref()
+            .map(|_| ())
+            .map_err(|e| format!("{path}: {e}"));
+        self.record(description, result);
+        loaded.ok()
+    }
+}
+
+/// Artifact locations for one circuit
+struct CircuitPaths<'a> {
+    name: &'static str,
+    proving_key: &'a str,
+    verifying_key: &'a str,
+    proof: &'a str,
+    instance: &'a str,
+    witness: &'a str,
+}
+
+/// Check that the artifacts at `paths` belong together: each circuit's keys, proof, instance
+/// and witness come from the same setup and prove, the shared blinds fit both instances, and
+/// the Prepare and Show instances share `comm_W_shared`.
+pub fn doctor(paths: &Paths) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    let prepare = check_circuit(
+        &mut report,
+        CircuitPaths {
+            name: "prepare",
+            proving_key: &paths.prepare_proving_key,
+            verifying_key: &paths.prepare_verifying_key,
+            proof: &paths.prepare_proof,
+            instance: &paths.prepare_instance,
+            witness: &paths.prepare_witness,
+        },
+    );
+    let show = check_circuit(
+        &mut report,
+        CircuitPaths {
+            name: "show",
+            proving_key: &paths.show_proving_key,
+            verifying_key: &paths.show_verifying_key,
+            proof: &paths.show_proof,
+            instance: &paths.show_instance,
+            witness: &paths.show_witness,
+        },
+    );
+
+    let blinds = report.load(
+        "shared blinds load".to_string(),
+        &paths.shared_blinds,
+        load_shared_blinds::<E>,
+    );
+    if let Some(blinds) = blinds {
+        for (name, instance) in [("prepare", &prepare), ("show", &show)] {
+            if let Some(instance) = instance {
+                let rows = instance.num_shared_rows();
+                let result = if blinds.len() == rows {
+                    Ok(())
+                } else {
+                    Err(format!("{} blinds for {rows} shared rows", blinds.len()))
+                };
+                report.record(format!("{name}: shared blinds fit the instance"), result);
+            }
+        }
+    }
+
+    if let (Some(prepare), Some(show)) = (&prepare, &show) {
+        let result = if shared_commitment(&prepare.comm_W_shared)
+            == shared_commitment(&show.comm_W_shared)
+        {
+            Ok(())
+        } else {
+            Err("the instances were proved from inputs that do not link".to_string())
+        };
+        report.record(
+            "prepare and show instances share comm_W_shared".to_string(),
+            result,
+        );
+    }
+
+    report
+}
+
+/// Check one circuit's artifacts against each other, returning its instance for the
+/// cross-circuit checks
+fn check_circuit(report: &mut DoctorReport, paths: CircuitPaths) -> Option<SplitR1CSInstance<E>> {
+    let name = paths.name;
+    let pk = report.load(
+        format!("{name}: proving key loads"),
+        paths.proving_key,
+        load_proving_key,
+    );
+    let vk = report.load(
+        format!("{name}: verifying key loads"),
+        paths.verifying_key,
+        load_verifying_key,
+    );
+    let proof = report.load(format!("{name}: proof loads"), paths.proof, load_proof);
+    let instance = report.load(
+        format!("{name}: instance loads"),
+        paths.instance,
+        load_instance,
+    );
+    let witness = report.load(
+        format!("{name}: witness loads"),
+        paths.witness,
+        load_witness,
+    );
+
+    if let (Some(pk), Some(vk)) = (&pk, &vk) {
+        let result = match vk.digest() {
+            Ok(digest) if digest == pk.vk_digest => Ok(()),
+            Ok(_) => Err("the keys come from different setups".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        report.record(format!("{name}: proving key matches verifying key"), result);
+    }
+
+    let mut public_values = None;
+    if let (Some(proof), Some(vk)) = (&proof, &vk) {
+        let verified = proof.verify(vk);
+        report.record(
+            format!("{name}: proof verifies against the verifying key"),
+            verified.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+        );
+        public_values = verified.ok();
+    }
+
+    if let (Some(instance), Some(proof)) = (&instance, &proof) {
+        let result = if shared_commitment(&instance.comm_W_shared)
+            != shared_commitment(&proof.comm_W_shared())
+        {
+            Err("comm_W_shared differs; the proof was made from another instance".to_string())
+        } else if public_values
+            .as_ref()
+            .is_some_and(|values| *values != instance.public_values)
+        {
+            Err("public values differ; the proof was made from another instance".to_string())
+        } else {
+            Ok(())
+        };
+        report.record(format!("{name}: instance matches the proof"), result);
+    }
+
+    if let (Some(pk), Some(instance), Some(witness)) = (&pk, &instance, &witness) {
+        report.record(
+            format!("{name}: witness satisfies the instance"),
+            pk.S.is_sat(&pk.ck, instance, witness)
+                .map_err(|e| e.to_string()),
+        );
+    }
+
+    instance
+}
+
+/// Bincode encoding of a shared commitment, for comparing commitments across artifacts
+fn shared_commitment<T: serde::Serialize>(commitment: &T) -> Vec<u8> {
+    bincode::serialize(commitment).expect("commitment serialization is infallible")
+}
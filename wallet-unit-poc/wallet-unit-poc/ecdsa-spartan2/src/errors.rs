@@ -0,0 +1,361 @@
+//! Error types for parsing and validating circuit inputs and verification packages, and the
+//! crate-level `L8zkError` returned by setup, proving, verification and artifact I/O
+
+use crate::manifest::ArtifactKind;
+use bellpepper_core::SynthesisError;
+use spartan2::errors::SpartanError;
+use std::{error::Error as StdError, io, path::PathBuf};
+use thiserror::Error;
+
+/// Errors raised while parsing or validating circuit input JSON
+#[derive(Debug, Error)]
+pub enum InputError {
+    /// The key-binding coordinates decoded from `cnf.jwk` do not satisfy the P-256 curve equation
+    #[error("key-binding point (x, y) is not on the P-256 curve")]
+    PointNotOnCurve,
+    /// The input file exceeds the configured maximum size and was not parsed
+    #[error("input is {size} bytes, exceeding the {limit}-byte limit")]
+    InputTooLarge { size: u64, limit: u64 },
+    /// `periodIndex` does not point at the `.` separating the JWT header from its payload.
+    /// `actual_byte` is `None` when the index is outside the signed message.
+    #[error(
+        "periodIndex {claimed} does not point at the '.' after the JWT header (found {actual_byte:?})"
+    )]
+    BadPeriodIndex {
+        claimed: u64,
+        actual_byte: Option<u8>,
+    },
+    /// Prepare and Show would commit a different number of claim scalars to `comm_W_shared`
+    #[error("Prepare commits {prepare} claim scalars but Show commits {show}")]
+    ClaimLengthMismatch { prepare: usize, show: usize },
+    /// `multiSig` inputs carry a different number of `sig_r` and `sig_s_inverse` entries
+    #[error("multiSig input has {sig_r} sig_r entries but {sig_s_inverse} sig_s_inverse entries")]
+    SignatureCountMismatch { sig_r: usize, sig_s_inverse: usize },
+    /// The circuit cannot verify this many signatures
+    #[error("input carries {count} signatures; the circuit verifies between 1 and {max}")]
+    UnsupportedSignatureCount { count: usize, max: usize },
+    /// A claim is not valid base64url
+    #[error("claim is not valid base64url: {0}")]
+    InvalidBase64Url(String),
+    /// A disclosed claim is neither a JSON member `"key":value` nor an SD-JWT disclosure
+    #[error("disclosed claim is not a JSON key-value pair: {0}")]
+    MalformedClaim(String),
+    /// A claim declared as `"hex"` is not valid hex
+    #[error("claim is not valid hex: {0}")]
+    InvalidHexClaim(String),
+    /// `claimEncoding` is not one of `base64url`, `hex` or `raw`
+    #[error("unknown claimEncoding {0}; expected base64url, hex or raw")]
+    UnknownClaimEncoding(String),
+    /// A claim row's length is not a multiple of 4, so it has no whole decoded length. The
+    /// circuits size their decoded claims as `(maxClaimsLength * 3) / 4`, which circom only
+    /// compiles for multiples of 4.
+    #[error("claim row has {length} characters; the maximum claim length must be a multiple of 4")]
+    UnalignedClaimLength { length: usize },
+    /// The disclosed claim decodes to more bytes than its decoded length holds
+    #[error("claim {index} decodes to {decoded} bytes, more than its decoded length of {length}")]
+    ClaimExceedsDecodedLength {
+        index: usize,
+        decoded: usize,
+        length: usize,
+    },
+    /// A witness value at a claim output position is larger than a byte
+    #[error("witness value {index} is an ageClaim output but is not a byte")]
+    ClaimOutputNotAByte { index: usize },
+    /// The message is not `header.payload` or `header.payload.signature`: it has fewer than two
+    /// or more than three `.`-separated parts
+    #[error("JWT has {parts} '.'-separated parts, expected header.payload[.signature]")]
+    MalformedJwt { parts: usize },
+    /// The JWT has no signature part, or it is not a base64url ES256 signature
+    #[error("JWT signature is malformed: {0}")]
+    MalformedJwtSignature(String),
+    /// The JWT signature does not verify against the issuer key
+    #[error("JWT signature does not verify against the issuer key pubKeyX/pubKeyY")]
+    JwtSignatureMismatch,
+    /// `decodeFlags` does not carry exactly one flag per claim
+    #[error("decodeFlags has {flags} entries but there are {claims} claims")]
+    DecodeFlagsLengthMismatch { flags: usize, claims: usize },
+    /// A `decodeFlags` entry is neither 0 (skip) nor 1 (decode)
+    #[error("decodeFlags[{index}] is {value}; expected 0 (skip) or 1 (decode)")]
+    InvalidDecodeFlag { index: usize, value: u64 },
+    /// An entry of an integer array field is neither a JSON number nor a decimal string
+    #[error("{field}[{index}] is {value}; expected a non-negative integer or decimal string")]
+    MalformedArrayEntry {
+        field: &'static str,
+        index: usize,
+        value: String,
+    },
+    /// `messageLength` is longer than the `message` array holding it
+    #[error("messageLength {message_length} exceeds the {max}-byte message")]
+    MessageLengthOutOfRange { message_length: u64, max: usize },
+    /// `claimLengths` does not carry exactly one length per claim
+    #[error("claimLengths has {claim_lengths} entries but there are {claims} claims")]
+    ClaimLengthsCountMismatch { claims: usize, claim_lengths: usize },
+    /// `ageClaimIndex` does not name one of the claims
+    #[error("ageClaimIndex {index} is out of range for {claims} claims")]
+    AgeClaimIndexOutOfRange { index: u64, claims: usize },
+    /// `matchIndex`, `matchLength` and `matchSubstring` disagree on the number of matches
+    #[error(
+        "match arrays disagree: matchIndex has {match_index} entries, matchLength \
+         {match_length}, matchSubstring {match_substring}"
+    )]
+    MatchArraysLengthMismatch {
+        match_index: usize,
+        match_length: usize,
+        match_substring: usize,
+    },
+    /// `matchesCount` is larger than the match arrays
+    #[error("matchesCount {matches_count} exceeds the {max} entries of the match arrays")]
+    MatchesCountOutOfRange { matches_count: u64, max: usize },
+    /// The input file is YAML or TOML but the crate was built without the feature to read it
+    #[error("reading this input requires building with the `{feature}` feature")]
+    UnsupportedInputFormat { feature: &'static str },
+    /// An object in the input JSON repeats a key (only checked by the strict reader)
+    #[error("input JSON repeats the field `{0}`")]
+    DuplicateField(String),
+    /// The rust-witness generator panicked on these inputs, or returned a witness of the
+    /// wrong length for the circuit
+    #[error("witness generation for {circuit} failed: {reason}")]
+    WitnessGenFailed {
+        circuit: &'static str,
+        reason: String,
+    },
+    /// A field required by the circuit is missing or malformed
+    #[error(transparent)]
+    Synthesis(#[from] SynthesisError),
+}
+
+/// Errors raised while unpacking or verifying a verification package
+#[derive(Debug, Error)]
+pub enum PackageError {
+    /// The package was written by an incompatible version of this crate
+    #[error("unsupported package version {found}; expected {expected}")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    /// The bundled digest does not match the key, proof and public values
+    #[error("package digest does not match its contents")]
+    DigestMismatch,
+    /// The proof verified but its public values differ from the ones in the package
+    #[error("proof public values do not match the package")]
+    PublicValuesMismatch,
+    #[error("failed to decode package: {0}")]
+    Decode(#[from] bincode::Error),
+    #[error("proof verification failed: {0}")]
+    Verification(#[from] SpartanError),
+}
+
+/// Errors raised while building or reading a public-input descriptor
+#[derive(Debug, Error)]
+pub enum DescriptorError {
+    /// Every public value needs exactly one label
+    #[error("{labels} labels for {values} public values")]
+    LabelCountMismatch { labels: usize, values: usize },
+    /// A recorded value is not the canonical hex of a field element
+    #[error("public input {label} is not a field element: {value}")]
+    InvalidValue { label: String, value: String },
+    /// The descriptor was written by an incompatible version of this crate
+    #[error("unsupported descriptor version {found}; expected {expected}")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    #[error("failed to decode descriptor: {0}")]
+    Decode(#[from] serde_json::Error),
+}
+
+/// Errors raised while verifying a Show proof against a verifier-issued nonce
+#[derive(Debug, Error)]
+pub enum NonceError {
+    #[error("proof verification failed: {0}")]
+    Verification(#[from] SpartanError),
+    /// The proof verified, but for a different nonce (e.g. a replayed proof)
+    #[error("proof nonce does not match the expected nonce")]
+    NonceMismatch,
+}
+
+/// Errors raised when a Prepare and a Show proof were not produced under parameters that can
+/// link
+#[derive(Debug, Error)]
+pub enum LinkError {
+    /// The proof was not made with the given verifying key's circuit and parameters; the
+    /// verifier's transcript starts from the key's digest, so any other key rejects it
+    #[error("{circuit} proof was not produced under its verifying key: {source}")]
+    WrongParameters {
+        circuit: &'static str,
+        #[source]
+        source: SpartanError,
+    },
+    /// The proofs commit a different number of shared rows, so their `comm_W_shared` cannot
+    /// describe the same values
+    #[error("Prepare proof commits {prepare} shared rows but the Show proof commits {show}")]
+    SharedRowsMismatch { prepare: usize, show: usize },
+}
+
+/// Errors raised while loading `l8zk.toml`
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read config {}: {source}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    /// The file is not valid TOML, has an unknown key or an invalid value
+    #[error("invalid config {}: {reason}", path.display())]
+    Invalid { path: PathBuf, reason: String },
+}
+
+/// Errors raised while loading a saved artifact
+#[derive(Debug, Error)]
+pub enum ArtifactError {
+    /// The file is a different kind of artifact, e.g. a proving key passed as the verifying key
+    #[error("expected a {expected:?} file but found a {found:?}")]
+    WrongArtifactKind {
+        expected: ArtifactKind,
+        found: ArtifactKind,
+    },
+    /// A verifying key was requested from a proving key, which does not contain one
+    #[error(
+        "a verifying key cannot be derived from a proving key: the proving key stores only the \
+         verifying key's digest, and Spartan2 builds verifying keys only in setup; re-run setup \
+         for this circuit"
+    )]
+    VerifyingKeyNotDerivable,
+    /// Shared blinds were saved for another engine, so they cannot reblind this engine's
+    /// instances
+    #[error(
+        "shared blinds were saved for {found} ({found_scalar_bytes}-byte scalars), not \
+         {expected} ({expected_scalar_bytes}-byte scalars)"
+    )]
+    SharedBlindsEngineMismatch {
+        found: String,
+        found_scalar_bytes: usize,
+        expected: String,
+        expected_scalar_bytes: usize,
+    },
+    /// A shared blinds file holds fewer or more blinds than its envelope declares
+    #[error("shared blinds file declares {declared} blinds but holds {found}")]
+    SharedBlindsCountMismatch { declared: usize, found: usize },
+    /// A content-addressed file does not hash to its name, so it was altered or truncated
+    #[error("{path} does not match the hash it is stored under")]
+    ContentHashMismatch { path: String },
+    /// Writing the file failed, either with an error that retrying cannot fix or on every
+    /// attempt allowed by `WriteRetry`
+    #[error("could not write {path} after {attempts} attempt(s): {source}")]
+    WriteFailed {
+        path: String,
+        attempts: u32,
+        #[source]
+        source: io::Error,
+    },
+    /// The manifest records that the artifact was made with another version of the circuit
+    #[error(
+        "{path} was made with circuit version ({found}) but this binary runs ({expected}); \
+         re-run setup and prove with the current circuit"
+    )]
+    CircuitVersionMismatch {
+        path: String,
+        found: String,
+        expected: String,
+    },
+    /// The manifest records a circuit version for the artifact, but the current one could not
+    /// be computed to compare it with
+    #[error("{path} is stamped with circuit version ({found}) but it cannot be checked: {reason}")]
+    CircuitVersionUncheckable {
+        path: String,
+        found: String,
+        reason: String,
+    },
+    /// The file uses an encoding whose cargo feature is not enabled
+    #[error("{path} is encoded with {encoding}, which needs the `{feature}` feature")]
+    EncodingNotEnabled {
+        path: String,
+        encoding: &'static str,
+        feature: &'static str,
+    },
+}
+
+/// Verification could not be carried out, as opposed to a proof that was checked and found
+/// invalid
+#[derive(Debug, Error)]
+pub enum VerifyError {
+    /// The proof or verifying key could not be found or decoded
+    #[error("could not load the proof or verifying key: {0}")]
+    Load(#[source] Box<L8zkError>),
+    /// Spartan2 could not run the check, e.g. because the verifying key is corrupt or belongs
+    /// to another engine
+    #[error("verification could not run: {0}")]
+    Spartan(#[source] SpartanError),
+    /// The proof verifies under none of the verifying keys `verify_against_any` was given
+    #[error("the proof does not verify under any of the {keys} allowed verifying keys")]
+    NoMatchingKey { keys: usize },
+}
+
+/// Crate-level error returned by setup, proving, verification and artifact I/O.
+///
+/// Setup, proving and verification all fail with a `SpartanError`, so those variants are
+/// constructed explicitly at the stage that failed rather than through `From`.
+#[derive(Debug, Error)]
+pub enum L8zkError {
+    /// Circuit inputs could not be parsed or validated
+    #[error(transparent)]
+    Input(#[from] InputError),
+    /// Spartan setup failed
+    #[error("setup failed: {0}")]
+    Setup(#[source] SpartanError),
+    /// Spartan proving or reblinding failed
+    #[error("proving failed: {0}")]
+    Prove(#[source] SpartanError),
+    /// The proof was checked and found invalid
+    #[error("proof verification failed: {0}")]
+    Verify(#[source] SpartanError),
+    /// Verification could not be carried out, so nothing is known about the proof
+    #[error(transparent)]
+    Unverifiable(#[from] VerifyError),
+    /// A key, proof, instance, witness, blinds or manifest file could not be read or written
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// An artifact or manifest could not be encoded or decoded
+    #[error("serialization failed: {0}")]
+    Serialization(#[source] Box<dyn StdError + Send + Sync>),
+    /// A saved artifact is of the wrong kind or cannot be produced
+    #[error(transparent)]
+    Artifact(#[from] ArtifactError),
+    /// Reblinding was given a different number of shared blinds than the instance has shared rows
+    #[error("expected {expected} shared blinds, got {found}")]
+    SharedBlindsMismatch { expected: usize, found: usize },
+    /// The public inputs supplied at prove or verify time are not the ones the circuit exposes
+    /// or the proof was made for
+    #[error("the supplied public inputs do not match the proof's public values")]
+    PublicInputsMismatch,
+    /// An artifact produced by an earlier step (setup, prove, ...) has not been written yet
+    #[error("{path} not found: {hint}")]
+    MissingArtifact { path: String, hint: String },
+    /// The witness commitment is estimated to need more memory than the prover was allowed
+    #[error(
+        "committing to the witness needs about {estimated} bytes, over the {budget}-byte budget"
+    )]
+    CommitMemoryExceeded { estimated: usize, budget: usize },
+    /// A proof's public-input descriptor is malformed
+    #[error(transparent)]
+    Descriptor(#[from] DescriptorError),
+}
+
+impl From<SynthesisError> for L8zkError {
+    fn from(err: SynthesisError) -> Self {
+        L8zkError::Input(InputError::Synthesis(err))
+    }
+}
+
+impl From<bincode::Error> for L8zkError {
+    fn from(err: bincode::Error) -> Self {
+        L8zkError::Serialization(err)
+    }
+}
+
+impl From<serde_json::Error> for L8zkError {
+    fn from(err: serde_json::Error) -> Self {
+        L8zkError::Serialization(Box::new(err))
+    }
+}
+
+impl From<InputError> for SynthesisError {
+    fn from(err: InputError) -> Self {
+        match err {
+            InputError::Synthesis(e) => e,
+            // Keep the validation message so it surfaces through Spartan's error reason
+            other => SynthesisError::IoError(io::Error::new(io::ErrorKind::InvalidData, other)),
+        }
+    }
+}
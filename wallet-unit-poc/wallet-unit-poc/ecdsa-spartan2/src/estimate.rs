@@ -0,0 +1,454 @@
+//! Proof-size estimates from the R1CS shape, for capacity planning without proving.
+//!
+//! The model counts the parts of a ZK-Spartan proof with a Hyrax commitment:
+//! - one group element per Hyrax row of the witness commitment,
+//! - the row-combined opening vector (one scalar per Hyrax column),
+//! - the outer (degree 3) and inner (degree 2) sumcheck round polynomials.
+//!
+//! The constants are calibrated against the proof sizes in the README but have not been
+//! checked against other circuits; treat the result as an estimate, not an exact size.
+//!
+//! `proof_size_breakdown` measures an actual proof instead. Spartan2 keeps the proof's fields
+//! private but serializes them in order, so each field's bincode size is taken as it passes
+//! through serde.
+//!
+//! `estimate_commit_memory` predicts the memory the Hyrax witness commitment holds at once,
+//! which `prove_within_memory` checks against a budget before proving.
+//!
+//! `estimate_pipeline` predicts how long `benchmark` takes. Setup is measured; prove, reblind
+//! and verify are extrapolated linearly in the constraint count from a `CostModel` timed on a
+//! small circuit. Spartan's costs are not linear at every size, so expect the prediction to be
+//! off by a factor of up to about two.
+
+use std::{fmt, path::Path, time::Instant};
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::{derive::rand_core::OsRng, Field};
+use serde::{
+    ser::{self, Impossible, SerializeStruct},
+    Serialize, Serializer,
+};
+use spartan2::{traits::circuit::SpartanCircuit, zk_spartan::R1CSSNARK};
+
+use crate::{
+    circuits::common::load_r1cs_file,
+    errors::L8zkError,
+    prover::{prove_circuit_in_memory, reblind_bundle, verify_circuit_with_loaded_data},
+    setup::setup_circuit_keys_no_save,
+    Scalar, E,
+};
+
+/// Hyrax columns per committed row
+pub const HYRAX_NUM_COLS: usize = 1024;
+/// Serialized size of a compressed T256 group element
+pub const GROUP_ELEMENT_BYTES: usize = 33;
+/// Serialized size of a scalar
+pub const SCALAR_BYTES: usize = 32;
+
+/// In-memory size of a projective T256 group element (three coordinates)
+const PROJECTIVE_POINT_BYTES: usize = 3 * SCALAR_BYTES;
+
+/// Bytes the Hyrax commitment to a witness of `num_variables` holds at once: the witness padded
+/// to a power of two, plus one blind and one projective row commitment per Hyrax row.
+///
+/// Spartan2 commits to the whole witness in one call, so this cannot be lowered by committing
+/// in chunks; it is a lower bound on the prover's peak, which also holds the R1CS matrices and
+/// sumcheck tables.
+pub fn estimate_commit_memory(num_variables: usize) -> usize {
+    let padded_vars = num_variables.max(1).next_power_of_two();
+    let num_rows = padded_vars.div_ceil(HYRAX_NUM_COLS);
+    padded_vars * SCALAR_BYTES + num_rows * (SCALAR_BYTES + PROJECTIVE_POINT_BYTES)
+}
+
+/// Predicted proof size, broken down by component
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofSizeEstimate {
+    pub num_constraints: usize,
+    pub num_variables: usize,
+    pub witness_commitment_bytes: usize,
+    pub opening_bytes: usize,
+    pub sumcheck_bytes: usize,
+}
+
+impl ProofSizeEstimate {
+    pub fn total_bytes(&self) -> usize {
+        self.witness_commitment_bytes + self.opening_bytes + self.sumcheck_bytes
+    }
+}
+
+/// Constraints in the circuit `CostModel::calibrate` times
+pub const CALIBRATION_CONSTRAINTS: usize = 1 << 14;
+
+/// Milliseconds per constraint of each step after setup
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CostModel {
+    pub prove_ms_per_constraint: f64,
+    pub reblind_ms_per_constraint: f64,
+    pub verify_ms_per_constraint: f64,
+}
+
+impl CostModel {
+    /// Set up, prove, reblind and verify a circuit of `CALIBRATION_CONSTRAINTS` squarings and
+    /// divide each step's time by its constraint count. Takes a few seconds.
+    pub fn calibrate() -> Result<Self, L8zkError> {
+        let circuit = CalibrationCircuit {
+            constraints: CALIBRATION_CONSTRAINTS,
+        };
+        let (pk, vk) = setup_circuit_keys_no_save(circuit.clone())?;
+
+        let t0 = Instant::now();
+        let proved = prove_circuit_in_memory(circuit, &pk)?;
+        let prove_ms = elapsed_ms(t0);
+
+        let blinds: Vec<Scalar> = (0..proved.instance.num_shared_rows())
+            .map(|_| Scalar::random(OsRng))
+            .collect();
+        let t0 = Instant::now();
+        let reblinded = reblind_bundle(proved, &pk, &blinds)?;
+        let reblind_ms = elapsed_ms(t0);
+
+        let t0 = Instant::now();
+        verify_circuit_with_loaded_data(&reblinded.proof, &vk)?;
+        let verify_ms = elapsed_ms(t0);
+
+        let per_constraint = |ms: f64| ms / CALIBRATION_CONSTRAINTS as f64;
+        Ok(Self {
+            prove_ms_per_constraint: per_constraint(prove_ms),
+            reblind_ms_per_constraint: per_constraint(reblind_ms),
+            verify_ms_per_constraint: per_constraint(verify_ms),
+        })
+    }
+}
+
+fn elapsed_ms(t0: Instant) -> f64 {
+    t0.elapsed().as_secs_f64() * 1000.0
+}
+
+/// `x^(2^n)` by repeated squaring: one shared value and one constraint per squaring
+#[derive(Debug, Clone)]
+struct CalibrationCircuit {
+    constraints: usize,
+}
+
+impl SpartanCircuit<E> for CalibrationCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<Scalar>],
+        _: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let mut value = shared[0].clone();
+        for i in 0..self.constraints {
+            value = value.square(cs.namespace(|| format!("square_{i}")))?;
+        }
+        Ok(())
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![AllocatedNum::alloc(cs.namespace(|| "x"), || {
+            Ok(Scalar::from(3u64))
+        })?])
+    }
+
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+/// A circuit's constraint count and measured setup time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MeasuredSetup {
+    pub constraints: usize,
+    pub setup_ms: f64,
+}
+
+/// Duration of one `benchmark` step
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepEstimate {
+    pub step: &'static str,
+    pub ms: f64,
+    /// Whether the time was measured rather than extrapolated
+    pub measured: bool,
+}
+
+/// Predicted duration of each `benchmark` step
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PipelineEstimate {
+    pub steps: Vec<StepEstimate>,
+}
+
+impl PipelineEstimate {
+    pub fn total_ms(&self) -> f64 {
+        self.steps.iter().map(|step| step.ms).sum()
+    }
+}
+
+/// Predict the `benchmark` steps from each circuit's measured setup and `model`
+pub fn estimate_pipeline(
+    model: &CostModel,
+    prepare: MeasuredSetup,
+    show: MeasuredSetup,
+) -> PipelineEstimate {
+    let measured = |step, ms| StepEstimate {
+        step,
+        ms,
+        measured: true,
+    };
+    let extrapolated = |step, ms_per_constraint: f64, setup: MeasuredSetup| StepEstimate {
+        step,
+        ms: ms_per_constraint * setup.constraints as f64,
+        measured: false,
+    };
+
+    PipelineEstimate {
+        steps: vec![
+            measured("Prepare setup", prepare.setup_ms),
+            measured("Show setup", show.setup_ms),
+            extrapolated("Prove Prepare", model.prove_ms_per_constraint, prepare),
+            extrapolated("Reblind Prepare", model.reblind_ms_per_constraint, prepare),
+            extrapolated("Prove Show", model.prove_ms_per_constraint, show),
+            extrapolated("Reblind Show", model.reblind_ms_per_constraint, show),
+            extrapolated("Verify Prepare", model.verify_ms_per_constraint, prepare),
+            extrapolated("Verify Show", model.verify_ms_per_constraint, show),
+        ],
+    }
+}
+
+/// Estimate the proof size for a circuit with the given constraint and variable counts
+pub fn estimate_proof_size(num_constraints: usize, num_variables: usize) -> ProofSizeEstimate {
+    // Spartan pads both dimensions to powers of two
+    let padded_vars = num_variables.max(1).next_power_of_two();
+    let padded_cons = num_constraints.max(1).next_power_of_two();
+
+    let num_rows = padded_vars.div_ceil(HYRAX_NUM_COLS);
+    let outer_rounds = padded_cons.trailing_zeros() as usize;
+    let inner_rounds = padded_vars.trailing_zeros() as usize;
+
+    ProofSizeEstimate {
+        num_constraints,
+        num_variables,
+        witness_commitment_bytes: num_rows * GROUP_ELEMENT_BYTES,
+        opening_bytes: HYRAX_NUM_COLS.min(padded_vars) * SCALAR_BYTES,
+        sumcheck_bytes: (outer_rounds * 4 + inner_rounds * 3) * SCALAR_BYTES,
+    }
+}
+
+/// Estimate the proof size of the circuit compiled to the `.r1cs` file at `r1cs_path`.
+/// Fails with `MissingArtifact` when the file does not exist.
+pub fn estimate_proof_size_from_r1cs(r1cs_path: &Path) -> Result<ProofSizeEstimate, L8zkError> {
+    let r1cs = load_r1cs_file(r1cs_path)?;
+    Ok(estimate_proof_size(
+        r1cs.constraints.len(),
+        r1cs.num_variables,
+    ))
+}
+
+/// Serialized size of one field of a proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ComponentSize {
+    /// Spartan2's name for the field, e.g. a sumcheck proof, commitment or evaluation argument
+    pub name: &'static str,
+    pub bytes: u64,
+}
+
+/// Measured size of a proof, per field in serialization order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SizeBreakdown {
+    pub components: Vec<ComponentSize>,
+}
+
+impl SizeBreakdown {
+    /// Equal to the size of the bincode-encoded proof
+    pub fn total_bytes(&self) -> u64 {
+        self.components
+            .iter()
+            .map(|component| component.bytes)
+            .sum()
+    }
+}
+
+/// Bincode size of each top-level field of `proof`. The fields are Spartan2's own (sumcheck
+/// proofs, witness commitments, evaluation arguments, ...), so their names follow its version.
+pub fn proof_size_breakdown(proof: &R1CSSNARK<E>) -> Result<SizeBreakdown, L8zkError> {
+    let mut sizer = FieldSizer::default();
+    proof
+        .serialize(&mut sizer)
+        .map_err(|e| L8zkError::Serialization(Box::new(e)))?;
+    Ok(sizer.breakdown)
+}
+
+/// Serializer that records the bincode size of each field of a struct instead of encoding it
+#[derive(Default)]
+struct FieldSizer {
+    breakdown: SizeBreakdown,
+}
+
+#[derive(Debug)]
+struct SizeError(String);
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot break down proof size: {}", self.0)
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+impl ser::Error for SizeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SizeError(msg.to_string())
+    }
+}
+
+fn not_a_struct() -> SizeError {
+    SizeError("the proof does not serialize as a struct".to_string())
+}
+
+/// Methods for values other than structs, which a proof never serializes as
+macro_rules! reject_non_struct {
+    ($($method:ident($($ty:ty),*);)*) => {
+        $(fn $method(self, $(_: $ty),*) -> Result<(), SizeError> {
+            Err(not_a_struct())
+        })*
+    };
+}
+
+impl Serializer for &mut FieldSizer {
+    type Ok = ();
+    type Error = SizeError;
+    type SerializeSeq = Impossible<(), SizeError>;
+    type SerializeTuple = Impossible<(), SizeError>;
+    type SerializeTupleStruct = Impossible<(), SizeError>;
+    type SerializeTupleVariant = Impossible<(), SizeError>;
+    type SerializeMap = Impossible<(), SizeError>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), SizeError>;
+
+    reject_non_struct! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<(), SizeError> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), SizeError> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SizeError> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SizeError> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SizeError> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SizeError> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SizeError> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SizeError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SizeError> {
+        Err(not_a_struct())
+    }
+}
+
+impl SerializeStruct for &mut FieldSizer {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        let bytes = bincode::serialized_size(value).map_err(|e| SizeError(e.to_string()))?;
+        self.breakdown
+            .components
+            .push(ComponentSize { name, bytes });
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
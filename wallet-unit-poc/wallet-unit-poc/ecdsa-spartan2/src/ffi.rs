@@ -0,0 +1,101 @@
+//! C ABI for embedding the verifier in other languages (Swift, Kotlin, Go, ...).
+//!
+//! Enabled with the `cdylib` feature. Build a shared library with:
+//!   cargo rustc --release --lib --features cdylib --crate-type cdylib
+//!
+//! Byte buffers cross the boundary as `(ptr, len)` pairs holding the same encodings written
+//! by `save_proof` and `save_keys` (the verifying key may be tagged or untagged). Callers that want Rust-owned
+//! memory can obtain it with `l8zk_alloc` and must release it with `l8zk_free`.
+
+use std::{panic::catch_unwind, ptr, slice};
+
+use spartan2::{traits::snark::R1CSSNARKTrait, zk_spartan::R1CSSNARK};
+
+use crate::{
+    manifest::ArtifactKind,
+    prover::{verify_verdict, Verdict},
+    setup::strip_key_tag,
+    E,
+};
+
+/// The proof verified under the supplied verifying key
+pub const L8ZK_VERIFY_OK: i32 = 1;
+/// The proof and key decoded, but the proof did not verify
+pub const L8ZK_VERIFY_REJECTED: i32 = 0;
+/// A null buffer was passed in
+pub const L8ZK_ERR_NULL_POINTER: i32 = -1;
+/// The proof bytes could not be decoded
+pub const L8ZK_ERR_INVALID_PROOF: i32 = -2;
+/// The verifying key bytes could not be decoded
+pub const L8ZK_ERR_INVALID_VK: i32 = -3;
+/// Verification panicked; no result is available
+pub const L8ZK_ERR_PANIC: i32 = -4;
+/// The proof and key decoded, but verification could not run (e.g. the key is corrupt)
+pub const L8ZK_ERR_VERIFY: i32 = -5;
+
+/// Verify a bincode-encoded proof against a bincode-encoded verifying key.
+///
+/// Returns `L8ZK_VERIFY_OK` (1), `L8ZK_VERIFY_REJECTED` (0), or a negative error code.
+///
+/// # Safety
+///
+/// `proof_ptr` and `vk_ptr` must point to readable buffers of at least `proof_len` and
+/// `vk_len` bytes respectively, valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn l8zk_verify(
+    proof_ptr: *const u8,
+    proof_len: usize,
+    vk_ptr: *const u8,
+    vk_len: usize,
+) -> i32 {
+    if proof_ptr.is_null() || vk_ptr.is_null() {
+        return L8ZK_ERR_NULL_POINTER;
+    }
+
+    let proof_bytes = slice::from_raw_parts(proof_ptr, proof_len);
+    let vk_bytes = slice::from_raw_parts(vk_ptr, vk_len);
+
+    catch_unwind(|| verify_bytes(proof_bytes, vk_bytes)).unwrap_or(L8ZK_ERR_PANIC)
+}
+
+/// Allocate a zeroed buffer of `len` bytes owned by this library.
+/// The buffer must be released with `l8zk_free` using the same `len`.
+#[no_mangle]
+pub extern "C" fn l8zk_alloc(len: usize) -> *mut u8 {
+    if len == 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(vec![0u8; len].into_boxed_slice()) as *mut u8
+}
+
+/// Release a buffer previously returned by `l8zk_alloc`.
+///
+/// # Safety
+///
+/// `ptr` must be null or a pointer returned by `l8zk_alloc(len)` that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn l8zk_free(ptr: *mut u8, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)));
+}
+
+fn verify_bytes(proof_bytes: &[u8], vk_bytes: &[u8]) -> i32 {
+    let Ok(proof) = bincode::deserialize::<R1CSSNARK<E>>(proof_bytes) else {
+        return L8ZK_ERR_INVALID_PROOF;
+    };
+    let Ok(vk_bytes) = strip_key_tag(vk_bytes, ArtifactKind::VerifyingKey) else {
+        return L8ZK_ERR_INVALID_VK;
+    };
+    let Ok(vk) = bincode::deserialize::<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey>(vk_bytes)
+    else {
+        return L8ZK_ERR_INVALID_VK;
+    };
+
+    match verify_verdict(&proof, &vk) {
+        Ok(Verdict::Valid) => L8ZK_VERIFY_OK,
+        Ok(Verdict::Invalid(_)) => L8ZK_VERIFY_REJECTED,
+        Err(_) => L8ZK_ERR_VERIFY,
+    }
+}
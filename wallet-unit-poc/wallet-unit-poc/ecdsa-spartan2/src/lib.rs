@@ -12,23 +12,92 @@ pub type E = T256HyraxEngine;
 pub type Scalar = <E as Engine>::Scalar;
 
 pub mod circuits;
+pub mod cli;
+pub mod commitment;
+pub mod config;
+pub mod descriptor;
+pub mod doctor;
+pub mod errors;
+pub mod estimate;
+#[cfg(feature = "cdylib")]
+pub mod ffi;
+pub mod manifest;
+pub mod metrics;
+pub mod package;
 pub mod prover;
+pub mod secret;
 pub mod setup;
+pub mod shape;
+mod strict_json;
 pub mod utils;
+pub mod version;
 
 // Re-export commonly used types and functions
-pub use circuits::{prepare_circuit::PrepareCircuit, show_circuit::ShowCircuit};
+pub use circuits::{
+    common::{check_jwt_output_layout, OutputLayoutCheck},
+    prepare_circuit::{PrepareCircuit, CHECK_LAYOUT_ENV},
+    show_circuit::{find_age_boundary, ShowCircuit},
+};
+pub use commitment::{commit_claim, open_claim_commitment, ClaimCommitment};
+pub use config::{take_config_flag, Config, CONFIG_FILE};
+pub use descriptor::{
+    descriptor_path, load_descriptor, save_descriptor, verify_with_descriptor, PublicInput,
+    PublicInputsDescriptor, DESCRIPTOR_VERSION, PREPARE_PUBLIC_INPUTS, SHOW_PUBLIC_INPUTS,
+};
+pub use doctor::{doctor, DoctorCheck, DoctorReport};
+pub use errors::{
+    ArtifactError, ConfigError, DescriptorError, InputError, L8zkError, LinkError, NonceError,
+    PackageError, VerifyError,
+};
+pub use estimate::{
+    estimate_commit_memory, estimate_pipeline, estimate_proof_size, estimate_proof_size_from_r1cs,
+    proof_size_breakdown, ComponentSize, CostModel, MeasuredSetup, PipelineEstimate,
+    ProofSizeEstimate, SizeBreakdown, StepEstimate, CALIBRATION_CONSTRAINTS,
+};
+pub use manifest::{load_manifest, record_circuit_version, ArtifactEntry, ArtifactKind, Manifest};
+pub use metrics::{LatencySummary, SynthesisCounters, SynthesisCounts};
+pub use package::{export_verification_package, verify_package, VerifyOutput};
 pub use prover::{
-    generate_prepare_witness, generate_shared_blinds, prove_circuit, prove_circuit_with_pk,
-    reblind, reblind_with_loaded_data, run_circuit, verify_circuit,
-    verify_circuit_with_loaded_data,
+    assert_same_parameters, benchmark_verify, check_prepare_witness, circuit_public_values,
+    generate_linked_witnesses, generate_prepare_witness, generate_prepare_witness_from_json,
+    generate_prepare_witness_with_limit, generate_shared_blinds, generate_shared_blinds_for,
+    generate_shared_blinds_seeded, instance_to_json, minimize_proof, prove_circuit,
+    prove_circuit_in_memory, prove_circuit_in_memory_with_public_inputs, prove_circuit_to_writer,
+    prove_circuit_with_pk, prove_dir, prove_linked_in_memory, prove_within_memory,
+    r1cs_witness_to_scalars, reblind, reblind_batch, reblind_bundle, reblind_with_loaded_data,
+    run_circuit, scalars_to_r1cs_witness, verify_against_any, verify_batch_within, verify_circuit,
+    verify_circuit_verdict, verify_circuit_with_loaded_data, verify_dir, verify_from_reader,
+    verify_show_with_nonce, verify_verdict, verify_with_public_inputs, BatchVerifyReport,
+    MinimizeReport, ProofNaming, ProveDirReport, ProveOutput, ProverContext, Verdict,
+    VerifyDirReport, WitnessCheck,
 };
+pub use secret::{zeroize_scalars, SecretScalars};
 pub use setup::{
     load_instance, load_proof, load_proving_key, load_shared_blinds, load_verifying_key,
-    load_witness, save_keys, setup_circuit_keys, setup_circuit_keys_no_save, PREPARE_PROVING_KEY,
-    PREPARE_VERIFYING_KEY, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY,
+    load_witness, save_keys, save_verifying_key, setup_circuit_keys, setup_circuit_keys_no_save,
+    write_with_retry, Paths, ProofStore, SharedBlindsEnvelope, WriteRetry, CBOR_PROOF_TAG,
+    DEFAULT_WRITE_ATTEMPTS, PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY, SHARED_BLINDS_TAG,
+    SHOW_PROVING_KEY, SHOW_VERIFYING_KEY,
 };
+#[cfg(feature = "cbor")]
+pub use setup::{load_proof_cbor, save_proof_cbor};
+pub use shape::{dump_shape, NamespaceShape, ShapeDump};
 pub use utils::{
-    bigint_to_scalar, calculate_jwt_output_indices, convert_bigint_to_scalar, parse_jwt_inputs,
-    parse_show_inputs,
+    assert_prepare_show_claim_lengths_match, bigint_to_scalar, build_message_array,
+    calculate_jwt_output_indices, check_link, compute_message_hash, compute_show_shared_scalars,
+    convert_bigint_to_scalar, decode_base64url_strict, decoded_claim_len, detect_circuit_kind,
+    disclosed_claim_range, discloses_claim, extract_jwt_outputs, extract_prepare_shared_data,
+    format_scalar, is_on_p256_curve, list_jwt_claims, pack_shared_scalars, parse_decode_flags,
+    parse_disclosed_claim, parse_jwt_inputs, parse_show_inputs, prepare_input_schema,
+    read_input_json, read_input_json_strict, reconstruct_disclosure, scalar_to_hex,
+    show_input_schema, split_jwt, validate_decode_flags, validate_jwt_input_schema,
+    validate_jwt_issuer_signature, validate_period_index, verify_ecdsa_offcircuit, ClaimEncoding,
+    ClaimInfo, DecodeFlag, DisclosedData, InputCircuit, InputFormat, InputSource, JwtPart,
+    JwtParts, LinkCheck, MultiClaimLayout, CLAIM_ENCODING_FIELD, DECODED_CLAIM_LENGTHS_FIELD,
+    DEFAULT_MAX_INPUT_SIZE, DISCLOSED_CLAIM_RANGE_FIELD, DISCLOSE_CLAIM_FIELD, JWT_MAX_SIGNATURES,
+    PARALLEL_PARSE_THRESHOLD, SHOW_NONCE_FIELD,
+};
+pub use version::{
+    check_build_version, check_circuit_version, recorded_circuit_version, CircuitVersion,
+    CIRCOM_ARTIFACTS_SHA256,
 };
@@ -10,45 +10,71 @@
 //!
 //! Typical post-keygen flow:
 //! 0. `prepare setup` and `show setup` — load proving/verification keys and witnesses for each circuit.
-//! 1. `generate_shared_blinds` — derive shared blinding factors used by both circuits.
-//! 2. `prove_prepare` — produce the initial Prepare proof.
+//! 1. `prove_prepare` — produce the initial Prepare proof.
+//! 2. `generate_shared_blinds` — derive one shared blinding factor per shared row of the Prepare instance.
 //! 3. `reblind_prepare` — reblind the Prepare proof without changing its `comm_W_shared`.
 //! 4. `prove_show` — produce the Show proof using the shared witness commitment.
 //! 5. `reblind_show` — reblind the Show proof; the reblinded proof maintains the same `comm_W_shared` as step 3.
 //!
 //! Every proof emitted in this sequence (including the reblinded variants) should verify successfully.
 
+use ecdsa_spartan2::cli::{
+    parse_command_with_config, print_usage, CircuitAction, CircuitKind, CommandOptions,
+    OutputFormat, DEFAULT_VERIFY_ITERATIONS,
+};
+use ecdsa_spartan2::metrics::{metrics_to_json, render_prometheus, Metric};
 use ecdsa_spartan2::{
-    generate_shared_blinds, load_instance, load_proof, load_shared_blinds, load_witness,
-    prove_circuit, prove_circuit_with_pk, reblind, reblind_with_loaded_data, run_circuit,
-    save_keys, setup::PREPARE_INSTANCE, setup::PREPARE_PROOF, setup::PREPARE_PROVING_KEY,
-    setup::PREPARE_VERIFYING_KEY, setup::PREPARE_WITNESS, setup::SHARED_BLINDS,
-    setup::SHOW_INSTANCE, setup::SHOW_PROOF, setup::SHOW_PROVING_KEY, setup::SHOW_VERIFYING_KEY,
-    setup::SHOW_WITNESS, setup_circuit_keys, setup_circuit_keys_no_save, verify_circuit,
-    verify_circuit_with_loaded_data, PrepareCircuit, ShowCircuit, E,
+    benchmark_verify, bigint_to_scalar, check_circuit_version, check_link, check_prepare_witness,
+    circuit_public_values, descriptor_path, detect_circuit_kind, doctor, dump_shape,
+    estimate_pipeline, estimate_proof_size_from_r1cs, generate_shared_blinds_for,
+    generate_shared_blinds_seeded, instance_to_json, load_descriptor, load_instance, load_proof,
+    load_proving_key, load_verifying_key, load_witness, minimize_proof, prepare_input_schema,
+    proof_size_breakdown, prove_circuit, prove_circuit_to_writer, prove_circuit_with_pk, prove_dir,
+    read_input_json, reblind, reblind_with_loaded_data, record_circuit_version,
+    recorded_circuit_version, run_circuit, save_descriptor, save_keys, setup_circuit_keys,
+    setup_circuit_keys_no_save, show_input_schema, take_config_flag, verify_circuit,
+    verify_circuit_with_loaded_data, verify_dir, verify_with_descriptor, verify_with_public_inputs,
+    ArtifactError, CircuitVersion, Config, CostModel, InputCircuit, InputSource, L8zkError,
+    MeasuredSetup, Paths, PrepareCircuit, ProofSizeEstimate, PublicInputsDescriptor, ShowCircuit,
+    SizeBreakdown, CALIBRATION_CONSTRAINTS, CHECK_LAYOUT_ENV, DEFAULT_MAX_INPUT_SIZE, E,
+    SHOW_PUBLIC_INPUTS,
+};
+use serde::Serialize;
+use spartan2::traits::circuit::SpartanCircuit;
+use std::{
+    env::{self, args},
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+    time::Instant,
 };
-use std::{env::args, fs, path::PathBuf, process, time::Instant};
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::EnvFilter;
 
-const NUM_SHARED: usize = 1;
-
 /// Helper function to get file size in bytes
 fn get_file_size(path: &str) -> u64 {
     fs::metadata(path).map(|m| m.len()).unwrap_or(0)
 }
 
-#[derive(Debug)]
+/// A benchmark step that failed while running with `--keep-going`
+#[derive(Debug, Serialize)]
+struct StepFailure {
+    step: &'static str,
+    error: String,
+}
+
+#[derive(Debug, Serialize)]
 struct BenchmarkResults {
-    prepare_setup_ms: u128,
-    show_setup_ms: u128,
-    generate_blinds_ms: u128,
-    prove_prepare_ms: u128,
-    reblind_prepare_ms: u128,
-    prove_show_ms: u128,
-    reblind_show_ms: u128,
-    verify_prepare_ms: u128,
-    verify_show_ms: u128,
+    // Timing measurements in milliseconds; `None` if the step failed or was skipped
+    prepare_setup_ms: Option<u128>,
+    show_setup_ms: Option<u128>,
+    generate_blinds_ms: Option<u128>,
+    prove_prepare_ms: Option<u128>,
+    reblind_prepare_ms: Option<u128>,
+    prove_show_ms: Option<u128>,
+    reblind_show_ms: Option<u128>,
+    verify_prepare_ms: Option<u128>,
+    verify_show_ms: Option<u128>,
     // Size measurements in bytes
     prepare_proving_key_bytes: u64,
     prepare_verifying_key_bytes: u64,
@@ -58,9 +84,20 @@ struct BenchmarkResults {
     show_proof_bytes: u64,
     prepare_witness_bytes: u64,
     show_witness_bytes: u64,
+    // Proof bytes per component; `None` if the proof is missing
+    prepare_proof_breakdown: Option<SizeBreakdown>,
+    show_proof_breakdown: Option<SizeBreakdown>,
+    failures: Vec<StepFailure>,
 }
 
 impl BenchmarkResults {
+    fn format_ms(ms: Option<u128>) -> String {
+        match ms {
+            Some(ms) => format!("{} ms", ms),
+            None => "n/a".to_string(),
+        }
+    }
+
     fn format_size(bytes: u64) -> String {
         if bytes < 1024 {
             format!("{} B", bytes)
@@ -71,6 +108,114 @@ impl BenchmarkResults {
         }
     }
 
+    fn metrics(&self) -> Vec<Metric> {
+        let timings = [
+            (
+                "l8zk_setup_prepare_seconds",
+                "Prepare circuit setup time",
+                self.prepare_setup_ms,
+            ),
+            (
+                "l8zk_setup_show_seconds",
+                "Show circuit setup time",
+                self.show_setup_ms,
+            ),
+            (
+                "l8zk_generate_blinds_seconds",
+                "Shared blinds generation time",
+                self.generate_blinds_ms,
+            ),
+            (
+                "l8zk_prove_prepare_seconds",
+                "Prepare proof generation time",
+                self.prove_prepare_ms,
+            ),
+            (
+                "l8zk_reblind_prepare_seconds",
+                "Prepare proof reblind time",
+                self.reblind_prepare_ms,
+            ),
+            (
+                "l8zk_prove_show_seconds",
+                "Show proof generation time",
+                self.prove_show_ms,
+            ),
+            (
+                "l8zk_reblind_show_seconds",
+                "Show proof reblind time",
+                self.reblind_show_ms,
+            ),
+            (
+                "l8zk_verify_prepare_seconds",
+                "Prepare proof verification time",
+                self.verify_prepare_ms,
+            ),
+            (
+                "l8zk_verify_show_seconds",
+                "Show proof verification time",
+                self.verify_show_ms,
+            ),
+        ];
+
+        // Failed or skipped steps are omitted rather than reported as zero
+        let mut metrics: Vec<Metric> = timings
+            .into_iter()
+            .filter_map(|(name, help, ms)| ms.map(|ms| Metric::seconds(name, help, ms)))
+            .collect();
+
+        metrics.extend([
+            Metric::new(
+                "l8zk_proving_key_bytes",
+                "Serialized proving key size",
+                self.prepare_proving_key_bytes as f64,
+            )
+            .for_circuit("prepare"),
+            Metric::new(
+                "l8zk_proving_key_bytes",
+                "Serialized proving key size",
+                self.show_proving_key_bytes as f64,
+            )
+            .for_circuit("show"),
+            Metric::new(
+                "l8zk_verifying_key_bytes",
+                "Serialized verifying key size",
+                self.prepare_verifying_key_bytes as f64,
+            )
+            .for_circuit("prepare"),
+            Metric::new(
+                "l8zk_verifying_key_bytes",
+                "Serialized verifying key size",
+                self.show_verifying_key_bytes as f64,
+            )
+            .for_circuit("show"),
+            Metric::new(
+                "l8zk_proof_bytes",
+                "Serialized proof size",
+                self.prepare_proof_bytes as f64,
+            )
+            .for_circuit("prepare"),
+            Metric::new(
+                "l8zk_proof_bytes",
+                "Serialized proof size",
+                self.show_proof_bytes as f64,
+            )
+            .for_circuit("show"),
+            Metric::new(
+                "l8zk_witness_bytes",
+                "Serialized witness size",
+                self.prepare_witness_bytes as f64,
+            )
+            .for_circuit("prepare"),
+            Metric::new(
+                "l8zk_witness_bytes",
+                "Serialized witness size",
+                self.show_witness_bytes as f64,
+            )
+            .for_circuit("show"),
+        ]);
+        metrics
+    }
+
     fn print_summary(&self) {
         println!("\n╔════════════════════════════════════════════════╗");
         println!("║        BENCHMARK RESULTS SUMMARY               ║");
@@ -78,40 +223,40 @@ impl BenchmarkResults {
         println!("║ TIMING MEASUREMENTS                            ║");
         println!("╠════════════════════════════════════════════════╣");
         println!(
-            "║ Prepare Setup:          {:>10} ms      ║",
-            self.prepare_setup_ms
+            "║ Prepare Setup:          {:>13}      ║",
+            Self::format_ms(self.prepare_setup_ms)
         );
         println!(
-            "║ Show Setup:             {:>10} ms      ║",
-            self.show_setup_ms
+            "║ Show Setup:             {:>13}      ║",
+            Self::format_ms(self.show_setup_ms)
         );
         println!(
-            "║ Generate Blinds:        {:>10} ms      ║",
-            self.generate_blinds_ms
+            "║ Generate Blinds:        {:>13}      ║",
+            Self::format_ms(self.generate_blinds_ms)
         );
         println!(
-            "║ Prove Prepare:          {:>10} ms      ║",
-            self.prove_prepare_ms
+            "║ Prove Prepare:          {:>13}      ║",
+            Self::format_ms(self.prove_prepare_ms)
         );
         println!(
-            "║ Reblind Prepare:        {:>10} ms      ║",
-            self.reblind_prepare_ms
+            "║ Reblind Prepare:        {:>13}      ║",
+            Self::format_ms(self.reblind_prepare_ms)
         );
         println!(
-            "║ Prove Show:             {:>10} ms      ║",
-            self.prove_show_ms
+            "║ Prove Show:             {:>13}      ║",
+            Self::format_ms(self.prove_show_ms)
         );
         println!(
-            "║ Reblind Show:           {:>10} ms      ║",
-            self.reblind_show_ms
+            "║ Reblind Show:           {:>13}      ║",
+            Self::format_ms(self.reblind_show_ms)
         );
         println!(
-            "║ Verify Prepare:         {:>10} ms      ║",
-            self.verify_prepare_ms
+            "║ Verify Prepare:         {:>13}      ║",
+            Self::format_ms(self.verify_prepare_ms)
         );
         println!(
-            "║ Verify Show:            {:>10} ms      ║",
-            self.verify_show_ms
+            "║ Verify Show:            {:>13}      ║",
+            Self::format_ms(self.verify_show_ms)
         );
         println!("╠════════════════════════════════════════════════╣");
         println!("║ SIZE MEASUREMENTS                              ║");
@@ -140,6 +285,25 @@ impl BenchmarkResults {
             "║ Show Proof:             {:>12}       ║",
             Self::format_size(self.show_proof_bytes)
         );
+        for (circuit, breakdown) in [
+            ("PREPARE", &self.prepare_proof_breakdown),
+            ("SHOW", &self.show_proof_breakdown),
+        ] {
+            let Some(breakdown) = breakdown else {
+                continue;
+            };
+            println!("╠════════════════════════════════════════════════╣");
+            println!("║ {:<46} ║", format!("{circuit} PROOF BREAKDOWN"));
+            println!("╠════════════════════════════════════════════════╣");
+            for component in &breakdown.components {
+                println!(
+                    "║   {:<22.22}{:>12}       ║",
+                    component.name,
+                    Self::format_size(component.bytes)
+                );
+            }
+        }
+        println!("╠════════════════════════════════════════════════╣");
         println!(
             "║ Prepare Witness:        {:>12}       ║",
             Self::format_size(self.prepare_witness_bytes)
@@ -148,37 +312,20 @@ impl BenchmarkResults {
             "║ Show Witness:           {:>12}       ║",
             Self::format_size(self.show_witness_bytes)
         );
+        if !self.failures.is_empty() {
+            println!("╠════════════════════════════════════════════════╣");
+            println!("║ FAILED STEPS                                   ║");
+            println!("╠════════════════════════════════════════════════╣");
+            for failure in &self.failures {
+                println!("║ {:<46} ║", failure.step);
+            }
+        }
         println!("╚════════════════════════════════════════════════╝\n");
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum CircuitKind {
-    Prepare,
-    Show,
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum CircuitAction {
-    Run,
-    Setup,
-    Prove,
-    Verify,
-    Reblind,
-    GenerateSharedBlinds,
-    Benchmark,
-}
-
-#[derive(Debug, Default, Clone)]
-struct CommandOptions {
-    input: Option<PathBuf>,
-}
-
-#[derive(Debug, Clone)]
-struct ParsedCommand {
-    circuit: CircuitKind,
-    action: CircuitAction,
-    options: CommandOptions,
+        for failure in &self.failures {
+            println!("✗ {}: {}", failure.step, failure.error);
+        }
+    }
 }
 
 fn main() {
@@ -186,12 +333,32 @@ fn main() {
         .with_target(false)
         .with_ansi(true)
         .with_env_filter(EnvFilter::from_default_env())
+        // Logs go to stderr so stdout carries only command output (reports, --no-save proofs)
+        .with_writer(io::stderr)
         .init();
 
     let args: Vec<String> = args().collect();
     let command_args: &[String] = if args.len() > 1 { &args[1..] } else { &[] };
 
-    let command = match parse_command(command_args) {
+    // Defaults from l8zk.toml sit between the built-in defaults and explicit flags
+    let (config_path, command_args) = match take_config_flag(command_args) {
+        Ok(split) => split,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            print_usage();
+            process::exit(1);
+        }
+    };
+    let cwd = env::current_dir().expect("current directory is accessible");
+    let config = match Config::load(config_path.as_deref(), &cwd) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+    };
+
+    let command = match parse_command_with_config(&command_args, config.as_ref()) {
         Ok(cmd) => cmd,
         Err(err) => {
             eprintln!("Error: {}", err);
@@ -200,162 +367,338 @@ fn main() {
         }
     };
 
+    if command.action == CircuitAction::ShowHelp {
+        print_usage();
+        return;
+    }
+
+    let paths = Paths::from_env();
     match command.circuit {
-        CircuitKind::Prepare => execute_prepare(command.action, command.options),
-        CircuitKind::Show => execute_show(command.action, command.options),
+        CircuitKind::Prepare => execute_prepare(&paths, command.action, command.options),
+        CircuitKind::Show => execute_show(&paths, command.action, command.options),
+        CircuitKind::Auto => match detect_input_circuit(&command.options) {
+            InputCircuit::Prepare => execute_prepare(&paths, command.action, command.options),
+            InputCircuit::Show => execute_show(&paths, command.action, command.options),
+        },
+    }
+}
+
+/// Pick the circuit for `auto` from the fields of `--input`, exiting when the input cannot be
+/// read or matches neither circuit
+fn detect_input_circuit(options: &CommandOptions) -> InputCircuit {
+    let Some(input) = options.input.as_ref() else {
+        eprintln!("Error: auto requires --input <path> or --input-json <json>");
+        process::exit(1);
+    };
+    let cwd = env::current_dir().expect("current directory is accessible");
+    let json = match input.read(&cwd, DEFAULT_MAX_INPUT_SIZE, false) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", input, e);
+            process::exit(1);
+        }
+    };
+
+    match detect_circuit_kind(&json) {
+        Some(kind) => {
+            info!("Detected a {:?} input in {}", kind, input);
+            kind
+        }
+        None => {
+            eprintln!(
+                "Could not tell which circuit {} is for: expected message/claims (prepare) \
+                 or messageHash/currentYear (show). Run `prepare` or `show` explicitly.",
+                input
+            );
+            process::exit(1);
+        }
     }
 }
 
-/// Run the complete benchmark pipeline for a given input file
-fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
+/// Tracks step failures for the benchmark pipeline.
+/// Without `--keep-going` the first failure aborts the process.
+struct PipelineRun {
+    keep_going: bool,
+    failures: Vec<StepFailure>,
+}
+
+impl PipelineRun {
+    /// Record the outcome of a step, returning its value on success
+    fn record<T, D: std::fmt::Display>(
+        &mut self,
+        step: &'static str,
+        result: Result<T, D>,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) if self.keep_going => {
+                eprintln!("✗ {} failed: {}\n", step, e);
+                self.failures.push(StepFailure {
+                    step,
+                    error: e.to_string(),
+                });
+                None
+            }
+            Err(e) => {
+                eprintln!("{} failed: {}", step, e);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Run the complete benchmark pipeline for a given input file.
+/// With `keep_going`, a failing step is recorded and the steps that depend on it are skipped.
+fn run_complete_pipeline(
+    paths: &Paths,
+    input: Option<InputSource>,
+    keep_going: bool,
+) -> BenchmarkResults {
     println!("\n╔════════════════════════════════════════════════╗");
     println!("║     STARTING COMPLETE BENCHMARK PIPELINE       ║");
     println!("╚════════════════════════════════════════════════╝\n");
 
+    let mut run = PipelineRun {
+        keep_going,
+        failures: Vec::new(),
+    };
+
     // Step 1: Setup Prepare Circuit
     info!("Step 1/9: Setting up Prepare circuit...");
-    let prepare_circuit = PrepareCircuit::new(input_path.clone());
+    let prepare_circuit = PrepareCircuit::from_input(input.clone());
     let t0 = Instant::now();
-    let (prepare_pk, prepare_vk) = setup_circuit_keys_no_save(prepare_circuit);
-    let prepare_setup_ms = t0.elapsed().as_millis();
-    println!("✓ Prepare setup completed: {} ms\n", prepare_setup_ms);
+    let prepare_keys = run.record("Prepare setup", setup_circuit_keys_no_save(prepare_circuit));
+    let prepare_setup_ms = prepare_keys.as_ref().map(|_| t0.elapsed().as_millis());
+    if let Some(ms) = prepare_setup_ms {
+        println!("✓ Prepare setup completed: {} ms\n", ms);
+    }
 
     // Save Prepare keys after timing
-    if let Err(e) = save_keys(
-        PREPARE_PROVING_KEY,
-        PREPARE_VERIFYING_KEY,
-        &prepare_pk,
-        &prepare_vk,
-    ) {
-        eprintln!("Failed to save Prepare keys: {}", e);
-        std::process::exit(1);
+    if let Some((prepare_pk, prepare_vk)) = &prepare_keys {
+        run.record(
+            "Save Prepare keys",
+            save_keys(
+                &paths.prepare_proving_key,
+                &paths.prepare_verifying_key,
+                prepare_pk,
+                prepare_vk,
+            ),
+        );
     }
 
     // Step 2: Setup Show Circuit
     info!("Step 2/9: Setting up Show circuit...");
-    let show_circuit = ShowCircuit::new(input_path.clone());
+    let show_circuit = ShowCircuit::from_input(input.clone());
     let t0 = Instant::now();
-    let (show_pk, show_vk) = setup_circuit_keys_no_save(show_circuit);
-    let show_setup_ms = t0.elapsed().as_millis();
-    println!("✓ Show setup completed: {} ms\n", show_setup_ms);
+    let show_keys = run.record("Show setup", setup_circuit_keys_no_save(show_circuit));
+    let show_setup_ms = show_keys.as_ref().map(|_| t0.elapsed().as_millis());
+    if let Some(ms) = show_setup_ms {
+        println!("✓ Show setup completed: {} ms\n", ms);
+    }
 
     // Save Show keys after timing
-    if let Err(e) = save_keys(SHOW_PROVING_KEY, SHOW_VERIFYING_KEY, &show_pk, &show_vk) {
-        eprintln!("Failed to save Show keys: {}", e);
-        std::process::exit(1);
+    if let Some((show_pk, show_vk)) = &show_keys {
+        run.record(
+            "Save Show keys",
+            save_keys(
+                &paths.show_proving_key,
+                &paths.show_verifying_key,
+                show_pk,
+                show_vk,
+            ),
+        );
     }
 
-    // Step 3: Generate Shared Blinds
-    info!("Step 3/9: Generating shared blinds...");
-    let t0 = Instant::now();
-    generate_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED);
-    let generate_blinds_ms = t0.elapsed().as_millis();
-    println!("✓ Shared blinds generated: {} ms\n", generate_blinds_ms);
-
     // Note: We already have prepare_pk and show_pk from setup, no need to reload from files
 
-    // Step 4: Prove Prepare Circuit
-    info!("Step 4/9: Proving Prepare circuit...");
-    let t0 = Instant::now();
-    let prepare_circuit = PrepareCircuit::new(input_path.clone());
-    prove_circuit_with_pk(
-        prepare_circuit,
-        &prepare_pk,
-        PREPARE_INSTANCE,
-        PREPARE_WITNESS,
-        PREPARE_PROOF,
-    );
-    let prove_prepare_ms = t0.elapsed().as_millis();
-    println!("✓ Prepare proof generated: {} ms\n", prove_prepare_ms);
+    // Step 3: Prove Prepare Circuit
+    info!("Step 3/9: Proving Prepare circuit...");
+    let prove_prepare_ms = prepare_keys.as_ref().and_then(|(prepare_pk, _)| {
+        let t0 = Instant::now();
+        let prepare_circuit = PrepareCircuit::from_input(input.clone());
+        let result = circuit_public_values(&prepare_circuit).and_then(|public_inputs| {
+            prove_circuit_with_pk(
+                prepare_circuit,
+                prepare_pk,
+                public_inputs,
+                &paths.prepare_instance,
+                &paths.prepare_witness,
+                &paths.prepare_proof,
+            )
+        });
+        run.record("Prove Prepare", result)
+            .map(|_| t0.elapsed().as_millis())
+    });
+    if let Some(ms) = prove_prepare_ms {
+        println!("✓ Prepare proof generated: {} ms\n", ms);
+    }
+
+    // Step 4: Generate Shared Blinds
+    info!("Step 4/9: Generating shared blinds...");
+    // One blind per shared row of the Prepare instance, which Show shares
+    let shared_blinds = prove_prepare_ms.and_then(|_| {
+        let prepare_instance = run.record(
+            "Load Prepare instance",
+            load_instance(&paths.prepare_instance),
+        )?;
+        let t0 = Instant::now();
+        run.record(
+            "Generate shared blinds",
+            generate_shared_blinds_for(&paths.shared_blinds, &prepare_instance),
+        )
+        .map(|blinds| (blinds, t0.elapsed().as_millis()))
+    });
+    let generate_blinds_ms = shared_blinds.as_ref().map(|(_, ms)| *ms);
+    let shared_blinds = shared_blinds.map(|(blinds, _)| blinds);
+    if let Some(ms) = generate_blinds_ms {
+        println!("✓ Shared blinds generated: {} ms\n", ms);
+    }
 
     // Step 5: Reblind Prepare
     info!("Step 5/9: Reblinding Prepare proof...");
-    // Load data before timing (file I/O should not be part of reblind benchmark)
-    let prepare_instance = load_instance(PREPARE_INSTANCE).expect("load prepare instance failed");
-    let prepare_witness = load_witness(PREPARE_WITNESS).expect("load prepare witness failed");
-    let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).expect("load shared_blinds failed");
+    let reblind_prepare_ms = prepare_keys
+        .as_ref()
+        .filter(|_| prove_prepare_ms.is_some())
+        .and_then(|(prepare_pk, _)| {
+            let shared_blinds = shared_blinds.as_ref()?;
+            // Load data before timing (file I/O should not be part of reblind benchmark)
+            let (prepare_instance, prepare_witness) = run.record(
+                "Load Prepare instance/witness",
+                load_instance(&paths.prepare_instance).and_then(|instance| {
+                    load_witness(&paths.prepare_witness).map(|witness| (instance, witness))
+                }),
+            )?;
+            // Reuse shared_blinds from Step 4 (already in memory)
 
-    let t0 = Instant::now();
-    reblind_with_loaded_data(
-        PrepareCircuit::default(),
-        &prepare_pk,
-        prepare_instance,
-        prepare_witness,
-        &shared_blinds,
-        PREPARE_INSTANCE,
-        PREPARE_WITNESS,
-        PREPARE_PROOF,
-    );
-    let reblind_prepare_ms = t0.elapsed().as_millis();
-    println!("✓ Prepare proof reblinded: {} ms\n", reblind_prepare_ms);
+            let t0 = Instant::now();
+            let result = reblind_with_loaded_data(
+                PrepareCircuit::default(),
+                prepare_pk,
+                prepare_instance,
+                prepare_witness,
+                shared_blinds,
+                &paths.prepare_instance,
+                &paths.prepare_witness,
+                &paths.prepare_proof,
+            );
+            run.record("Reblind Prepare", result)
+                .map(|_| t0.elapsed().as_millis())
+        });
+    if let Some(ms) = reblind_prepare_ms {
+        println!("✓ Prepare proof reblinded: {} ms\n", ms);
+    }
 
     // Step 6: Prove Show Circuit
     info!("Step 6/9: Proving Show circuit...");
-    let t0 = Instant::now();
-    let show_circuit = ShowCircuit::new(input_path.clone());
-    prove_circuit_with_pk(
-        show_circuit,
-        &show_pk,
-        SHOW_INSTANCE,
-        SHOW_WITNESS,
-        SHOW_PROOF,
-    );
-    let prove_show_ms = t0.elapsed().as_millis();
-    println!("✓ Show proof generated: {} ms\n", prove_show_ms);
+    let prove_show_ms = show_keys.as_ref().and_then(|(show_pk, _)| {
+        let t0 = Instant::now();
+        let show_circuit = ShowCircuit::from_input(input.clone());
+        let result = circuit_public_values(&show_circuit).and_then(|public_inputs| {
+            prove_circuit_with_pk(
+                show_circuit,
+                show_pk,
+                public_inputs,
+                &paths.show_instance,
+                &paths.show_witness,
+                &paths.show_proof,
+            )
+        });
+        run.record("Prove Show", result)
+            .map(|_| t0.elapsed().as_millis())
+    });
+    if let Some(ms) = prove_show_ms {
+        println!("✓ Show proof generated: {} ms\n", ms);
+    }
 
     // Step 7: Reblind Show
     info!("Step 7/9: Reblinding Show proof...");
-    // Load data before timing (file I/O should not be part of reblind benchmark)
-    let show_instance = load_instance(SHOW_INSTANCE).expect("load show instance failed");
-    let show_witness = load_witness(SHOW_WITNESS).expect("load show witness failed");
-    // Reuse shared_blinds from Prepare step (already loaded)
+    let reblind_show_ms = show_keys
+        .as_ref()
+        .filter(|_| prove_show_ms.is_some())
+        .and_then(|(show_pk, _)| {
+            let shared_blinds = shared_blinds.as_ref()?;
+            // Load data before timing (file I/O should not be part of reblind benchmark)
+            let (show_instance, show_witness) = run.record(
+                "Load Show instance/witness",
+                load_instance(&paths.show_instance).and_then(|instance| {
+                    load_witness(&paths.show_witness).map(|witness| (instance, witness))
+                }),
+            )?;
+            // Reuse shared_blinds from Step 4 (already in memory)
 
-    let t0 = Instant::now();
-    reblind_with_loaded_data(
-        ShowCircuit::default(),
-        &show_pk,
-        show_instance,
-        show_witness,
-        &shared_blinds,
-        SHOW_INSTANCE,
-        SHOW_WITNESS,
-        SHOW_PROOF,
-    );
-    let reblind_show_ms = t0.elapsed().as_millis();
-    println!("✓ Show proof reblinded: {} ms\n", reblind_show_ms);
+            let t0 = Instant::now();
+            let result = reblind_with_loaded_data(
+                ShowCircuit::default(),
+                show_pk,
+                show_instance,
+                show_witness,
+                shared_blinds,
+                &paths.show_instance,
+                &paths.show_witness,
+                &paths.show_proof,
+            );
+            run.record("Reblind Show", result)
+                .map(|_| t0.elapsed().as_millis())
+        });
+    if let Some(ms) = reblind_show_ms {
+        println!("✓ Show proof reblinded: {} ms\n", ms);
+    }
 
     // Step 8: Verify Prepare
     info!("Step 8/9: Verifying Prepare proof...");
-    // Load proof and verifying key before timing (file I/O should not be part of verify benchmark)
-    let prepare_proof = load_proof(PREPARE_PROOF).expect("load prepare proof failed");
-    // Reuse prepare_vk from setup step (already in memory)
+    let verify_prepare_ms = prepare_keys
+        .as_ref()
+        .filter(|_| prove_prepare_ms.is_some())
+        .and_then(|(_, prepare_vk)| {
+            // Load proof before timing (file I/O should not be part of verify benchmark)
+            let prepare_proof =
+                run.record("Load Prepare proof", load_proof(&paths.prepare_proof))?;
+            // Reuse prepare_vk from setup step (already in memory)
 
-    let t0 = Instant::now();
-    verify_circuit_with_loaded_data(&prepare_proof, &prepare_vk);
-    let verify_prepare_ms = t0.elapsed().as_millis();
-    println!("✓ Prepare proof verified: {} ms\n", verify_prepare_ms);
+            let t0 = Instant::now();
+            let result = verify_circuit_with_loaded_data(&prepare_proof, prepare_vk);
+            run.record("Verify Prepare", result)
+                .map(|_| t0.elapsed().as_millis())
+        });
+    if let Some(ms) = verify_prepare_ms {
+        println!("✓ Prepare proof verified: {} ms\n", ms);
+    }
 
     // Step 9: Verify Show
     info!("Step 9/9: Verifying Show proof...");
-    // Load proof and verifying key before timing (file I/O should not be part of verify benchmark)
-    let show_proof = load_proof(SHOW_PROOF).expect("load show proof failed");
-    // Reuse show_vk from setup step (already in memory)
+    let verify_show_ms = show_keys
+        .as_ref()
+        .filter(|_| prove_show_ms.is_some())
+        .and_then(|(_, show_vk)| {
+            // Load proof before timing (file I/O should not be part of verify benchmark)
+            let show_proof = run.record("Load Show proof", load_proof(&paths.show_proof))?;
+            // Reuse show_vk from setup step (already in memory)
 
-    let t0 = Instant::now();
-    verify_circuit_with_loaded_data(&show_proof, &show_vk);
-    let verify_show_ms = t0.elapsed().as_millis();
-    println!("✓ Show proof verified: {} ms\n", verify_show_ms);
+            let t0 = Instant::now();
+            let result = verify_circuit_with_loaded_data(&show_proof, show_vk);
+            run.record("Verify Show", result)
+                .map(|_| t0.elapsed().as_millis())
+        });
+    if let Some(ms) = verify_show_ms {
+        println!("✓ Show proof verified: {} ms\n", ms);
+    }
 
     // Measure file sizes
     info!("Measuring artifact sizes...");
-    let prepare_proving_key_bytes = get_file_size(PREPARE_PROVING_KEY);
-    let prepare_verifying_key_bytes = get_file_size(PREPARE_VERIFYING_KEY);
-    let show_proving_key_bytes = get_file_size(SHOW_PROVING_KEY);
-    let show_verifying_key_bytes = get_file_size(SHOW_VERIFYING_KEY);
-    let prepare_proof_bytes = get_file_size(PREPARE_PROOF);
-    let show_proof_bytes = get_file_size(SHOW_PROOF);
-    let prepare_witness_bytes = get_file_size(PREPARE_WITNESS);
-    let show_witness_bytes = get_file_size(SHOW_WITNESS);
+    let prepare_proving_key_bytes = get_file_size(&paths.prepare_proving_key);
+    let prepare_verifying_key_bytes = get_file_size(&paths.prepare_verifying_key);
+    let show_proving_key_bytes = get_file_size(&paths.show_proving_key);
+    let show_verifying_key_bytes = get_file_size(&paths.show_verifying_key);
+    let prepare_proof_bytes = get_file_size(&paths.prepare_proof);
+    let show_proof_bytes = get_file_size(&paths.show_proof);
+    let prepare_witness_bytes = get_file_size(&paths.prepare_witness);
+    let show_witness_bytes = get_file_size(&paths.show_witness);
+    let prepare_proof_breakdown = load_proof(&paths.prepare_proof)
+        .and_then(|proof| proof_size_breakdown(&proof))
+        .ok();
+    let show_proof_breakdown = load_proof(&paths.show_proof)
+        .and_then(|proof| proof_size_breakdown(&proof))
+        .ok();
 
     BenchmarkResults {
         prepare_setup_ms,
@@ -375,292 +718,1120 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
         show_proof_bytes,
         prepare_witness_bytes,
         show_witness_bytes,
+        prepare_proof_breakdown,
+        show_proof_breakdown,
+        failures: run.failures,
     }
 }
 
-fn execute_prepare(action: CircuitAction, options: CommandOptions) {
+fn execute_prepare(paths: &Paths, action: CircuitAction, options: CommandOptions) {
+    let synthesizes = matches!(
+        action,
+        CircuitAction::Run
+            | CircuitAction::Setup
+            | CircuitAction::SetupAndProve
+            | CircuitAction::Prove
+            | CircuitAction::Benchmark
+            | CircuitAction::DumpShape
+    );
+    if synthesizes && env::var_os(CHECK_LAYOUT_ENV).is_some() {
+        check_prepare_layout(&PrepareCircuit::from_input(options.input.clone()));
+    }
+
+    let cwd = env::current_dir().expect("current directory is accessible");
+    let artifacts = VersionedArtifacts::prepare(paths, &cwd);
+    let version = artifacts.check(action, &options);
+
     match action {
         CircuitAction::Setup => {
             info!(
-                input = ?options.input,
+                input = ?options.input.as_ref().map(ToString::to_string),
                 "Setting up Spartan-2 keys for the Prepare circuit"
             );
-            let circuit = PrepareCircuit::new(options.input.clone());
-            setup_circuit_keys(circuit, PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY);
+            let circuit = PrepareCircuit::from_input(options.input.clone());
+            if let Err(e) = setup_circuit_keys(
+                circuit.clone(),
+                &paths.prepare_proving_key,
+                &paths.prepare_verifying_key,
+            ) {
+                eprintln!("Prepare setup failed: {}", e);
+                process::exit(1);
+            }
+            info!(counts = ?circuit.counters().snapshot(), "Prepare setup synthesis counts");
+        }
+        CircuitAction::SetupAndProve => {
+            info!(input = ?options.input.as_ref().map(ToString::to_string), "Setting up and proving the Prepare circuit");
+            setup_and_prove(
+                PrepareCircuit::from_input(options.input.clone()),
+                &paths.prepare_proving_key,
+                &paths.prepare_verifying_key,
+                &paths.prepare_instance,
+                &paths.prepare_witness,
+                &paths.prepare_proof,
+            );
         }
         CircuitAction::Run => {
-            let circuit = PrepareCircuit::new(options.input.clone());
+            let circuit = PrepareCircuit::from_input(options.input.clone());
             info!("Running Prepare circuit with ZK-Spartan");
-            run_circuit(circuit);
+            if let Err(e) = run_circuit(circuit) {
+                eprintln!("Prepare circuit run failed: {}", e);
+                process::exit(1);
+            }
         }
         CircuitAction::Prove => {
-            let circuit = PrepareCircuit::new(options.input.clone());
+            let circuit = PrepareCircuit::from_input(options.input.clone());
             info!("Proving Prepare circuit with ZK-Spartan");
-            prove_circuit(
-                circuit,
-                PREPARE_PROVING_KEY,
-                PREPARE_INSTANCE,
-                PREPARE_WITNESS,
-                PREPARE_PROOF,
-            );
+            let t0 = Instant::now();
+            let proof_bytes = if options.no_save {
+                prove_to_stdout(circuit.clone(), &paths.prepare_proving_key)
+            } else {
+                if let Err(e) = prove_circuit(
+                    circuit.clone(),
+                    &paths.prepare_proving_key,
+                    &paths.prepare_instance,
+                    &paths.prepare_witness,
+                    &paths.prepare_proof,
+                ) {
+                    eprintln!("Failed to prove circuit: {}", e);
+                    process::exit(1);
+                }
+                get_file_size(&paths.prepare_proof)
+            };
+            let mut metrics = vec![
+                Metric::seconds(
+                    "l8zk_prove_prepare_seconds",
+                    "Prepare proof generation time",
+                    t0.elapsed().as_millis(),
+                ),
+                Metric::new(
+                    "l8zk_proof_bytes",
+                    "Serialized proof size",
+                    proof_bytes as f64,
+                )
+                .for_circuit("prepare"),
+            ];
+            metrics.extend(circuit.counters().snapshot().metrics("prepare"));
+            report_metrics(&options, &metrics);
         }
         CircuitAction::Verify => {
             info!("Verifying Prepare proof with ZK-Spartan");
-            verify_circuit(PREPARE_PROOF, PREPARE_VERIFYING_KEY);
+            let t0 = Instant::now();
+            if let Err(e) = verify_circuit(&paths.prepare_proof, &paths.prepare_verifying_key) {
+                eprintln!("Prepare proof verification failed: {}", e);
+                process::exit(1);
+            }
+            report_metrics(
+                &options,
+                &[Metric::seconds(
+                    "l8zk_verify_prepare_seconds",
+                    "Prepare proof verification time",
+                    t0.elapsed().as_millis(),
+                )],
+            );
         }
         CircuitAction::Reblind => {
             info!("Reblind Spartan sumcheck + Hyrax PCS Prepare");
-            reblind(
+            if let Err(e) = reblind(
                 PrepareCircuit::default(),
-                PREPARE_PROVING_KEY,
-                PREPARE_INSTANCE,
-                PREPARE_WITNESS,
-                PREPARE_PROOF,
-                SHARED_BLINDS,
-            );
+                &paths.prepare_proving_key,
+                &paths.prepare_instance,
+                &paths.prepare_witness,
+                &paths.prepare_proof,
+                &paths.shared_blinds,
+            ) {
+                eprintln!("Failed to reblind proof: {}", e);
+                process::exit(1);
+            }
+            if options.verify_after {
+                info!("Verifying reblinded Prepare proof");
+                if let Err(e) = verify_circuit(&paths.prepare_proof, &paths.prepare_verifying_key) {
+                    eprintln!("Reblinded Prepare proof failed verification: {}", e);
+                    process::exit(1);
+                }
+                info!("Reblinded Prepare proof verified");
+            }
         }
         CircuitAction::GenerateSharedBlinds => {
             info!("Generating shared blinds for Spartan-2 circuits");
-            generate_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED);
+            // Size the blinds from the Prepare instance; Show shares the same rows
+            let result =
+                load_instance(&paths.prepare_instance).and_then(|instance| match &options.seed {
+                    Some(seed) => generate_shared_blinds_seeded::<E>(
+                        &paths.shared_blinds,
+                        instance.num_shared_rows(),
+                        seed,
+                    ),
+                    None => generate_shared_blinds_for(&paths.shared_blinds, &instance),
+                });
+            if let Err(e) = result {
+                eprintln!("Failed to generate shared blinds: {}", e);
+                process::exit(1);
+            }
         }
-        CircuitAction::Benchmark => {
-            let results = run_complete_pipeline(options.input);
-            results.print_summary();
+        CircuitAction::Benchmark if options.estimate => run_benchmark_estimate(&options),
+        // Checks and stamps both circuits itself
+        CircuitAction::Benchmark => return run_benchmark(paths, &cwd, &options),
+        CircuitAction::ProveDir => run_prove_dir(
+            &options,
+            &paths.prepare_proving_key,
+            version.as_ref(),
+            PrepareCircuit::new,
+        ),
+        CircuitAction::VerifyDir => run_verify_dir(&options),
+        CircuitAction::BenchmarkVerify => run_benchmark_verify(&options),
+        CircuitAction::MinimizeProof => run_minimize_proof(&options),
+        CircuitAction::InspectInstance => run_inspect_instance(&options),
+        CircuitAction::CheckLink => run_check_link(&options),
+        CircuitAction::CheckWitness => run_check_witness(&options),
+        CircuitAction::Doctor => run_doctor(paths),
+        CircuitAction::ExportSchema => run_export_schema(prepare_input_schema(), &options),
+        CircuitAction::DeriveVk => run_derive_vk(&options),
+        CircuitAction::EstimateProof => {
+            let cwd = env::current_dir().expect("current directory is accessible");
+            run_estimate_proof("prepare", &PrepareCircuit::r1cs_path(&cwd), &options);
         }
+        CircuitAction::DumpShape => run_dump_shape(
+            "prepare",
+            &PrepareCircuit::from_input(options.input.clone()),
+            &options,
+        ),
+        CircuitAction::ShowHelp => unreachable!("help is handled in main"),
     }
+
+    artifacts.stamp(version.as_ref(), action, &options);
 }
 
-fn execute_show(action: CircuitAction, options: CommandOptions) {
+fn execute_show(paths: &Paths, action: CircuitAction, options: CommandOptions) {
+    let cwd = env::current_dir().expect("current directory is accessible");
+    let artifacts = VersionedArtifacts::show(paths, &cwd);
+    let version = artifacts.check(action, &options);
+
     match action {
         CircuitAction::Setup => {
-            info!(input = ?options.input, "Setting up Spartan-2 keys for the Show circuit");
-            let circuit = ShowCircuit::new(options.input.clone());
-            setup_circuit_keys(circuit, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY);
+            info!(input = ?options.input.as_ref().map(ToString::to_string), "Setting up Spartan-2 keys for the Show circuit");
+            let circuit = ShowCircuit::from_input(options.input.clone());
+            if let Err(e) = setup_circuit_keys(
+                circuit.clone(),
+                &paths.show_proving_key,
+                &paths.show_verifying_key,
+            ) {
+                eprintln!("Show setup failed: {}", e);
+                process::exit(1);
+            }
+            info!(counts = ?circuit.counters().snapshot(), "Show setup synthesis counts");
+        }
+        CircuitAction::SetupAndProve => {
+            info!(input = ?options.input.as_ref().map(ToString::to_string), "Setting up and proving the Show circuit");
+            setup_and_prove(
+                ShowCircuit::from_input(options.input.clone()),
+                &paths.show_proving_key,
+                &paths.show_verifying_key,
+                &paths.show_instance,
+                &paths.show_witness,
+                &paths.show_proof,
+            );
         }
         CircuitAction::Run => {
-            let circuit = ShowCircuit::new(options.input.clone());
+            let circuit = ShowCircuit::from_input(options.input.clone());
             info!("Running Show circuit with ZK-Spartan");
-            run_circuit(circuit);
+            if let Err(e) = run_circuit(circuit) {
+                eprintln!("Show circuit run failed: {}", e);
+                process::exit(1);
+            }
         }
         CircuitAction::Prove => {
-            let circuit = ShowCircuit::new(options.input.clone());
+            let circuit = ShowCircuit::from_input(options.input.clone());
             info!("Proving Show circuit with ZK-Spartan");
-            prove_circuit(
-                circuit,
-                SHOW_PROVING_KEY,
-                SHOW_INSTANCE,
-                SHOW_WITNESS,
-                SHOW_PROOF,
-            );
+            let t0 = Instant::now();
+            let proof_bytes = if options.no_save {
+                prove_to_stdout(circuit.clone(), &paths.show_proving_key)
+            } else {
+                let proved = prove_circuit(
+                    circuit.clone(),
+                    &paths.show_proving_key,
+                    &paths.show_instance,
+                    &paths.show_witness,
+                    &paths.show_proof,
+                )
+                .and_then(|()| circuit_public_values(&circuit))
+                .and_then(|values| {
+                    let descriptor = PublicInputsDescriptor::new(SHOW_PUBLIC_INPUTS, &values)?;
+                    save_descriptor(&descriptor_path(&paths.show_proof), &descriptor)
+                });
+                if let Err(e) = proved {
+                    eprintln!("Failed to prove circuit: {}", e);
+                    process::exit(1);
+                }
+                get_file_size(&paths.show_proof)
+            };
+            let mut metrics = vec![
+                Metric::seconds(
+                    "l8zk_prove_show_seconds",
+                    "Show proof generation time",
+                    t0.elapsed().as_millis(),
+                ),
+                Metric::new(
+                    "l8zk_proof_bytes",
+                    "Serialized proof size",
+                    proof_bytes as f64,
+                )
+                .for_circuit("show"),
+            ];
+            metrics.extend(circuit.counters().snapshot().metrics("show"));
+            report_metrics(&options, &metrics);
         }
         CircuitAction::Verify => {
             info!("Verifying Show proof with ZK-Spartan");
-            verify_circuit(SHOW_PROOF, SHOW_VERIFYING_KEY);
+            let t0 = Instant::now();
+            if let Err(e) = verify_show(
+                &paths.show_proof,
+                &paths.show_verifying_key,
+                options.expected_nonce.as_deref(),
+            ) {
+                eprintln!("Show proof verification failed: {}", e);
+                process::exit(1);
+            }
+            report_metrics(
+                &options,
+                &[Metric::seconds(
+                    "l8zk_verify_show_seconds",
+                    "Show proof verification time",
+                    t0.elapsed().as_millis(),
+                )],
+            );
         }
         CircuitAction::Reblind => {
             info!("Reblind Spartan sumcheck + Hyrax PCS Show");
-            reblind(
+            if let Err(e) = reblind(
                 ShowCircuit::default(),
-                SHOW_PROVING_KEY,
-                SHOW_INSTANCE,
-                SHOW_WITNESS,
-                SHOW_PROOF,
-                SHARED_BLINDS,
-            );
+                &paths.show_proving_key,
+                &paths.show_instance,
+                &paths.show_witness,
+                &paths.show_proof,
+                &paths.shared_blinds,
+            ) {
+                eprintln!("Failed to reblind proof: {}", e);
+                process::exit(1);
+            }
+            if options.verify_after {
+                info!("Verifying reblinded Show proof");
+                if let Err(e) = verify_circuit(&paths.show_proof, &paths.show_verifying_key) {
+                    eprintln!("Reblinded Show proof failed verification: {}", e);
+                    process::exit(1);
+                }
+                info!("Reblinded Show proof verified");
+            }
         }
         CircuitAction::GenerateSharedBlinds => {
             eprintln!("Error: generate_shared_blinds is only supported for the Prepare circuit");
             process::exit(1);
         }
-        CircuitAction::Benchmark => {
-            let results = run_complete_pipeline(options.input);
-            results.print_summary();
+        CircuitAction::Benchmark if options.estimate => run_benchmark_estimate(&options),
+        // Checks and stamps both circuits itself
+        CircuitAction::Benchmark => return run_benchmark(paths, &cwd, &options),
+        CircuitAction::ProveDir => run_prove_dir(
+            &options,
+            &paths.show_proving_key,
+            version.as_ref(),
+            ShowCircuit::new,
+        ),
+        CircuitAction::VerifyDir => run_verify_dir(&options),
+        CircuitAction::BenchmarkVerify => run_benchmark_verify(&options),
+        CircuitAction::MinimizeProof => run_minimize_proof(&options),
+        CircuitAction::InspectInstance => run_inspect_instance(&options),
+        CircuitAction::CheckLink => run_check_link(&options),
+        CircuitAction::CheckWitness => run_check_witness(&options),
+        CircuitAction::Doctor => run_doctor(paths),
+        CircuitAction::ExportSchema => run_export_schema(show_input_schema(), &options),
+        CircuitAction::DeriveVk => run_derive_vk(&options),
+        CircuitAction::EstimateProof => {
+            let cwd = env::current_dir().expect("current directory is accessible");
+            run_estimate_proof("show", &ShowCircuit::r1cs_path(&cwd), &options);
         }
+        CircuitAction::DumpShape => run_dump_shape(
+            "show",
+            &ShowCircuit::from_input(options.input.clone()),
+            &options,
+        ),
+        CircuitAction::ShowHelp => unreachable!("help is handled in main"),
     }
+
+    artifacts.stamp(version.as_ref(), action, &options);
+}
+
+/// One circuit's key, instance, witness and proof paths, for the circuit version gate
+struct VersionedArtifacts<'a> {
+    circuit: &'static str,
+    r1cs_path: PathBuf,
+    proving_key: &'a str,
+    verifying_key: &'a str,
+    instance: &'a str,
+    witness: &'a str,
+    proof: &'a str,
+    /// Stamped with the Prepare version they are sized from. Show only reads them, and
+    /// `load_shared_blinds` checks the build they were made with.
+    shared_blinds: Option<&'a str>,
 }
 
-fn parse_command(args: &[String]) -> Result<ParsedCommand, String> {
-    if args.is_empty() {
-        return Err("No command provided".into());
+impl<'a> VersionedArtifacts<'a> {
+    fn prepare(paths: &'a Paths, cwd: &Path) -> Self {
+        VersionedArtifacts {
+            circuit: "Prepare",
+            r1cs_path: PrepareCircuit::r1cs_path(cwd),
+            proving_key: &paths.prepare_proving_key,
+            verifying_key: &paths.prepare_verifying_key,
+            instance: &paths.prepare_instance,
+            witness: &paths.prepare_witness,
+            proof: &paths.prepare_proof,
+            shared_blinds: Some(&paths.shared_blinds),
+        }
     }
 
-    match args[0].as_str() {
-        "-h" | "--help" => {
-            print_usage();
-            process::exit(0);
-        }
-        "prepare" => parse_circuit_command(CircuitKind::Prepare, &args[1..]),
-        "show" => parse_circuit_command(CircuitKind::Show, &args[1..]),
-        "benchmark" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare, // Benchmark runs both circuits, but we need to pick one for the enum
-            action: CircuitAction::Benchmark,
-            options: parse_options(&args[1..])?,
-        }),
-        "setup_prepare" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::Setup,
-            options: parse_options(&args[1..])?,
-        }),
-        "setup_show" => Ok(ParsedCommand {
-            circuit: CircuitKind::Show,
-            action: CircuitAction::Setup,
-            options: parse_options(&args[1..])?,
-        }),
-        "prove_prepare" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::Prove,
-            options: parse_options(&args[1..])?,
-        }),
-        "prove_show" => Ok(ParsedCommand {
-            circuit: CircuitKind::Show,
-            action: CircuitAction::Prove,
-            options: parse_options(&args[1..])?,
-        }),
-        "verify_prepare" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::Verify,
-            options: ensure_no_options(&args[1..])?,
-        }),
-        "verify_show" => Ok(ParsedCommand {
-            circuit: CircuitKind::Show,
-            action: CircuitAction::Verify,
-            options: ensure_no_options(&args[1..])?,
-        }),
-        "reblind_prepare" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::Reblind,
-            options: ensure_no_options(&args[1..])?,
-        }),
-        "reblind_show" => Ok(ParsedCommand {
-            circuit: CircuitKind::Show,
-            action: CircuitAction::Reblind,
-            options: ensure_no_options(&args[1..])?,
-        }),
-        "generate_shared_blinds" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::GenerateSharedBlinds,
-            options: ensure_no_options(&args[1..])?,
-        }),
-        other => Err(format!("Unknown command '{other}'")),
-    }
-}
-
-fn parse_circuit_command(circuit: CircuitKind, tail: &[String]) -> Result<ParsedCommand, String> {
-    if tail.is_empty() {
-        return Ok(ParsedCommand {
-            circuit,
-            action: CircuitAction::Run,
-            options: CommandOptions::default(),
-        });
+    fn show(paths: &'a Paths, cwd: &Path) -> Self {
+        VersionedArtifacts {
+            circuit: "Show",
+            r1cs_path: ShowCircuit::r1cs_path(cwd),
+            proving_key: &paths.show_proving_key,
+            verifying_key: &paths.show_verifying_key,
+            instance: &paths.show_instance,
+            witness: &paths.show_witness,
+            proof: &paths.show_proof,
+            shared_blinds: None,
+        }
+    }
+
+    /// The artifacts `action` loads and the ones it saves
+    fn read_and_written(
+        &self,
+        action: CircuitAction,
+        options: &CommandOptions,
+    ) -> (Vec<&'a str>, Vec<&'a str>) {
+        let keys = vec![self.proving_key, self.verifying_key];
+        let proved = vec![self.instance, self.witness, self.proof];
+        let blinds: Vec<&'a str> = self.shared_blinds.into_iter().collect();
+        match action {
+            CircuitAction::Setup => (vec![], keys),
+            CircuitAction::SetupAndProve => (vec![], [keys, proved].concat()),
+            CircuitAction::Prove if options.no_save => (vec![self.proving_key], vec![]),
+            CircuitAction::Prove => (vec![self.proving_key], proved),
+            CircuitAction::Verify => (vec![self.verifying_key, self.proof], vec![]),
+            CircuitAction::Reblind => (
+                [vec![self.proving_key, self.instance, self.witness], blinds].concat(),
+                proved,
+            ),
+            CircuitAction::GenerateSharedBlinds => (vec![self.instance], blinds),
+            // The proofs land under --out-dir; `run_prove_dir` stamps them
+            CircuitAction::ProveDir => (vec![self.proving_key], vec![]),
+            CircuitAction::Benchmark if !options.estimate => {
+                (vec![], [keys, proved, blinds].concat())
+            }
+            _ => (vec![], vec![]),
+        }
+    }
+
+    /// Exit if an artifact `action` loads was made with another version of the circuit, or
+    /// is stamped with a version that cannot be checked because the R1CS is not available to
+    /// hash. Returns the current version, or `None` when `action` touches no artifacts or,
+    /// as on a machine that only verifies unstamped artifacts, there is no R1CS.
+    fn check(&self, action: CircuitAction, options: &CommandOptions) -> Option<CircuitVersion> {
+        let (read, written) = self.read_and_written(action, options);
+        if read.is_empty() && written.is_empty() {
+            return None;
+        }
+
+        let version = CircuitVersion::from_r1cs(&self.r1cs_path);
+        for path in read {
+            let checked = match (&version, recorded_circuit_version(path)) {
+                (_, Err(e)) => Err(e),
+                (_, Ok(None)) => {
+                    warn!("{} has no recorded circuit version; not checking it", path);
+                    Ok(())
+                }
+                (Ok(version), Ok(Some(_))) => check_circuit_version(path, version),
+                (Err(e), Ok(Some(found))) => Err(ArtifactError::CircuitVersionUncheckable {
+                    path: path.to_string(),
+                    found: found.to_string(),
+                    reason: format!("could not hash {}: {e}", self.r1cs_path.display()),
+                }
+                .into()),
+            };
+            if let Err(e) = checked {
+                eprintln!("{} circuit version check failed: {}", self.circuit, e);
+                process::exit(1);
+            }
+        }
+        match version {
+            Ok(version) => Some(version),
+            Err(e) => {
+                warn!(
+                    "Not stamping {} artifacts with a circuit version; could not hash {}: {}",
+                    self.circuit,
+                    self.r1cs_path.display(),
+                    e
+                );
+                None
+            }
+        }
     }
 
-    let first = &tail[0];
-    let (action, option_start) = match first.as_str() {
-        "run" => (CircuitAction::Run, 1),
-        "setup" => (CircuitAction::Setup, 1),
-        "prove" => (CircuitAction::Prove, 1),
-        "verify" => (CircuitAction::Verify, 1),
-        "reblind" => (CircuitAction::Reblind, 1),
-        "generate_shared_blinds" => (CircuitAction::GenerateSharedBlinds, 1),
-        "benchmark" => (CircuitAction::Benchmark, 1),
-        s if s.starts_with('-') => (CircuitAction::Run, 0),
-        other => {
-            return Err(format!(
-                "Unknown action '{other}' for {:?}. Expected one of run|setup|prove|verify|reblind|generate_shared_blinds|benchmark.",
-                circuit
-            ))
+    /// Record `version` for the artifacts `action` saved
+    fn stamp(
+        &self,
+        version: Option<&CircuitVersion>,
+        action: CircuitAction,
+        options: &CommandOptions,
+    ) {
+        let Some(version) = version else {
+            return;
+        };
+        for path in self.read_and_written(action, options).1 {
+            if let Err(e) = record_circuit_version(path, version) {
+                warn!("Could not record the circuit version of {}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Time both setups, then extrapolate the remaining benchmark steps from a calibration run
+fn run_benchmark_estimate(options: &CommandOptions) {
+    let prepare = measure_setup("Prepare", PrepareCircuit::from_input(options.input.clone()));
+    let show = measure_setup("Show", ShowCircuit::from_input(options.input.clone()));
+
+    info!(
+        constraints = CALIBRATION_CONSTRAINTS,
+        "Calibrating the cost model"
+    );
+    let model = match CostModel::calibrate() {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!("Calibration failed: {}", e);
+            process::exit(1);
         }
     };
+    let estimate = estimate_pipeline(&model, prepare, show);
 
-    if action == CircuitAction::GenerateSharedBlinds && circuit != CircuitKind::Prepare {
-        return Err(
-            "The generate_shared_blinds action is only supported for the Prepare circuit".into(),
+    if options.format == OutputFormat::Json {
+        let json = serde_json::json!({
+            "model": model,
+            "prepare": prepare,
+            "show": show,
+            "steps": estimate.steps,
+            "total_ms": estimate.total_ms(),
+        });
+        write_output(
+            options,
+            &serde_json::to_string_pretty(&json).expect("serialize benchmark estimate"),
         );
+        return;
     }
 
-    let options_slice = &tail[option_start..];
-    let options = match action {
-        CircuitAction::Run
-        | CircuitAction::Prove
-        | CircuitAction::Setup
-        | CircuitAction::Benchmark => parse_options(options_slice)?,
-        CircuitAction::Verify | CircuitAction::Reblind | CircuitAction::GenerateSharedBlinds => {
-            ensure_no_options(options_slice)?
+    println!("\nEstimated benchmark pipeline");
+    for step in &estimate.steps {
+        let source = if step.measured {
+            "measured"
+        } else {
+            "estimated"
+        };
+        println!("  {:<18} {:>10.0} ms  ({})", step.step, step.ms, source);
+    }
+    println!("  {:<18} {:>10.0} ms", "Total", estimate.total_ms());
+    println!(
+        "\nOnly setup was run. The other steps are extrapolated from a {}-constraint calibration \
+         circuit and can be off by a factor of two; run `benchmark` for real numbers.",
+        CALIBRATION_CONSTRAINTS
+    );
+}
+
+/// Set up `circuit` without saving its keys and return its setup time and constraint count
+fn measure_setup<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    name: &str,
+    circuit: C,
+) -> MeasuredSetup {
+    let constraints = match dump_shape(&circuit) {
+        Ok(shape) => shape.num_constraints,
+        Err(e) => {
+            eprintln!("Failed to synthesize the {} circuit: {}", name, e);
+            process::exit(1);
         }
     };
 
-    Ok(ParsedCommand {
-        circuit,
-        action,
-        options,
-    })
+    let t0 = Instant::now();
+    if let Err(e) = setup_circuit_keys_no_save(circuit) {
+        eprintln!("{} setup failed: {}", name, e);
+        process::exit(1);
+    }
+    let setup_ms = t0.elapsed().as_secs_f64() * 1000.0;
+    info!(constraints, setup_ms, "{} setup completed", name);
+
+    MeasuredSetup {
+        constraints,
+        setup_ms,
+    }
 }
 
-fn ensure_no_options(args: &[String]) -> Result<CommandOptions, String> {
-    if args.is_empty() {
-        Ok(CommandOptions::default())
-    } else {
-        Err(format!("Unexpected options: {}", args.join(" ")))
+/// Run the full pipeline, which saves both circuits' artifacts, checking and stamping them
+/// for each circuit. What was saved is stamped even when a step fails.
+fn run_benchmark(paths: &Paths, cwd: &Path, options: &CommandOptions) {
+    let circuits = [
+        VersionedArtifacts::prepare(paths, cwd),
+        VersionedArtifacts::show(paths, cwd),
+    ];
+    let versions = circuits
+        .each_ref()
+        .map(|artifacts| artifacts.check(CircuitAction::Benchmark, options));
+
+    let results = run_complete_pipeline(paths, options.input.clone(), options.keep_going);
+    report_benchmark(&results, options);
+    for (artifacts, version) in circuits.iter().zip(&versions) {
+        artifacts.stamp(version.as_ref(), CircuitAction::Benchmark, options);
+    }
+    if !results.failures.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// Print benchmark results in the format selected with `--format`
+fn report_benchmark(results: &BenchmarkResults, options: &CommandOptions) {
+    match options.format {
+        OutputFormat::Table => results.print_summary(),
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(results).expect("serialize benchmark results");
+            write_output(options, &json);
+        }
+        OutputFormat::Prometheus => write_output(options, &render_prometheus(&results.metrics())),
+    }
+}
+
+/// Print single-command metrics when a machine-readable `--format` was requested.
+/// The table format relies on the existing log output.
+fn report_metrics(options: &CommandOptions, metrics: &[Metric]) {
+    match options.format {
+        OutputFormat::Table => {}
+        OutputFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&metrics_to_json(metrics)).expect("serialize metrics");
+            write_output(options, &json);
+        }
+        OutputFormat::Prometheus => write_output(options, &render_prometheus(metrics)),
+    }
+}
+
+/// Set up keys and prove with the in-memory proving key, then save the keys alongside the
+/// proof. Avoids saving the proving key only to load it straight back for `prove`.
+fn setup_and_prove<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk_path: &str,
+    vk_path: &str,
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+) {
+    let t0 = Instant::now();
+    let (pk, vk) = match setup_circuit_keys_no_save(circuit.clone()) {
+        Ok(keys) => keys,
+        Err(e) => {
+            eprintln!("Setup failed: {}", e);
+            process::exit(1);
+        }
+    };
+    info!(elapsed_ms = t0.elapsed().as_millis(), "Setup completed");
+
+    let t0 = Instant::now();
+    let proved = circuit_public_values(&circuit).and_then(|public_inputs| {
+        prove_circuit_with_pk(
+            circuit,
+            &pk,
+            public_inputs,
+            instance_path,
+            witness_path,
+            proof_path,
+        )
+    });
+    if let Err(e) = proved {
+        eprintln!("Failed to prove circuit: {}", e);
+        process::exit(1);
+    }
+    info!(elapsed_ms = t0.elapsed().as_millis(), "Proof generated");
+
+    if let Err(e) = save_keys(pk_path, vk_path, &pk, &vk) {
+        eprintln!("Failed to save keys: {}", e);
+        process::exit(1);
     }
 }
 
-fn parse_options(args: &[String]) -> Result<CommandOptions, String> {
-    let mut options = CommandOptions::default();
-    let mut index = 0;
+/// Prove without touching the disk and write the serialized proof to stdout.
+/// Returns the proof size in bytes.
+fn prove_to_stdout<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk_path: &str,
+) -> u64 {
+    let result = load_proving_key(pk_path)
+        .and_then(|pk| prove_circuit_to_writer(circuit, &pk, io::stdout().lock()));
+    match result {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Failed to prove circuit: {}", e);
+            process::exit(1);
+        }
+    }
+}
 
-    while index < args.len() {
-        let arg = &args[index];
-        if arg == "--input" || arg == "-i" {
-            index += 1;
-            let value = args
-                .get(index)
-                .ok_or_else(|| "Missing value for --input".to_string())?;
-            options.input = Some(PathBuf::from(value));
-        } else if let Some(value) = arg.strip_prefix("--input=") {
-            if value.is_empty() {
-                return Err("Missing value for --input".into());
+/// Write report output to `--metrics-out` if given, otherwise to stdout
+fn write_output(options: &CommandOptions, contents: &str) {
+    match &options.metrics_out {
+        Some(path) => {
+            if let Err(e) = fs::write(path, contents) {
+                eprintln!("Failed to write metrics to {}: {}", path.display(), e);
+                process::exit(1);
             }
-            options.input = Some(PathBuf::from(value));
-        } else if arg == "--help" || arg == "-h" {
-            print_usage();
-            process::exit(0);
-        } else {
-            return Err(format!("Unknown option '{arg}'"));
+            info!("Wrote metrics to {}", path.display());
+        }
+        None => println!("{}", contents.trim_end()),
+    }
+}
+
+/// Prove every input in `--dir` into `--out-dir`, stamp the new proofs with `version` and
+/// print a summary
+fn run_prove_dir<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    options: &CommandOptions,
+    pk_path: &str,
+    version: Option<&CircuitVersion>,
+    circuit_for: impl Fn(PathBuf) -> C,
+) {
+    let (Some(dir), Some(out_dir)) = (options.dir.as_ref(), options.out_dir.as_ref()) else {
+        eprintln!("Error: prove-dir requires --dir <path> and --out-dir <path>");
+        process::exit(1);
+    };
+
+    info!("Proving all inputs in {} with ZK-Spartan", dir.display());
+    let report = match prove_dir(dir, out_dir, pk_path, options.naming, circuit_for) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to prove inputs in {}: {}", dir.display(), e);
+            process::exit(1);
+        }
+    };
+
+    println!(
+        "Proved {} input(s) in {}: {} proved, {} already proved, {} failed",
+        report.total(),
+        dir.display(),
+        report.proved.len(),
+        report.skipped.len(),
+        report.failed.len()
+    );
+    for (input, proof) in &report.proved {
+        println!("  ✓ {} -> {}", input.display(), proof.display());
+        if let Some(version) = version {
+            let proof = proof.to_string_lossy();
+            if let Err(e) = record_circuit_version(&proof, version) {
+                warn!("Could not record the circuit version of {}: {}", proof, e);
+            }
+        }
+    }
+    for (input, err) in &report.failed {
+        println!("  ✗ {}: {}", input.display(), err);
+    }
+
+    if !report.failed.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// Verify every proof in `--dir` against `--vk` and print a pass/fail summary
+fn run_verify_dir(options: &CommandOptions) {
+    let (Some(dir), Some(vk)) = (options.dir.as_ref(), options.vk.as_ref()) else {
+        eprintln!("Error: verify-dir requires --dir <path> and --vk <path>");
+        process::exit(1);
+    };
+
+    info!("Verifying all proofs in {} with ZK-Spartan", dir.display());
+    let report = match verify_dir(dir, &vk.to_string_lossy()) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to verify proofs in {}: {}", dir.display(), e);
+            process::exit(1);
+        }
+    };
+
+    println!(
+        "Verified {} proof(s) in {}: {} passed, {} failed, {} other file(s) skipped",
+        report.total(),
+        dir.display(),
+        report.passed.len(),
+        report.failed.len(),
+        report.skipped.len()
+    );
+    for (path, err) in &report.failed {
+        println!("  ✗ {}: {}", path.display(), err);
+    }
+
+    if !report.failed.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// Verify `--proof` against `--vk` `--iterations` times and report latency and throughput
+fn run_benchmark_verify(options: &CommandOptions) {
+    let (Some(proof), Some(vk)) = (options.proof.as_ref(), options.vk.as_ref()) else {
+        eprintln!("Error: benchmark-verify requires --proof <path> and --vk <path>");
+        process::exit(1);
+    };
+    let iterations = options.iterations.unwrap_or(DEFAULT_VERIFY_ITERATIONS);
+
+    info!(
+        "Verifying {} {} times with ZK-Spartan",
+        proof.display(),
+        iterations
+    );
+    let summary =
+        match benchmark_verify(&proof.to_string_lossy(), &vk.to_string_lossy(), iterations) {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("Verify benchmark failed: {}", e);
+                process::exit(1);
+            }
+        };
+
+    if options.format == OutputFormat::Table {
+        let micros = |d: std::time::Duration| d.as_secs_f64() * 1e6;
+        println!("Verified {} {} times", proof.display(), summary.iterations);
+        println!("  Mean:     {:>10.1} µs", micros(summary.mean));
+        println!("  Median:   {:>10.1} µs", micros(summary.median));
+        println!("  p99:      {:>10.1} µs", micros(summary.p99));
+        println!("  Rate:     {:>10.1} verifications/s", summary.per_second());
+        return;
+    }
+    report_metrics(options, &summary.metrics());
+}
+
+/// Re-encode `--in` minimally into `--out`, or report that it already is minimal
+fn run_minimize_proof(options: &CommandOptions) {
+    let (Some(input), Some(out), Some(vk)) = (&options.proof, &options.out, &options.vk) else {
+        eprintln!("Error: minimize-proof requires --in <path>, --out <path> and --vk <path>");
+        process::exit(1);
+    };
+
+    let report = match minimize_proof(
+        &input.to_string_lossy(),
+        &out.to_string_lossy(),
+        &vk.to_string_lossy(),
+    ) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to minimize proof: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if report.written {
+        println!(
+            "Wrote {} ({} bytes, {} fewer than {})",
+            out.display(),
+            report.minimized_bytes,
+            report.original_bytes - report.minimized_bytes,
+            input.display()
+        );
+    } else {
+        println!(
+            "{} is already minimal ({} bytes); nothing written",
+            input.display(),
+            report.original_bytes
+        );
+    }
+}
+
+/// Print an input JSON Schema, or write it to `--out`
+fn run_export_schema(schema: serde_json::Value, options: &CommandOptions) {
+    let json = serde_json::to_string_pretty(&schema).expect("schema serialization is infallible");
+    match &options.out {
+        Some(path) => {
+            if let Err(e) = fs::write(path, json) {
+                eprintln!("Failed to write schema to {}: {}", path.display(), e);
+                process::exit(1);
+            }
+            info!("Wrote input schema to {}", path.display());
         }
-        index += 1;
+        None => println!("{}", json),
     }
+}
 
-    Ok(options)
+/// Warn loudly if the compiled `jwt.circom` no longer has the output layout the decoded claim
+/// length formula assumes
+fn check_prepare_layout(circuit: &PrepareCircuit) {
+    match circuit.check_output_layout() {
+        Ok(check) if check.matches() => {
+            info!(
+                age_claim_len = check.computed,
+                "jwt.r1cs output layout matches"
+            )
+        }
+        Ok(check) => warn!(
+            computed = check.computed,
+            inferred = check.inferred,
+            "!!! jwt.r1cs has {} ageClaim outputs but the input's claim rows give {}; the circuit \
+             was compiled with a different maxClaimsLength and claim indices will be wrong !!!",
+            check.inferred,
+            check.computed
+        ),
+        Err(e) => warn!("Could not check the jwt.r1cs output layout: {}", e),
+    }
+}
+
+/// Synthesize `circuit` as setup does and write its shape summary to `--out`
+fn run_dump_shape<C: SpartanCircuit<E>>(name: &'static str, circuit: &C, options: &CommandOptions) {
+    let path = options.out.as_ref().expect("dump-shape requires --out");
+    let shape = match dump_shape(circuit) {
+        Ok(shape) => shape,
+        Err(e) => {
+            eprintln!("Failed to synthesize the {} circuit: {}", name, e);
+            process::exit(1);
+        }
+    };
+    let json = serde_json::to_string_pretty(&shape).expect("shape serialization is infallible");
+    if let Err(e) = fs::write(path, json) {
+        eprintln!("Failed to write shape to {}: {}", path.display(), e);
+        process::exit(1);
+    }
+    info!(
+        constraints = shape.num_constraints,
+        variables = shape.num_variables,
+        "Wrote {} circuit shape to {}",
+        name,
+        path.display()
+    );
+}
+
+/// Check that the artifacts at `paths` come from the same setup and prove runs
+fn run_doctor(paths: &Paths) {
+    let report = doctor(paths);
+
+    for check in &report.checks {
+        match &check.result {
+            Ok(()) => println!("✓ {}", check.description),
+            Err(reason) => println!("✗ {}: {}", check.description, reason),
+        }
+    }
+
+    if report.is_consistent() {
+        println!("All artifacts are consistent");
+    } else {
+        println!("Some artifacts are missing or do not belong together");
+        process::exit(1);
+    }
 }
 
-fn print_usage() {
+/// Explain why the verifying key for `--pk` cannot be derived. A Spartan2 proving key holds
+/// only the verifying key's digest, so this fails without reading the (large) proving key.
+fn run_derive_vk(options: &CommandOptions) {
+    let pk_path = options.pk.as_ref().expect("derive-vk requires --pk");
     eprintln!(
-        "Usage:
-  ecdsa-spartan2 <prepare|show> [run|setup|prove|verify] [options]
-  ecdsa-spartan2 benchmark [options]
-
-Commands:
-  benchmark            Run complete pipeline with full metrics (setup, prove, reblind, verify)
-  prepare <action>     Run action on Prepare circuit
-  show <action>        Run action on Show circuit
-
-Actions:
-  run                  Run the complete circuit (setup, prove, verify)
-  setup                Generate proving and verifying keys
-  prove                Generate proof
-  verify               Verify proof
-  reblind              Reblind proof
-  benchmark            Run complete benchmark pipeline
-
-Options:
-  --input, -i <path>   Override the circuit input JSON (run/prove/setup/benchmark)
-
-Examples:
-  cargo run --release -- benchmark --input ../circom/inputs/jwt/generated.json
-  cargo run --release -- prepare run --input ../circom/inputs/jwt/generated.json
-  cargo run --release -- show prove --input ../circom/inputs/show/generated.json
-  cargo run --release -- show verify
-
-Legacy commands like `prepare`, `show`, `prove_prepare`, etc. are still supported."
+        "Cannot derive a verifying key from {}: {}",
+        pk_path.display(),
+        ArtifactError::VerifyingKeyNotDerivable
+    );
+    process::exit(1);
+}
+
+/// Print the public IO and shared commitment of the instance at `--input` as JSON
+fn run_inspect_instance(options: &CommandOptions) {
+    let Some(InputSource::Path(path)) = options.input.as_ref() else {
+        eprintln!("Error: inspect-instance requires --input <path>");
+        process::exit(1);
+    };
+
+    let instance = match load_instance(&path.to_string_lossy()) {
+        Ok(instance) => instance,
+        Err(e) => {
+            eprintln!("Failed to load instance from {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+
+    let json = serde_json::to_string_pretty(&instance_to_json(&instance))
+        .expect("instance JSON serialization is infallible");
+    write_output(options, &json);
+}
+
+/// Verify a Show proof against `expected_nonce`, the nonce the verifier issued, or else
+/// against the public inputs in its descriptor, when `show prove` wrote one next to it.
+///
+/// The prover writes the descriptor, so a nonce read from it only shows which nonce the proof
+/// was made for. It does not stop a replay unless the verifier compares it with the nonce it
+/// issued, which `--expected-nonce` does.
+fn verify_show(
+    proof_path: &str,
+    vk_path: &str,
+    expected_nonce: Option<&str>,
+) -> Result<(), L8zkError> {
+    if let Some(nonce) = expected_nonce {
+        let nonce = nonce
+            .parse::<rust_witness::BigInt>()
+            .map_err(|e| L8zkError::Serialization(Box::new(e)))
+            .and_then(|nonce| bigint_to_scalar(nonce).map_err(L8zkError::from))?;
+        let proof = load_proof(proof_path)?;
+        let vk = load_verifying_key(vk_path)?;
+        return verify_with_public_inputs(&proof, &vk, &[nonce]);
+    }
+
+    let descriptor_path = descriptor_path(proof_path);
+    if !Path::new(&descriptor_path).exists() {
+        return verify_circuit(proof_path, vk_path);
+    }
+
+    warn!(
+        "Checking the Show proof against the nonce in {}, which the prover wrote; pass \
+         --expected-nonce to check it against the nonce you issued",
+        descriptor_path
+    );
+    let descriptor = load_descriptor(&descriptor_path)?;
+    let proof = load_proof(proof_path)?;
+    let vk = load_verifying_key(vk_path)?;
+    verify_with_descriptor(&proof, &vk, &descriptor)
+}
+
+/// Check that the saved Prepare witness at `--witness` was generated from `--input`
+fn run_check_witness(options: &CommandOptions) {
+    let (Some(witness_path), Some(input)) = (options.witness.as_ref(), options.input.as_ref())
+    else {
+        eprintln!("Error: check-witness requires --witness <path> and --input <path>");
+        process::exit(1);
+    };
+
+    let witness = match load_witness(&witness_path.to_string_lossy()) {
+        Ok(witness) => witness,
+        Err(e) => {
+            eprintln!(
+                "Failed to load witness from {}: {}",
+                witness_path.display(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+    let cwd = env::current_dir().expect("current directory is accessible");
+    let json_value = match input.read(&cwd, DEFAULT_MAX_INPUT_SIZE, false) {
+        Ok(json_value) => json_value,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", input, e);
+            process::exit(1);
+        }
+    };
+
+    match check_prepare_witness(&witness, &json_value) {
+        Ok(check) if check.matches() => println!(
+            "{} matches {} ({} values)",
+            witness_path.display(),
+            input,
+            check.expected_len
+        ),
+        Ok(check) => {
+            println!(
+                "{} does not match {}: first difference at index {} ({} values saved, {} expected)",
+                witness_path.display(),
+                input,
+                check.first_mismatch.expect("a mismatch has an index"),
+                check.actual_len,
+                check.expected_len
+            );
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to regenerate the witness from {}: {}", input, e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Compare the shared values of `--prepare` and `--show` inputs before proving either
+fn run_check_link(options: &CommandOptions) {
+    let (Some(prepare_path), Some(show_path)) =
+        (options.prepare_input.as_ref(), options.show_input.as_ref())
+    else {
+        eprintln!("Error: check-link requires --prepare <path> and --show <path>");
+        process::exit(1);
+    };
+
+    let load = |path: &PathBuf| {
+        read_input_json(path, DEFAULT_MAX_INPUT_SIZE).unwrap_or_else(|e| {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            process::exit(1);
+        })
+    };
+    let (prepare_json, show_json) = (load(prepare_path), load(show_path));
+
+    let link = match check_link(&prepare_json, &show_json) {
+        Ok(link) => link,
+        Err(e) => {
+            eprintln!("Failed to derive shared values: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let mark = |ok: bool| if ok { "✓" } else { "✗" };
+    println!("{} KeyBindingX", mark(link.keybinding_x));
+    println!("{} KeyBindingY", mark(link.keybinding_y));
+    println!(
+        "{} Claims (Prepare: {} scalars, Show: {} scalars)",
+        mark(link.claims),
+        link.prepare_claim_len,
+        link.show_claim_len
+    );
+
+    if link.is_linked() {
+        println!("Inputs link: comm_W_shared will match");
+    } else {
+        println!("Inputs do not link: comm_W_shared will differ");
+        process::exit(1);
+    }
+}
+
+/// Estimate the proof size from the compiled R1CS at `r1cs_path` and report it
+fn run_estimate_proof(circuit: &'static str, r1cs_path: &Path, options: &CommandOptions) {
+    match estimate_proof_size_from_r1cs(r1cs_path) {
+        Ok(estimate) => report_estimate(circuit, &estimate, options),
+        Err(e) => {
+            eprintln!("Failed to estimate the {} proof size: {}", circuit, e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Print a proof-size estimate as a table, or as metrics for machine-readable formats
+fn report_estimate(circuit: &'static str, estimate: &ProofSizeEstimate, options: &CommandOptions) {
+    if options.format == OutputFormat::Table {
+        println!("Estimated {} proof size", circuit);
+        println!("  Constraints:         {}", estimate.num_constraints);
+        println!("  Variables:           {}", estimate.num_variables);
+        println!(
+            "  Witness commitment:  {} bytes",
+            estimate.witness_commitment_bytes
+        );
+        println!("  Opening:             {} bytes", estimate.opening_bytes);
+        println!("  Sumchecks:           {} bytes", estimate.sumcheck_bytes);
+        println!(
+            "  Total:               {} bytes (~{:.2} KB)",
+            estimate.total_bytes(),
+            estimate.total_bytes() as f64 / 1024.0
+        );
+        return;
+    }
+
+    report_metrics(
+        options,
+        &[
+            Metric::new(
+                "l8zk_estimated_proof_bytes",
+                "Estimated serialized proof size",
+                estimate.total_bytes() as f64,
+            )
+            .for_circuit(circuit),
+            Metric::new(
+                "l8zk_constraints",
+                "Number of R1CS constraints",
+                estimate.num_constraints as f64,
+            )
+            .for_circuit(circuit),
+        ],
     );
 }
@@ -0,0 +1,142 @@
+//! `manifest.json` listing the artifacts written by setup, prove and reblind.
+//!
+//! Every saved key, proof, instance, witness or blinds file is recorded in the `manifest.json`
+//! of the directory it was written to, so downstream tools can discover artifacts without
+//! hardcoding the `keys/*` paths. Re-saving an artifact replaces its entry. Proofs written by
+//! `prove_dir` also record the input they were proved from, and artifacts saved by the CLI
+//! record the `CircuitVersion` they were made with.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{errors::L8zkError, version::CircuitVersion};
+
+/// File name of the manifest kept next to the artifacts
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    ProvingKey,
+    VerifyingKey,
+    Proof,
+    Instance,
+    Witness,
+    SharedBlinds,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactEntry {
+    /// Path the artifact was written to, as given to the save function
+    pub path: String,
+    pub kind: ArtifactKind,
+    pub bytes: u64,
+    /// Hex-encoded SHA-256 of the file contents
+    pub sha256: String,
+    /// Input file the artifact was proved from, for proofs written by `prove_dir`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Circuit the artifact was made with, checked by `check_circuit_version`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_version: Option<CircuitVersion>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub artifacts: Vec<ArtifactEntry>,
+}
+
+/// Location of the manifest that records an artifact saved to `artifact_path`
+pub fn manifest_path_for(artifact_path: &str) -> PathBuf {
+    Path::new(artifact_path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(MANIFEST_FILE)
+}
+
+/// Read a manifest, treating a missing file as empty
+pub fn load_manifest(path: &Path) -> Result<Manifest, L8zkError> {
+    if !path.exists() {
+        return Ok(Manifest::default());
+    }
+    Ok(serde_json::from_slice(&fs::read(path)?)?)
+}
+
+/// Record `contents`, just written to `artifact_path`, in that directory's manifest
+pub fn record_artifact(
+    artifact_path: &str,
+    kind: ArtifactKind,
+    contents: &[u8],
+) -> Result<(), L8zkError> {
+    let manifest_path = manifest_path_for(artifact_path);
+    let mut manifest = load_manifest(&manifest_path)?;
+
+    let entry = ArtifactEntry {
+        path: artifact_path.to_string(),
+        kind,
+        bytes: contents.len() as u64,
+        sha256: hex::encode(Sha256::digest(contents)),
+        source: None,
+        circuit_version: None,
+    };
+    match manifest
+        .artifacts
+        .iter_mut()
+        .find(|existing| existing.path == entry.path)
+    {
+        Some(existing) => *existing = entry,
+        None => manifest.artifacts.push(entry),
+    }
+
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+    Ok(())
+}
+
+/// Record in the manifest that the artifact at `artifact_path`, already recorded by
+/// `record_artifact`, was proved from `source`
+pub fn record_artifact_source(artifact_path: &str, source: &Path) -> Result<(), L8zkError> {
+    update_entry(artifact_path, |entry| {
+        entry.source = Some(source.to_string_lossy().into_owned())
+    })
+}
+
+/// Record in the manifest that the artifact at `artifact_path`, already recorded by
+/// `record_artifact`, was made with `version`
+pub fn record_circuit_version(
+    artifact_path: &str,
+    version: &CircuitVersion,
+) -> Result<(), L8zkError> {
+    update_entry(artifact_path, |entry| {
+        entry.circuit_version = Some(version.clone())
+    })
+}
+
+/// Apply `update` to the manifest entry of `artifact_path` and save the manifest, failing
+/// with `NotFound` when `record_artifact` has not recorded the artifact
+fn update_entry(
+    artifact_path: &str,
+    update: impl FnOnce(&mut ArtifactEntry),
+) -> Result<(), L8zkError> {
+    let manifest_path = manifest_path_for(artifact_path);
+    let mut manifest = load_manifest(&manifest_path)?;
+
+    let entry = manifest
+        .artifacts
+        .iter_mut()
+        .find(|existing| existing.path == artifact_path)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{artifact_path} is not in {}", manifest_path.display()),
+            )
+        })?;
+    update(entry);
+
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+    Ok(())
+}
@@ -0,0 +1,228 @@
+//! Machine-readable output for timing and size measurements
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+
+/// A single gauge sample, optionally labelled with the circuit it belongs to
+#[derive(Debug, Clone)]
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub circuit: Option<&'static str>,
+    pub value: f64,
+}
+
+impl Metric {
+    pub fn new(name: &'static str, help: &'static str, value: f64) -> Self {
+        Self {
+            name,
+            help,
+            circuit: None,
+            value,
+        }
+    }
+
+    pub fn for_circuit(mut self, circuit: &'static str) -> Self {
+        self.circuit = Some(circuit);
+        self
+    }
+
+    /// Build a `*_seconds` metric from a millisecond measurement
+    pub fn seconds(name: &'static str, help: &'static str, elapsed_ms: u128) -> Self {
+        Self::new(name, help, elapsed_ms as f64 / 1000.0)
+    }
+}
+
+/// Render metrics in the Prometheus text exposition format.
+/// `# HELP`/`# TYPE` headers are emitted once per metric name, so samples sharing a
+/// name should be adjacent.
+pub fn render_prometheus(metrics: &[Metric]) -> String {
+    let mut out = String::new();
+    let mut previous: Option<&str> = None;
+
+    for metric in metrics {
+        if previous != Some(metric.name) {
+            out.push_str(&format!("# HELP {} {}\n", metric.name, metric.help));
+            out.push_str(&format!("# TYPE {} gauge\n", metric.name));
+            previous = Some(metric.name);
+        }
+        match metric.circuit {
+            Some(circuit) => out.push_str(&format!(
+                "{}{{circuit=\"{}\"}} {}\n",
+                metric.name, circuit, metric.value
+            )),
+            None => out.push_str(&format!("{} {}\n", metric.name, metric.value)),
+        }
+    }
+
+    out
+}
+
+/// Render metrics as a JSON array of `{ name, circuit, value }` objects
+pub fn metrics_to_json(metrics: &[Metric]) -> Value {
+    Value::Array(
+        metrics
+            .iter()
+            .map(|metric| {
+                json!({
+                    "name": metric.name,
+                    "circuit": metric.circuit,
+                    "value": metric.value,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Latency distribution of a repeated operation, e.g. the verifications of `benchmark_verify`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub iterations: usize,
+    pub total: Duration,
+    pub mean: Duration,
+    pub median: Duration,
+    pub p99: Duration,
+}
+
+impl LatencySummary {
+    /// Summarize per-iteration timings; `None` when there are none. Percentiles use the
+    /// nearest-rank method, so they are always one of the samples.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort();
+
+        let iterations = samples.len();
+        let total: Duration = samples.iter().sum();
+        let nearest_rank = |percentile: usize| samples[(iterations * percentile).div_ceil(100) - 1];
+        // In nanoseconds, since `Duration / u32` would truncate the iteration count
+        let mean_nanos = total.as_nanos() / iterations as u128;
+        let mean = Duration::new(
+            (mean_nanos / 1_000_000_000) as u64,
+            (mean_nanos % 1_000_000_000) as u32,
+        );
+
+        Some(Self {
+            iterations,
+            total,
+            mean,
+            median: nearest_rank(50),
+            p99: nearest_rank(99),
+        })
+    }
+
+    /// Sustained throughput: iterations completed per second of total time
+    pub fn per_second(&self) -> f64 {
+        self.iterations as f64 / self.total.as_secs_f64()
+    }
+
+    pub fn metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::new(
+                "l8zk_verify_mean_seconds",
+                "Mean verification latency",
+                self.mean.as_secs_f64(),
+            ),
+            Metric::new(
+                "l8zk_verify_median_seconds",
+                "Median verification latency",
+                self.median.as_secs_f64(),
+            ),
+            Metric::new(
+                "l8zk_verify_p99_seconds",
+                "99th percentile verification latency",
+                self.p99.as_secs_f64(),
+            ),
+            Metric::new(
+                "l8zk_verifications_per_second",
+                "Sustained verification throughput",
+                self.per_second(),
+            ),
+        ]
+    }
+}
+
+/// Structural counts accumulated while circuits are synthesized.
+///
+/// Circuits hold the counters behind an `Arc`, so the clones Spartan makes during setup and
+/// proving all add to the same totals. Counts accumulate across phases until `reset`.
+#[derive(Debug, Default)]
+pub struct SynthesisCounters {
+    constraints: AtomicUsize,
+    variables: AtomicUsize,
+    shared_values: AtomicUsize,
+}
+
+impl SynthesisCounters {
+    pub fn add_constraints(&self, count: usize) {
+        self.constraints.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn add_variables(&self, count: usize) {
+        self.variables.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn add_shared_values(&self, count: usize) {
+        self.shared_values.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> SynthesisCounts {
+        SynthesisCounts {
+            constraints: self.constraints.load(Ordering::Relaxed),
+            variables: self.variables.load(Ordering::Relaxed),
+            shared_values: self.shared_values.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.constraints.store(0, Ordering::Relaxed);
+        self.variables.store(0, Ordering::Relaxed);
+        self.shared_values.store(0, Ordering::Relaxed);
+    }
+}
+
+/// A point-in-time copy of `SynthesisCounters`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SynthesisCounts {
+    /// Constraints synthesized, from the circuit's `.r1cs` plus any added in Rust
+    pub constraints: usize,
+    /// Variables of the synthesized `.r1cs`
+    pub variables: usize,
+    /// Values allocated for `comm_W_shared`
+    pub shared_values: usize,
+}
+
+impl SynthesisCounts {
+    /// Scalars committed in the witness: the shared values plus the synthesized variables
+    pub fn committed_scalars(&self) -> usize {
+        self.shared_values + self.variables
+    }
+
+    pub fn metrics(&self, circuit: &'static str) -> Vec<Metric> {
+        vec![
+            Metric::new(
+                "l8zk_synthesized_constraints",
+                "Constraints synthesized",
+                self.constraints as f64,
+            )
+            .for_circuit(circuit),
+            Metric::new(
+                "l8zk_shared_values",
+                "Values allocated for comm_W_shared",
+                self.shared_values as f64,
+            )
+            .for_circuit(circuit),
+            Metric::new(
+                "l8zk_committed_scalars",
+                "Scalars committed in the witness",
+                self.committed_scalars() as f64,
+            )
+            .for_circuit(circuit),
+        ]
+    }
+}
@@ -0,0 +1,101 @@
+//! Self-describing verification packages for relying parties.
+//!
+//! A package bundles the verifying key, the proof and the expected public values into one
+//! versioned blob, so a verifier does not need to know which `keys/*` files belong together.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use spartan2::{traits::snark::R1CSSNARKTrait, zk_spartan::R1CSSNARK};
+
+use crate::{errors::PackageError, Scalar, E};
+
+/// Format version written at the start of every package
+pub const PACKAGE_VERSION: u32 = 1;
+
+type VerifierKey = <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey;
+
+#[derive(Deserialize)]
+struct VerificationPackage {
+    // Must stay the first field so the version can be read before decoding the rest
+    version: u32,
+    digest: [u8; 32],
+    vk: VerifierKey,
+    proof: R1CSSNARK<E>,
+    public_values: Vec<Scalar>,
+}
+
+/// Borrowed form of `VerificationPackage` with the same encoding, used when exporting
+#[derive(Serialize)]
+struct VerificationPackageRef<'a> {
+    version: u32,
+    digest: [u8; 32],
+    vk: &'a VerifierKey,
+    proof: &'a R1CSSNARK<E>,
+    public_values: &'a [Scalar],
+}
+
+/// What a successfully verified package attests to
+#[derive(Debug, Clone)]
+pub struct VerifyOutput {
+    pub version: u32,
+    /// SHA-256 over the encoded verifying key, proof and public values
+    pub digest: [u8; 32],
+    pub public_values: Vec<Scalar>,
+}
+
+fn package_digest(
+    vk: &VerifierKey,
+    proof: &R1CSSNARK<E>,
+    public_values: &[Scalar],
+) -> Result<[u8; 32], bincode::Error> {
+    let mut hasher = Sha256::new();
+    hasher.update(bincode::serialize(vk)?);
+    hasher.update(bincode::serialize(proof)?);
+    hasher.update(bincode::serialize(public_values)?);
+    Ok(hasher.finalize().into())
+}
+
+/// Bundle a verifying key, a proof and the public values the verifier should expect
+pub fn export_verification_package(
+    vk: &VerifierKey,
+    proof: &R1CSSNARK<E>,
+    public_ctx: &[Scalar],
+) -> Vec<u8> {
+    let digest = package_digest(vk, proof, public_ctx).expect("keys and proofs serialize");
+    let package = VerificationPackageRef {
+        version: PACKAGE_VERSION,
+        digest,
+        vk,
+        proof,
+        public_values: public_ctx,
+    };
+    bincode::serialize(&package).expect("keys and proofs serialize")
+}
+
+/// Unpack a package produced by `export_verification_package` and verify its proof
+pub fn verify_package(bytes: &[u8]) -> Result<VerifyOutput, PackageError> {
+    let version: u32 = bincode::deserialize(bytes)?;
+    if version != PACKAGE_VERSION {
+        return Err(PackageError::UnsupportedVersion {
+            found: version,
+            expected: PACKAGE_VERSION,
+        });
+    }
+
+    let package: VerificationPackage = bincode::deserialize(bytes)?;
+    let digest = package_digest(&package.vk, &package.proof, &package.public_values)?;
+    if digest != package.digest {
+        return Err(PackageError::DigestMismatch);
+    }
+
+    let public_values = package.proof.verify(&package.vk)?;
+    if public_values != package.public_values {
+        return Err(PackageError::PublicValuesMismatch);
+    }
+
+    Ok(VerifyOutput {
+        version,
+        digest,
+        public_values,
+    })
+}
@@ -1,54 +1,96 @@
-use std::{env::current_dir, fs::File, time::Instant};
+use std::{
+    env::current_dir,
+    fs::{self, read_dir},
+    io::{self, BufReader, BufWriter, Read, Write},
+    num::NonZeroUsize,
+    panic::resume_unwind,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    circuits::prepare_circuit::jwt_witness,
+    circuits::{
+        common::{guarded_witness, r1cs_wire_count, witness_len},
+        prepare_circuit::{jwt_witness, PrepareCircuit},
+        show_circuit::ShowCircuit,
+    },
+    errors::{L8zkError, LinkError, NonceError, VerifyError},
+    estimate::estimate_commit_memory,
+    manifest::{load_manifest, record_artifact_source, ArtifactKind, MANIFEST_FILE},
+    metrics::LatencySummary,
+    secret::zeroize_scalars,
     setup::{
         load_instance, load_proof, load_proving_key, load_shared_blinds, load_verifying_key,
         load_witness, save_instance, save_proof, save_shared_blinds, save_witness,
     },
-    utils::{convert_bigint_to_scalar, parse_jwt_inputs},
+    shape::dump_shape,
+    utils::{
+        compute_prepare_shared_scalars, convert_bigint_to_scalar, format_scalar,
+        pack_shared_scalars, parse_jwt_inputs, read_input_json, scalar_to_hex,
+        PrepareSharedScalars, DEFAULT_MAX_INPUT_SIZE,
+    },
     Scalar, E,
 };
 
 use bellpepper_core::SynthesisError;
-use ff::{derive::rand_core::OsRng, Field};
-use serde_json::Value;
+use ff::{
+    derive::rand_core::{self, OsRng, RngCore},
+    Field,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use spartan2::{
     bellpepper::{solver::SatisfyingAssignment, zk_r1cs::SpartanWitness},
     errors::SpartanError,
     provider::traits::DlogGroup,
+    r1cs::{R1CSWitness, SplitR1CSInstance},
     traits::{
-        circuit::SpartanCircuit, snark::R1CSSNARKTrait, transcript::TranscriptEngineTrait, Engine,
+        circuit::SpartanCircuit, pcs::PCSEngineTrait, snark::R1CSSNARKTrait,
+        transcript::TranscriptEngineTrait, Engine,
     },
     zk_spartan::R1CSSNARK,
 };
-use tracing::info;
+use tracing::{debug, info};
+
+/// Label the proving and reblinding transcripts are initialised with.
+///
+/// It must equal the label `R1CSSNARK::verify` starts its own transcript with, together with the
+/// `vk` and `public_values` absorptions that follow. Spartan2 hardcodes that label and offers
+/// no way to pass another one to the verifier, so a per-deployment domain separator mixed in
+/// here would produce proofs no verifier accepts. Domain separation needs support in Spartan2's
+/// verifier first.
+pub const TRANSCRIPT_LABEL: &[u8] = b"R1CSSNARK";
 
 /// Run circuit using ZK-Spartan (setup, prepare, prove, verify)
-pub fn run_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(circuit: C) {
+pub fn run_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+) -> Result<(), L8zkError> {
     // SETUP using ZK-Spartan
     let t0 = Instant::now();
-    let (pk, vk) = R1CSSNARK::<E>::setup(circuit.clone()).expect("setup failed");
+    let (pk, vk) = R1CSSNARK::<E>::setup(circuit.clone()).map_err(L8zkError::Setup)?;
     let setup_ms = t0.elapsed().as_millis();
     info!(elapsed_ms = setup_ms, "ZK-Spartan setup");
 
     // PREPARE
     let t0 = Instant::now();
     let mut prep_snark =
-        R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), false).expect("prep_prove failed");
+        R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), false).map_err(L8zkError::Prove)?;
     let prep_ms = t0.elapsed().as_millis();
     info!(elapsed_ms = prep_ms, "ZK-Spartan prep_prove");
 
     // PROVE
     let t0 = Instant::now();
-    let proof =
-        R1CSSNARK::<E>::prove(&pk, circuit.clone(), &mut prep_snark, false).expect("prove failed");
+    let proof = R1CSSNARK::<E>::prove(&pk, circuit.clone(), &mut prep_snark, false)
+        .map_err(L8zkError::Prove)?;
     let prove_ms = t0.elapsed().as_millis();
     info!(elapsed_ms = prove_ms, "ZK-Spartan prove");
 
     // VERIFY
     let t0 = Instant::now();
-    proof.verify(&vk).expect("verify errored");
+    proof.verify(&vk).map_err(L8zkError::Verify)?;
     let verify_ms = t0.elapsed().as_millis();
     info!(elapsed_ms = verify_ms, "ZK-Spartan verify");
 
@@ -58,15 +100,107 @@ pub fn run_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(circuit: C) {
         setup_ms, prep_ms, prove_ms, verify_ms
     );
 
-    info!("comm_W_shared: {:?}", proof.comm_W_shared());
+    info!(
+        "comm_W_shared: {}",
+        proof.comm_W_shared().map_or_else(
+            || "none".to_string(),
+            |commitment| commitment_hex(&commitment)
+        )
+    );
+    Ok(())
 }
 
-pub fn generate_shared_blinds<E: Engine>(shared_blinds_path: &str, n: usize) {
+/// Generate `n` random shared blinds, save them to `shared_blinds_path`, and return them
+/// so they can be used for reblinding without reloading the file
+pub fn generate_shared_blinds<E: Engine>(
+    shared_blinds_path: &str,
+    n: usize,
+) -> Result<Vec<E::Scalar>, L8zkError> {
     let blinds: Vec<_> = (0..n).map(|_| E::Scalar::random(OsRng)).collect();
-    if let Err(e) = save_shared_blinds::<E>(shared_blinds_path, &blinds) {
-        eprintln!("Failed to save instance: {}", e);
-        std::process::exit(1);
+    save_shared_blinds::<E>(shared_blinds_path, &blinds)?;
+    Ok(blinds)
+}
+
+/// Like `generate_shared_blinds`, but derive the blinds from `seed` so the same seed always
+/// gives the same blinds. Anyone who knows the seed can undo the reblinding, so this is only
+/// for reproducing bugs and pinning test fixtures.
+pub fn generate_shared_blinds_seeded<E: Engine>(
+    shared_blinds_path: &str,
+    n: usize,
+    seed: &[u8],
+) -> Result<Vec<E::Scalar>, L8zkError> {
+    let mut rng = SeededRng::new(seed);
+    let blinds: Vec<_> = (0..n).map(|_| E::Scalar::random(&mut rng)).collect();
+    save_shared_blinds::<E>(shared_blinds_path, &blinds)?;
+    Ok(blinds)
+}
+
+/// Deterministic byte stream for seeded blinds: SHA-256 over a domain tag, the seed and a
+/// block counter
+struct SeededRng {
+    seed: Vec<u8>,
+    counter: u64,
+    block: [u8; 32],
+    used: usize,
+}
+
+impl SeededRng {
+    const DOMAIN: &'static [u8] = b"l8zk shared blinds";
+
+    fn new(seed: &[u8]) -> Self {
+        Self {
+            seed: seed.to_vec(),
+            counter: 0,
+            block: [0; 32],
+            used: 32,
+        }
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
     }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.used == self.block.len() {
+                self.block = Sha256::new()
+                    .chain_update(Self::DOMAIN)
+                    .chain_update(&self.seed)
+                    .chain_update(self.counter.to_le_bytes())
+                    .finalize()
+                    .into();
+                self.counter += 1;
+                self.used = 0;
+            }
+            *byte = self.block[self.used];
+            self.used += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Generate one shared blind per shared row of `instance` and save them to
+/// `shared_blinds_path`. Linked Prepare and Show instances have the same shared rows, so either
+/// can size the blinds for both.
+pub fn generate_shared_blinds_for(
+    shared_blinds_path: &str,
+    instance: &SplitR1CSInstance<E>,
+) -> Result<Vec<Scalar>, L8zkError> {
+    generate_shared_blinds::<E>(shared_blinds_path, instance.num_shared_rows())
 }
 
 /// Only run the proving part of the circuit using ZK-Spartan (prep_prove, prove)
@@ -76,43 +210,235 @@ pub fn prove_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
     instance_path: &str,
     witness_path: &str,
     proof_path: &str,
-) {
+) -> Result<(), L8zkError> {
+    require_artifact(pk_path, "setup")?;
+
     let t0 = Instant::now();
-    let pk = load_proving_key(pk_path).expect("load proving key failed");
+    let pk = load_proving_key(pk_path)?;
     let load_pk_ms = t0.elapsed().as_millis();
 
     info!("ZK-Spartan load proving key: {} ms", load_pk_ms);
 
-    prove_circuit_with_pk(circuit, &pk, instance_path, witness_path, proof_path);
+    let public_inputs = circuit_public_values(&circuit)?;
+    prove_circuit_with_pk(
+        circuit,
+        &pk,
+        public_inputs,
+        instance_path,
+        witness_path,
+        proof_path,
+    )
+}
+
+/// Fail with `MissingArtifact` when the per-circuit artifact at `path` does not exist, naming
+/// the CLI `step` that writes it.
+///
+/// The circuit in the hint is read from the file name (`show_*` for Show, anything else for
+/// Prepare), which matches the default artifact names.
+fn require_artifact(path: &str, step: &str) -> Result<(), L8zkError> {
+    let circuit = match Path::new(path).file_name().and_then(|name| name.to_str()) {
+        Some(name) if name.starts_with("show") => "show",
+        _ => "prepare",
+    };
+    require_artifact_with_hint(path, || format!("run `{circuit} {step}` first"))
+}
+
+/// Fail with `MissingArtifact` when `path` does not exist, with the `hint` the caller gives
+fn require_artifact_with_hint(path: &str, hint: impl FnOnce() -> String) -> Result<(), L8zkError> {
+    if Path::new(path).exists() {
+        return Ok(());
+    }
+    Err(L8zkError::MissingArtifact {
+        path: path.to_string(),
+        hint: hint(),
+    })
+}
+
+/// Keys for one circuit, each loaded from disk on first use and reused by later calls.
+///
+/// Only `prove` and `reblind` need the proving key, so a context that ends up only verifying
+/// never reads it; the verifying key is likewise read on the first `verify`.
+pub struct ProverContext {
+    pk_path: String,
+    vk_path: String,
+    pk: OnceLock<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey>,
+    vk: OnceLock<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey>,
+}
+
+impl ProverContext {
+    /// Record the key locations without reading either key
+    pub fn new(pk_path: impl Into<String>, vk_path: impl Into<String>) -> Self {
+        Self {
+            pk_path: pk_path.into(),
+            vk_path: vk_path.into(),
+            pk: OnceLock::new(),
+            vk: OnceLock::new(),
+        }
+    }
+
+    /// Whether the proving key has been read yet
+    pub fn proving_key_loaded(&self) -> bool {
+        self.pk.get().is_some()
+    }
+
+    /// Load the proving key on first use, then reuse it
+    pub fn proving_key(
+        &self,
+    ) -> Result<&<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey, L8zkError> {
+        if let Some(pk) = self.pk.get() {
+            return Ok(pk);
+        }
+
+        require_artifact(&self.pk_path, "setup")?;
+        let t0 = Instant::now();
+        let pk = load_proving_key(&self.pk_path)?;
+        info!(
+            elapsed_ms = t0.elapsed().as_millis(),
+            "ZK-Spartan load proving key"
+        );
+        Ok(self.pk.get_or_init(|| pk))
+    }
+
+    /// Load the verifying key on first use, then reuse it
+    pub fn verifying_key(
+        &self,
+    ) -> Result<&<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey, L8zkError> {
+        if let Some(vk) = self.vk.get() {
+            return Ok(vk);
+        }
+
+        require_artifact(&self.vk_path, "setup")?;
+        let vk = load_verifying_key(&self.vk_path)?;
+        Ok(self.vk.get_or_init(|| vk))
+    }
+
+    /// `prove_circuit_in_memory` with this context's proving key
+    pub fn prove<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+        &self,
+        circuit: C,
+    ) -> Result<ProveOutput, L8zkError> {
+        prove_circuit_in_memory(circuit, self.proving_key()?)
+    }
+
+    /// Reblind `instance` and `witness` with this context's proving key, without writing
+    /// anything to disk
+    pub fn reblind(
+        &self,
+        instance: SplitR1CSInstance<E>,
+        witness: R1CSWitness<E>,
+        randomness: &[Scalar],
+    ) -> Result<ProveOutput, L8zkError> {
+        reblind_in_memory(instance, witness, self.proving_key()?, randomness)
+    }
+
+    /// `verify_circuit_with_loaded_data` with this context's verifying key
+    pub fn verify(&self, proof: &R1CSSNARK<E>) -> Result<(), L8zkError> {
+        verify_circuit_with_loaded_data(proof, self.verifying_key()?)
+    }
+}
+
+/// Proof artifacts produced by `prove_circuit_in_memory`
+pub struct ProveOutput {
+    pub instance: SplitR1CSInstance<E>,
+    pub witness: R1CSWitness<E>,
+    pub proof: R1CSSNARK<E>,
+}
+
+/// Prove like `prove_circuit_in_memory` on a device with `max_commit_memory` bytes to spare
+/// for the witness commitment, failing with `CommitMemoryExceeded` before any work when
+/// `estimate_commit_memory` puts the commitment over that budget.
+///
+/// Spartan2 commits to the witness in one call and cannot commit in chunks, so this only fails
+/// fast instead of running out of memory part way through; it does not lower peak memory.
+pub fn prove_within_memory<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    max_commit_memory: usize,
+) -> Result<ProveOutput, L8zkError> {
+    let estimated = estimate_commit_memory(dump_shape(&circuit)?.num_variables);
+    if estimated > max_commit_memory {
+        return Err(L8zkError::CommitMemoryExceeded {
+            estimated,
+            budget: max_commit_memory,
+        });
+    }
+    debug!(
+        estimated,
+        max_commit_memory, "Witness commitment fits the memory budget"
+    );
+    prove_circuit_in_memory(circuit, pk)
 }
 
 /// Only run the proving part of the circuit using ZK-Spartan with a pre-loaded proving key
-/// This is useful for benchmarking to exclude file I/O from timing measurements
+/// This is useful for benchmarking to exclude file I/O from timing measurements.
+///
+/// `public_inputs` are absorbed into the transcript in place of the circuit's own
+/// `public_values` and must be the values the circuit was configured with, e.g. the nonce given
+/// to `ShowCircuit::with_nonce`; `circuit_public_values` returns the circuit's defaults.
 pub fn prove_circuit_with_pk<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
     circuit: C,
     pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    public_inputs: Vec<Scalar>,
     instance_path: &str,
     witness_path: &str,
     proof_path: &str,
-) {
+) -> Result<(), L8zkError> {
+    let ProveOutput {
+        instance,
+        mut witness,
+        proof,
+    } = prove_circuit_in_memory_with_public_inputs(circuit, pk, public_inputs)?;
+
+    // Save the instance, witness, and proof to file
+    let saved = save_instance(instance_path, &instance)
+        .and_then(|()| save_witness(witness_path, &witness))
+        .and_then(|()| save_proof(proof_path, &proof));
+
+    // The witness is secret; clear it whether or not saving succeeded
+    zeroize_scalars(&mut witness.W);
+    saved
+}
+
+/// The public values `circuit` exposes, as its proofs carry them
+pub fn circuit_public_values<C: SpartanCircuit<E>>(circuit: &C) -> Result<Vec<Scalar>, L8zkError> {
+    SpartanCircuit::<E>::public_values(circuit).map_err(|e| {
+        L8zkError::Prove(SpartanError::SynthesisError {
+            reason: format!("Circuit does not provide public IO: {e}"),
+        })
+    })
+}
+
+/// Prove with a pre-loaded proving key and return the artifacts without writing anything to disk
+pub fn prove_circuit_in_memory<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+) -> Result<ProveOutput, L8zkError> {
+    let public_inputs = circuit_public_values(&circuit)?;
+    prove_circuit_in_memory_with_public_inputs(circuit, pk, public_inputs)
+}
+
+/// `prove_circuit_in_memory` with the public inputs supplied by the caller. Fails with
+/// `PublicInputsMismatch` if the circuit exposes different values, since the proof would not
+/// verify against the supplied ones.
+pub fn prove_circuit_in_memory_with_public_inputs<
+    C: SpartanCircuit<E> + Clone + std::fmt::Debug,
+>(
+    circuit: C,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    public_inputs: Vec<Scalar>,
+) -> Result<ProveOutput, L8zkError> {
     let t0 = Instant::now();
     let mut prep_snark =
-        R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), false).expect("prep_prove failed");
+        R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), false).map_err(L8zkError::Prove)?;
     let prep_ms = t0.elapsed().as_millis();
     info!("ZK-Spartan prep_prove: {} ms", prep_ms);
 
     let t0 = Instant::now();
-    let mut transcript = <E as Engine>::TE::new(b"R1CSSNARK");
+    let mut transcript = <E as Engine>::TE::new(TRANSCRIPT_LABEL);
     transcript.absorb(b"vk", &pk.vk_digest);
 
-    let public_values = SpartanCircuit::<E>::public_values(&circuit)
-        .map_err(|e| SpartanError::SynthesisError {
-            reason: format!("Circuit does not provide public IO: {e}"),
-        })
-        .unwrap();
-
     // absorb the public values into the transcript
-    transcript.absorb(b"public_values", &public_values.as_slice());
+    transcript.absorb(b"public_values", &public_inputs.as_slice());
 
     let (instance, witness) = SatisfyingAssignment::r1cs_instance_and_witness(
         &mut prep_snark.ps,
@@ -122,10 +448,14 @@ pub fn prove_circuit_with_pk<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
         false,
         &mut transcript,
     )
-    .unwrap();
+    .map_err(L8zkError::Prove)?;
+    if instance.public_values != public_inputs {
+        return Err(L8zkError::PublicInputsMismatch);
+    }
 
     // generate a witness and proof
-    let res = R1CSSNARK::<E>::prove_inner(&pk, &instance, &witness, &mut transcript).unwrap();
+    let res = R1CSSNARK::<E>::prove_inner(&pk, &instance, &witness, &mut transcript)
+        .map_err(L8zkError::Prove)?;
     let prove_ms = t0.elapsed().as_millis();
 
     info!("ZK-Spartan prove: {} ms", prove_ms);
@@ -137,23 +467,74 @@ pub fn prove_circuit_with_pk<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
         prep_ms, prove_ms, total_ms
     );
 
-    // Save the instance to file
-    if let Err(e) = save_instance(instance_path, &instance) {
-        eprintln!("Failed to save instance: {}", e);
-        std::process::exit(1);
-    }
+    Ok(ProveOutput {
+        instance,
+        witness,
+        proof: res,
+    })
+}
 
-    // Save the witness to file
-    if let Err(e) = save_witness(witness_path, &witness) {
-        eprintln!("Failed to save witness: {}", e);
-        std::process::exit(1);
-    }
+/// Prove with a pre-loaded proving key and write the bincode-encoded proof to `writer` (a
+/// socket, HTTP response body, pipe, ...), the counterpart of `verify_from_reader`. Nothing is
+/// written to disk and the witness is cleared once proving is done. Returns the number of
+/// bytes written. No public-input descriptor is written; send the public inputs alongside the
+/// proof if the verifier does not already know them.
+pub fn prove_circuit_to_writer<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    writer: impl Write,
+) -> Result<u64, L8zkError> {
+    let ProveOutput {
+        mut witness, proof, ..
+    } = prove_circuit_in_memory(circuit, pk)?;
+    zeroize_scalars(&mut witness.W);
 
-    // Save the proof to file
-    if let Err(e) = save_proof(proof_path, &res) {
-        eprintln!("Failed to save proof: {}", e);
-        std::process::exit(1);
-    }
+    let bytes = bincode::serialized_size(&proof)?;
+    let mut writer = BufWriter::new(writer);
+    bincode::serialize_into(&mut writer, &proof)?;
+    writer.flush()?;
+    Ok(bytes)
+}
+
+/// Generate the Prepare and Show witnesses concurrently and keep them in the circuits for
+/// proving. The two witnesses are independent, so overlapping them shortens the time to a
+/// linked proof pair.
+pub fn generate_linked_witnesses(
+    prepare: &PrepareCircuit,
+    show: &ShowCircuit,
+) -> Result<(), L8zkError> {
+    let t0 = Instant::now();
+    // The larger Prepare witness stays on the calling thread
+    let (prepare_result, show_result) = thread::scope(|scope| {
+        let show_handle = scope.spawn(|| show.generate_witness());
+        let prepare_result = prepare.generate_witness();
+        let show_result = match show_handle.join() {
+            Ok(result) => result,
+            Err(panic) => resume_unwind(panic),
+        };
+        (prepare_result, show_result)
+    });
+    prepare_result?;
+    show_result?;
+    info!(
+        elapsed_ms = t0.elapsed().as_millis(),
+        "Prepare and Show witnesses generated"
+    );
+    Ok(())
+}
+
+/// Prove a linked Prepare/Show pair with pre-loaded proving keys, generating both witnesses
+/// in parallel first. Returns the Prepare and Show outputs in that order.
+pub fn prove_linked_in_memory(
+    prepare: PrepareCircuit,
+    prepare_pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    show: ShowCircuit,
+    show_pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+) -> Result<(ProveOutput, ProveOutput), L8zkError> {
+    generate_linked_witnesses(&prepare, &show)?;
+    let prepare_output = prove_circuit_in_memory(prepare, prepare_pk)?;
+    let show_output = prove_circuit_in_memory(show, show_pk)?;
+    Ok((prepare_output, show_output))
 }
 
 pub fn reblind<C: SpartanCircuit<E>>(
@@ -163,12 +544,19 @@ pub fn reblind<C: SpartanCircuit<E>>(
     witness_path: &str,
     proof_path: &str,
     shared_blinds_path: &str,
-) {
-    let pk = load_proving_key(pk_path).expect("load proving key failed");
-    let instance = load_instance(instance_path).expect("load instance failed");
-    let witness = load_witness(witness_path).expect("load witness failed");
-    let randomness =
-        load_shared_blinds::<E>(shared_blinds_path).expect("load shared_blinds failed");
+) -> Result<(), L8zkError> {
+    require_artifact(pk_path, "setup")?;
+    require_artifact(instance_path, "prove")?;
+    require_artifact(witness_path, "prove")?;
+    // Shared blinds serve both circuits and are written by `prepare generate_shared_blinds`
+    require_artifact_with_hint(shared_blinds_path, || {
+        "run `prepare generate_shared_blinds` first".to_string()
+    })?;
+
+    let pk = load_proving_key(pk_path)?;
+    let instance = load_instance(instance_path)?;
+    let witness = load_witness(witness_path)?;
+    let randomness = load_shared_blinds::<E>(shared_blinds_path)?;
 
     reblind_with_loaded_data(
         circuit,
@@ -179,101 +567,701 @@ pub fn reblind<C: SpartanCircuit<E>>(
         instance_path,
         witness_path,
         proof_path,
+    )
+}
+
+/// Reblind the instance and witness of `bundle` with the shared `randomness` and prove again,
+/// returning the reblinded artifacts together. The instance and witness always come from the
+/// same bundle, so an instance cannot be reblinded against another proof's witness.
+pub fn reblind_bundle(
+    bundle: ProveOutput,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    randomness: &[<E as Engine>::Scalar],
+) -> Result<ProveOutput, L8zkError> {
+    reblind_in_memory(bundle.instance, bundle.witness, pk, randomness)
+}
+
+/// Reblind a batch of instance/witness pairs that share one `comm_W_shared`, e.g. credentials
+/// issued to one holder, with the same `randomness`, and verify every reblinded proof.
+///
+/// The proving and verifying keys are loaded once by the caller and reused for the whole batch.
+/// With `parallel`, the batch is split across the available cores. Proofs are returned in the
+/// order of `items`; the first failure, in that order, fails the batch.
+pub fn reblind_batch(
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    items: Vec<(SplitR1CSInstance<E>, R1CSWitness<E>)>,
+    randomness: &[<E as Engine>::Scalar],
+    vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    parallel: bool,
+) -> Result<Vec<R1CSSNARK<E>>, L8zkError> {
+    let t0 = Instant::now();
+    let count = items.len();
+
+    let reblind_one = |(instance, witness)| -> Result<R1CSSNARK<E>, L8zkError> {
+        let ProveOutput {
+            witness: mut new_witness,
+            proof,
+            ..
+        } = reblind_in_memory(instance, witness, pk, randomness)?;
+        // Only the proof leaves the batch; clear the reblinded witness straight away
+        zeroize_scalars(&mut new_witness.W);
+        verify_verdict(&proof, vk)?.into_result()?;
+        Ok(proof)
+    };
+
+    let results: Vec<Result<R1CSSNARK<E>, L8zkError>> = if parallel && count > 1 {
+        let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let chunk_len = count.div_ceil(workers);
+        let mut items = items.into_iter();
+        let chunks: Vec<Vec<_>> = (0..count.div_ceil(chunk_len))
+            .map(|_| items.by_ref().take(chunk_len).collect())
+            .collect();
+
+        let reblind_one = &reblind_one;
+        thread::scope(|scope| {
+            let handles: Vec<_> = chunks
+                .into_iter()
+                .map(|chunk| {
+                    scope.spawn(move || chunk.into_iter().map(reblind_one).collect::<Vec<_>>())
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| match handle.join() {
+                    Ok(results) => results,
+                    Err(panic) => resume_unwind(panic),
+                })
+                .collect()
+        })
+    } else {
+        items.into_iter().map(reblind_one).collect()
+    };
+
+    let proofs = results.into_iter().collect::<Result<Vec<_>, _>>()?;
+    info!(
+        count,
+        parallel,
+        elapsed_ms = t0.elapsed().as_millis(),
+        "ZK-Spartan reblind batch"
     );
+    Ok(proofs)
 }
 
-/// Reblind with pre-loaded data - useful for benchmarking to exclude file I/O
-pub fn reblind_with_loaded_data<C: SpartanCircuit<E>>(
-    circuit: C,
+fn reblind_in_memory(
+    instance: SplitR1CSInstance<E>,
+    witness: R1CSWitness<E>,
     pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
-    instance: spartan2::r1cs::SplitR1CSInstance<E>,
-    witness: spartan2::r1cs::R1CSWitness<E>,
     randomness: &[<E as Engine>::Scalar],
-    instance_path: &str,
-    witness_path: &str,
-    proof_path: &str,
-) {
-    assert_eq!(randomness.len(), instance.num_shared_rows());
+) -> Result<ProveOutput, L8zkError> {
+    if randomness.len() != instance.num_shared_rows() {
+        return Err(L8zkError::SharedBlindsMismatch {
+            expected: instance.num_shared_rows(),
+            found: randomness.len(),
+        });
+    }
 
     // Reblind instance and witness
-    let mut reblind_transcript = <E as Engine>::TE::new(b"R1CSSNARK");
+    let mut reblind_transcript = <E as Engine>::TE::new(TRANSCRIPT_LABEL);
     reblind_transcript.absorb(b"vk", &pk.vk_digest);
 
-    let public_values = SpartanCircuit::<E>::public_values(&circuit)
-        .map_err(|e| SpartanError::SynthesisError {
-            reason: format!("Circuit does not provide public IO: {e}"),
-        })
-        .unwrap();
+    // Take the public values from the instance being reblinded rather than from `circuit`, which
+    // is usually a default circuit and would not carry e.g. the original Show nonce
+    let public_values = instance.public_values.clone();
 
     // absorb the public values into the reblind_transcript
     reblind_transcript.absorb(b"public_values", &public_values.as_slice());
 
     let (new_instance, new_witness) = SatisfyingAssignment::reblind_r1cs_instance_and_witness(
-        &randomness,
+        randomness,
         instance,
         witness,
         &pk.ck,
         &mut reblind_transcript,
     )
-    .unwrap();
+    .map_err(L8zkError::Prove)?;
 
-    println!(
-        "new instance: {:?}",
-        new_instance
-            .clone()
+    debug!(
+        public_values = %new_instance
+            .public_values
+            .iter()
+            .map(format_scalar)
+            .collect::<Vec<_>>()
+            .join(", "),
+        comm_W_shared = %new_instance
             .comm_W_shared
-            .map(|v| v.comm.iter().for_each(|v| println!("v: {:?}", v.affine())))
+            .as_ref()
+            .map_or_else(|| "none".to_string(), commitment_hex),
+        "Reblinded instance"
     );
 
     // generate a witness and proof
     let res =
         R1CSSNARK::<E>::prove_inner(&pk, &new_instance, &new_witness, &mut reblind_transcript)
-            .unwrap();
+            .map_err(L8zkError::Prove)?;
+
+    Ok(ProveOutput {
+        instance: new_instance,
+        witness: new_witness,
+        proof: res,
+    })
+}
+
+/// Reblind with pre-loaded data - useful for benchmarking to exclude file I/O
+pub fn reblind_with_loaded_data<C: SpartanCircuit<E>>(
+    _circuit: C,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    instance: SplitR1CSInstance<E>,
+    witness: spartan2::r1cs::R1CSWitness<E>,
+    randomness: &[<E as Engine>::Scalar],
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+) -> Result<(), L8zkError> {
+    let ProveOutput {
+        instance: new_instance,
+        witness: mut new_witness,
+        proof: res,
+    } = reblind_in_memory(instance, witness, pk, randomness)?;
 
-    // Save the instance to file
-    if let Err(e) = save_instance(instance_path, &new_instance) {
-        eprintln!("Failed to save instance: {}", e);
-        std::process::exit(1);
+    // Save the instance, witness, and proof to file
+    let saved = save_instance(instance_path, &new_instance)
+        .and_then(|()| save_witness(witness_path, &new_witness))
+        .and_then(|()| save_proof(proof_path, &res));
+
+    // The witness is secret; clear it whether or not saving succeeded
+    zeroize_scalars(&mut new_witness.W);
+    saved
+}
+
+/// Outcome of a verification that ran to completion
+#[derive(Debug)]
+pub enum Verdict {
+    /// The proof verified
+    Valid,
+    /// The proof is well-formed but false, e.g. forged or made for other public values
+    Invalid(SpartanError),
+}
+
+impl Verdict {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Verdict::Valid)
     }
 
-    // Save the witness to file
-    if let Err(e) = save_witness(witness_path, &new_witness) {
-        eprintln!("Failed to save witness: {}", e);
-        std::process::exit(1);
+    /// `Ok` for a valid proof, `L8zkError::Verify` for an invalid one
+    pub fn into_result(self) -> Result<(), L8zkError> {
+        match self {
+            Verdict::Valid => Ok(()),
+            Verdict::Invalid(e) => Err(L8zkError::Verify(e)),
+        }
     }
+}
+
+/// Verify `proof` against `vk`, telling an invalid proof apart from a verification that could
+/// not run. A relying party should reject the credential on `Verdict::Invalid` but treat
+/// `VerifyError` as its own failure.
+pub fn verify_verdict(
+    proof: &R1CSSNARK<E>,
+    vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+) -> Result<Verdict, VerifyError> {
+    let t0 = Instant::now();
+    let verdict = match proof.verify(vk) {
+        Ok(_) => Verdict::Valid,
+        Err(e) if is_rejection(&e) => Verdict::Invalid(e),
+        Err(e) => return Err(VerifyError::Spartan(e)),
+    };
+    info!(
+        elapsed_ms = t0.elapsed().as_millis(),
+        valid = verdict.is_valid(),
+        "ZK-Spartan verify"
+    );
+    Ok(verdict)
+}
 
-    // Save the proof to file
-    if let Err(e) = save_proof(proof_path, &res) {
-        eprintln!("Failed to save proof: {}", e);
-        std::process::exit(1);
+/// Verify `proof` against each of `vks` in turn and return the index of the first key it
+/// verifies under, for verifiers that accept credentials from several issuers.
+///
+/// A key the proof fails under, whether rejected or unable to run, is skipped; the proof
+/// was most likely made for another issuer. `VerifyError::NoMatchingKey` is returned when
+/// every key fails.
+pub fn verify_against_any(
+    proof: &R1CSSNARK<E>,
+    vks: &[<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey],
+) -> Result<usize, VerifyError> {
+    for (index, vk) in vks.iter().enumerate() {
+        match proof.verify(vk) {
+            Ok(_) => {
+                info!(index, "ZK-Spartan proof verified under allowed key");
+                return Ok(index);
+            }
+            Err(e) => debug!(index, error = %e, "Proof does not verify under allowed key"),
+        }
     }
+    Err(VerifyError::NoMatchingKey { keys: vks.len() })
+}
+
+/// Load the proof at `proof_path` and the verifying key at `vk_path`, then `verify_verdict`.
+/// An artifact that is missing or cannot be decoded is a `VerifyError::Load`.
+pub fn verify_circuit_verdict(proof_path: &str, vk_path: &str) -> Result<Verdict, VerifyError> {
+    let load = || -> Result<_, L8zkError> {
+        require_artifact(vk_path, "setup")?;
+        require_artifact(proof_path, "prove")?;
+        Ok((load_proof(proof_path)?, load_verifying_key(vk_path)?))
+    };
+    let (proof, vk) = load().map_err(|e| VerifyError::Load(Box::new(e)))?;
+    verify_verdict(&proof, &vk)
+}
+
+/// Whether Spartan2 failed because the proof's checks did not hold, rather than because the
+/// verification itself could not run
+fn is_rejection(err: &SpartanError) -> bool {
+    matches!(
+        err,
+        SpartanError::ProofVerifyError { .. }
+            | SpartanError::InvalidSumcheckProof { .. }
+            | SpartanError::InvalidPCS { .. }
+    )
 }
 
-/// Only run the verification part using ZK-Spartan
-pub fn verify_circuit(proof_path: &str, vk_path: &str) {
-    let proof = load_proof(proof_path).expect("load proof failed");
-    let vk = load_verifying_key(vk_path).expect("load verifying key failed");
+/// Only run the verification part using ZK-Spartan.
+///
+/// Like every verify function here, this reads only the proof and the verifying key, never the
+/// proving key.
+pub fn verify_circuit(proof_path: &str, vk_path: &str) -> Result<(), L8zkError> {
+    require_artifact(vk_path, "setup")?;
+    require_artifact(proof_path, "prove")?;
+
+    let proof = load_proof(proof_path)?;
+    let vk = load_verifying_key(vk_path)?;
 
-    verify_circuit_with_loaded_data(&proof, &vk);
+    verify_circuit_with_loaded_data(&proof, &vk)
+}
+
+/// Deserialize a proof from any reader (socket, HTTP body, ...) and verify it against `vk`,
+/// without going through the filesystem
+pub fn verify_from_reader<R: Read>(
+    reader: R,
+    vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+) -> Result<(), L8zkError> {
+    let proof: R1CSSNARK<E> = bincode::deserialize_from(BufReader::new(reader))?;
+    verify_circuit_with_loaded_data(&proof, vk)
 }
 
-/// Verify circuit with pre-loaded data - useful for benchmarking to exclude file I/O
+/// Verify circuit with pre-loaded data - useful for benchmarking to exclude file I/O.
+///
+/// An invalid proof is `L8zkError::Verify`; a verification that could not run is
+/// `L8zkError::Unverifiable`. A successful verification is logged with the proof's
+/// `comm_W_shared`, in the hex form `instance_to_json` prints, so an audit log records which
+/// key-bound credential was presented.
 pub fn verify_circuit_with_loaded_data(
     proof: &R1CSSNARK<E>,
     vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
-) {
+) -> Result<(), L8zkError> {
     let t0 = Instant::now();
-    proof.verify(&vk).expect("verify errored");
-    let verify_ms = t0.elapsed().as_millis();
-    info!(elapsed_ms = verify_ms, "ZK-Spartan verify");
+    verify_verdict(proof, vk)?.into_result()?;
+    let shared_commitment = proof.comm_W_shared().as_ref().map(commitment_hex);
+    info!(
+        comm_w_shared = shared_commitment.as_deref().unwrap_or("none"),
+        elapsed_ms = t0.elapsed().as_millis(),
+        "Verification successful!"
+    );
+    Ok(())
+}
+
+/// Verify a Show proof and check that it was produced for `expected_nonce`, the challenge the
+/// verifier issued for this session. A proof captured in an earlier session carries a
+/// different nonce and is rejected.
+pub fn verify_show_with_nonce(
+    proof: &R1CSSNARK<E>,
+    vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    expected_nonce: Scalar,
+) -> Result<(), NonceError> {
+    let public_values = proof.verify(vk)?;
+    if public_values != [expected_nonce] {
+        return Err(NonceError::NonceMismatch);
+    }
+    Ok(())
+}
+
+/// Verify a proof and check that it was produced for `public_inputs`, the values the verifier
+/// expects for this context (a date, a session nonce, ...)
+pub fn verify_with_public_inputs(
+    proof: &R1CSSNARK<E>,
+    vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    public_inputs: &[Scalar],
+) -> Result<(), L8zkError> {
+    let public_values = proof.verify(vk).map_err(L8zkError::Verify)?;
+    if public_values != public_inputs {
+        return Err(L8zkError::PublicInputsMismatch);
+    }
+    Ok(())
+}
+
+/// Check that a Prepare and a Show proof were produced under the parameters of their verifying
+/// keys and commit shared values of the same shape.
+///
+/// Equal `comm_W_shared` alone does not show that both proofs come from the intended circuits:
+/// a Show proof made with a differently parameterized circuit could carry the same commitment.
+/// Each proof must verify against its own key, whose digest the verifier's transcript is bound
+/// to, and both must commit the same number of shared rows.
+pub fn assert_same_parameters(
+    prepare_proof: &R1CSSNARK<E>,
+    show_proof: &R1CSSNARK<E>,
+    prepare_vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    show_vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+) -> Result<(), LinkError> {
+    for (circuit, proof, vk) in [
+        ("Prepare", prepare_proof, prepare_vk),
+        ("Show", show_proof, show_vk),
+    ] {
+        proof
+            .verify(vk)
+            .map_err(|source| LinkError::WrongParameters { circuit, source })?;
+    }
+
+    let shared_rows =
+        |proof: &R1CSSNARK<E>| proof.comm_W_shared().map_or(0, |comm| comm.comm.len());
+    let (prepare, show) = (shared_rows(prepare_proof), shared_rows(show_proof));
+    if prepare != show {
+        return Err(LinkError::SharedRowsMismatch { prepare, show });
+    }
+    Ok(())
+}
+
+/// Outcome of verifying every proof in a directory against a single verifying key
+#[derive(Debug, Default)]
+pub struct VerifyDirReport {
+    pub passed: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, String)>,
+    /// `*.bin` files that are not proofs, e.g. instances, witnesses or shared blinds
+    pub skipped: Vec<PathBuf>,
+}
+
+impl VerifyDirReport {
+    pub fn total(&self) -> usize {
+        self.passed.len() + self.failed.len()
+    }
+}
+
+/// Verify every `*.bin` proof in `dir` against the verifying key at `vk_path`.
+/// The verifying key is loaded once; a proof that fails to load or verify is recorded
+/// in the report instead of aborting the run.
+///
+/// Other artifacts share the `.bin` extension, so a file the directory's manifest records as
+/// another kind is listed in `skipped` rather than counted as a failed proof. Any other `.bin`
+/// file is treated as a proof: one that does not decode, e.g. because it was truncated, fails.
+pub fn verify_dir(dir: &Path, vk_path: &str) -> Result<VerifyDirReport, L8zkError> {
+    let vk = load_verifying_key(vk_path)?;
+
+    let mut proof_paths: Vec<PathBuf> = read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "bin"))
+        .collect();
+    proof_paths.sort();
+
+    let manifest = load_manifest(&dir.join(MANIFEST_FILE))?;
+    let recorded_kind = |path: &Path| {
+        manifest
+            .artifacts
+            .iter()
+            .find(|entry| Path::new(&entry.path).file_name() == path.file_name())
+            .map(|entry| entry.kind)
+    };
+
+    let mut report = VerifyDirReport::default();
+    for path in proof_paths {
+        if recorded_kind(&path).is_some_and(|kind| kind != ArtifactKind::Proof) {
+            report.skipped.push(path);
+            continue;
+        }
+        let proof = match load_proof(&path.to_string_lossy()) {
+            Ok(proof) => proof,
+            Err(e) => {
+                report.failed.push((path, e.to_string()));
+                continue;
+            }
+        };
+        match verify_circuit_with_loaded_data(&proof, &vk) {
+            Ok(()) => report.passed.push(path),
+            Err(e) => report.failed.push((path, e.to_string())),
+        }
+    }
+
+    Ok(report)
+}
+
+/// How `prove_dir` names the proof written for each input
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ProofNaming {
+    /// `<sha256 of the input file>.bin`; an edited input gets a new proof, an unchanged one
+    /// keeps its name
+    #[default]
+    ContentHash,
+    /// `<input file stem>.bin`; an edited input keeps the name of its stale proof
+    Stem,
+}
+
+impl FromStr for ProofNaming {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hash" => Ok(ProofNaming::ContentHash),
+            "stem" => Ok(ProofNaming::Stem),
+            other => Err(format!(
+                "Unknown naming '{other}'. Expected one of hash|stem."
+            )),
+        }
+    }
+}
+
+impl ProofNaming {
+    /// Path under `out_dir` that the proof for `input` is written to
+    pub fn proof_path(self, out_dir: &Path, input: &Path) -> Result<PathBuf, L8zkError> {
+        let name = match self {
+            ProofNaming::ContentHash => hex::encode(Sha256::digest(fs::read(input)?)),
+            ProofNaming::Stem => input
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("{} has no file name", input.display()),
+                    )
+                })?,
+        };
+        Ok(out_dir.join(format!("{name}.bin")))
+    }
+}
+
+/// Outcome of proving every input in a directory, as `(input, proof)` pairs
+#[derive(Debug, Default)]
+pub struct ProveDirReport {
+    pub proved: Vec<(PathBuf, PathBuf)>,
+    /// Inputs whose proof was already in the output directory
+    pub skipped: Vec<(PathBuf, PathBuf)>,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl ProveDirReport {
+    pub fn total(&self) -> usize {
+        self.proved.len() + self.skipped.len() + self.failed.len()
+    }
+}
+
+/// Prove every `*.json` input in `input_dir` with the proving key at `pk_path`, writing each
+/// proof under `out_dir` as named by `naming` and recording its input in `out_dir`'s manifest.
+///
+/// Inputs whose proof already exists are skipped, so an interrupted run can be repeated. The
+/// proving key is loaded once, and only if something is left to prove. Instances and witnesses
+/// are not kept, and neither is a public-input descriptor. An input that fails to prove is
+/// recorded in the report instead of aborting the run.
+pub fn prove_dir<C, F>(
+    input_dir: &Path,
+    out_dir: &Path,
+    pk_path: &str,
+    naming: ProofNaming,
+    circuit_for: F,
+) -> Result<ProveDirReport, L8zkError>
+where
+    C: SpartanCircuit<E> + Clone + std::fmt::Debug,
+    F: Fn(PathBuf) -> C,
+{
+    let mut input_paths: Vec<PathBuf> = read_dir(input_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    input_paths.sort();
 
-    info!("Verification successful! Time: {} ms", verify_ms);
+    let mut report = ProveDirReport::default();
+    let mut pending = Vec::new();
+    for input in input_paths {
+        match naming.proof_path(out_dir, &input) {
+            Ok(proof_path) if proof_path.exists() => report.skipped.push((input, proof_path)),
+            Ok(proof_path) => pending.push((input, proof_path)),
+            Err(e) => report.failed.push((input, e.to_string())),
+        }
+    }
+    if pending.is_empty() {
+        return Ok(report);
+    }
+
+    require_artifact(pk_path, "setup")?;
+    let pk = load_proving_key(pk_path)?;
+    for (input, proof_path) in pending {
+        // Identical inputs share a content-hash name; the first one proves it
+        if proof_path.exists() {
+            report.skipped.push((input, proof_path));
+            continue;
+        }
+        let result = prove_circuit_in_memory(circuit_for(input.clone()), &pk).and_then(
+            |ProveOutput {
+                 mut witness, proof, ..
+             }| {
+                zeroize_scalars(&mut witness.W);
+                let proof_path = proof_path.to_string_lossy();
+                save_proof(&proof_path, &proof)
+                    .and_then(|()| record_artifact_source(&proof_path, &input))
+            },
+        );
+        match result {
+            Ok(()) => report.proved.push((input, proof_path)),
+            Err(e) => report.failed.push((input, e.to_string())),
+        }
+    }
+
+    info!(
+        proved = report.proved.len(),
+        skipped = report.skipped.len(),
+        failed = report.failed.len(),
+        "ZK-Spartan prove dir"
+    );
+    Ok(report)
+}
+
+/// Load the proof and verifying key once, then verify `iterations` times and summarize the
+/// latency of each verification. Characterizes sustained verifier throughput, which a single
+/// timed verify does not; the first rejected verification aborts the run.
+pub fn benchmark_verify(
+    proof_path: &str,
+    vk_path: &str,
+    iterations: usize,
+) -> Result<LatencySummary, L8zkError> {
+    if iterations == 0 {
+        return Err(L8zkError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "benchmark_verify needs at least one iteration",
+        )));
+    }
+    require_artifact(vk_path, "setup")?;
+    require_artifact(proof_path, "prove")?;
+    let proof = load_proof(proof_path)?;
+    let vk = load_verifying_key(vk_path)?;
+
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let t0 = Instant::now();
+        verify_verdict(&proof, &vk)?.into_result()?;
+        samples.push(t0.elapsed());
+    }
+
+    let summary = LatencySummary::from_samples(samples).expect("at least one verification ran");
+    info!(
+        iterations,
+        mean_us = summary.mean.as_micros(),
+        p99_us = summary.p99.as_micros(),
+        "ZK-Spartan verify benchmark"
+    );
+    Ok(summary)
+}
+
+/// Outcome of `minimize_proof`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinimizeReport {
+    pub original_bytes: u64,
+    pub minimized_bytes: u64,
+    /// False when the proof was already minimal and nothing was written
+    pub written: bool,
+}
+
+/// Re-encode the proof at `in_path` as plain bincode and write it to `out_path` if that is
+/// smaller, after checking the re-encoded proof verifies against `vk_path`.
+///
+/// `R1CSSNARK::verify` reads every field of the proof, and Spartan2 keeps the fields private, so
+/// there is no field to drop. What can be removed is encoding overhead around them: bytes
+/// trailing the bincode body, or the larger CBOR encoding. A proof written by `save_proof` is
+/// already minimal and is left alone.
+pub fn minimize_proof(
+    in_path: &str,
+    out_path: &str,
+    vk_path: &str,
+) -> Result<MinimizeReport, L8zkError> {
+    require_artifact(vk_path, "setup")?;
+    require_artifact(in_path, "prove")?;
+    let original_bytes = fs::metadata(in_path)?.len();
+    let proof = load_proof(in_path)?;
+    let vk = load_verifying_key(vk_path)?;
+
+    let minimized = bincode::serialize(&proof)?;
+    let minimized_proof: R1CSSNARK<E> = bincode::deserialize(&minimized)?;
+    verify_circuit_with_loaded_data(&minimized_proof, &vk)?;
+
+    let minimized_bytes = minimized.len() as u64;
+    let written = minimized_bytes < original_bytes;
+    if written {
+        save_proof(out_path, &minimized_proof)?;
+    }
+    info!(
+        original_bytes,
+        minimized_bytes, written, "ZK-Spartan minimize proof"
+    );
+    Ok(MinimizeReport {
+        original_bytes,
+        minimized_bytes,
+        written,
+    })
+}
+
+/// Outcome of `verify_batch_within`
+#[derive(Debug, Default)]
+pub struct BatchVerifyReport {
+    /// Results for the proofs verified before the budget ran out, in input order
+    pub results: Vec<Result<(), L8zkError>>,
+    /// Proofs not started because the budget ran out
+    pub remaining: usize,
+}
+
+impl BatchVerifyReport {
+    pub fn checked(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|result| result.is_ok()).count()
+    }
+}
+
+/// Verify `proofs` in order against `vk` until `budget` has elapsed. No verification starts
+/// after the budget runs out, but one already running is finished, so the call can overrun
+/// `budget` by up to one verification.
+pub fn verify_batch_within(
+    proofs: &[R1CSSNARK<E>],
+    vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    budget: Duration,
+) -> BatchVerifyReport {
+    let t0 = Instant::now();
+
+    let mut report = BatchVerifyReport::default();
+    for proof in proofs {
+        if t0.elapsed() >= budget {
+            break;
+        }
+        report
+            .results
+            .push(verify_circuit_with_loaded_data(proof, vk));
+    }
+    report.remaining = proofs.len() - report.checked();
+
+    info!(
+        checked = report.checked(),
+        remaining = report.remaining,
+        elapsed_ms = t0.elapsed().as_millis(),
+        "ZK-Spartan batch verify"
+    );
+    report
 }
 
 /// Generate witness for the Prepare circuit.
 /// Returns the full witness vector, the decoded age-claim bytes, and the extracted KeyBindingX/Y values.
 pub fn generate_prepare_witness(
     input_json_path: Option<&std::path::Path>,
+) -> Result<Vec<Scalar>, SynthesisError> {
+    generate_prepare_witness_with_limit(input_json_path, DEFAULT_MAX_INPUT_SIZE)
+}
+
+/// Same as `generate_prepare_witness`, rejecting input files larger than `max_input_size` bytes
+pub fn generate_prepare_witness_with_limit(
+    input_json_path: Option<&std::path::Path>,
+    max_input_size: u64,
 ) -> Result<Vec<Scalar>, SynthesisError> {
     let root = current_dir().unwrap().join("../circom");
 
@@ -283,20 +1271,140 @@ pub fn generate_prepare_witness(
 
     info!("Loading prepare inputs from {}", json_path.display());
 
-    let json_file = File::open(&json_path).map_err(|_| SynthesisError::AssignmentMissing)?;
-
-    let json_value: Value =
-        serde_json::from_reader(json_file).map_err(|_| SynthesisError::AssignmentMissing)?;
+    let json_value = read_input_json(&json_path, max_input_size)?;
+    let wires = witness_len(None, &PrepareCircuit::r1cs_path(&current_dir().unwrap()))?;
+    generate_prepare_witness_from_json(&json_value, wires)
+}
 
+/// Generate the Prepare witness from input JSON that has already been read. `wires` is the
+/// length the compiled `jwt.circom` expects, e.g. from `witness_len`; a witness of another
+/// length fails with `WitnessGenFailed`.
+pub fn generate_prepare_witness_from_json(
+    json_value: &Value,
+    wires: usize,
+) -> Result<Vec<Scalar>, SynthesisError> {
     // Parse inputs using declarative field definitions
-    let inputs = parse_jwt_inputs(&json_value)?;
+    let inputs = parse_jwt_inputs(json_value)?;
 
     // Generate witness using native Rust (rust-witness)
     info!("Generating witness using native Rust (rust-witness)...");
     let t0 = Instant::now();
-    let witness_bigint = jwt_witness(inputs);
+    let witness_bigint = guarded_witness("jwt", wires, || jwt_witness(inputs))?;
     info!("rust-witness time: {} ms", t0.elapsed().as_millis());
 
     let witness: Vec<Scalar> = convert_bigint_to_scalar(witness_bigint)?;
     Ok(witness)
 }
+
+/// Lay out a Circom wire vector in Spartan's committed order: the `shared` values, as packed
+/// by `pack_shared_scalars`, then every wire after the constant one
+fn committed_layout(shared: &[Scalar], wires: &[Scalar]) -> Vec<Scalar> {
+    let mut committed = Vec::with_capacity(shared.len() + wires.len().saturating_sub(1));
+    committed.extend_from_slice(shared);
+    committed.extend_from_slice(wires.get(1..).unwrap_or_default());
+    committed
+}
+
+/// Wrap a cached Circom witness into a Spartan `R1CSWitness`, drawing a fresh commitment blind
+/// from the proving key's commitment key.
+///
+/// `wires` is the wire vector from `generate_prepare_witness`, constant wire first, and
+/// `shared` the values its circuit commits to `comm_W_shared`, as packed by
+/// `pack_shared_scalars`. They are laid out the way proving synthesizes them: the shared
+/// values, then every wire after the constant one. Proving may pad the witness with zeros
+/// after these.
+pub fn scalars_to_r1cs_witness(
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    shared: &[Scalar],
+    wires: &[Scalar],
+) -> R1CSWitness<E> {
+    R1CSWitness {
+        W: committed_layout(shared, wires),
+        r_W: <E as Engine>::PCS::blind(&pk.ck),
+        is_small: false,
+    }
+}
+
+/// Extract the committed witness scalars from a Spartan `R1CSWitness`, e.g. one loaded
+/// with `load_witness`
+pub fn r1cs_witness_to_scalars(witness: &R1CSWitness<E>) -> Vec<Scalar> {
+    witness.W.clone()
+}
+
+/// Where a saved Prepare witness first differs from the one its input produces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessCheck {
+    /// Committed values the input produces
+    pub expected_len: usize,
+    /// Committed values in the saved witness
+    pub actual_len: usize,
+    /// First committed index at which the two differ, if any
+    pub first_mismatch: Option<usize>,
+}
+
+impl WitnessCheck {
+    pub fn matches(&self) -> bool {
+        self.first_mismatch.is_none()
+    }
+}
+
+/// Regenerate the Prepare witness from `json_value` and compare it with `witness`, e.g. one
+/// loaded with `load_witness`, to catch a witness left over from another input.
+///
+/// The comparison is in Spartan's committed layout, as proving lays the witness out: the
+/// shared values, then every Circom wire after the constant one. Zeros after those count as
+/// padding rather than a difference.
+pub fn check_prepare_witness(
+    witness: &R1CSWitness<E>,
+    json_value: &Value,
+) -> Result<WitnessCheck, L8zkError> {
+    let PrepareSharedScalars {
+        keybinding_x,
+        keybinding_y,
+        mut claim_scalars,
+    } = compute_prepare_shared_scalars(json_value)?;
+    let (shared, _layout) = pack_shared_scalars(keybinding_x, keybinding_y, &[&claim_scalars]);
+    zeroize_scalars(&mut claim_scalars);
+
+    let wire_count = r1cs_wire_count(&PrepareCircuit::r1cs_path(&current_dir()?))?;
+    let mut wires = generate_prepare_witness_from_json(json_value, wire_count)?;
+    let mut expected = committed_layout(&shared, &wires);
+    zeroize_scalars(&mut wires);
+
+    let actual = &witness.W;
+    let first_mismatch = (0..expected.len().max(actual.len())).find(|&index| {
+        match (expected.get(index), actual.get(index)) {
+            (Some(expected), Some(actual)) => expected != actual,
+            (None, Some(padding)) => !bool::from(padding.is_zero()),
+            _ => true,
+        }
+    });
+
+    let check = WitnessCheck {
+        expected_len: expected.len(),
+        actual_len: actual.len(),
+        first_mismatch,
+    };
+    zeroize_scalars(&mut expected);
+    Ok(check)
+}
+
+/// Render a split R1CS instance in readable form, e.g. one loaded with `load_instance`.
+///
+/// Public values are big-endian hex scalars; `comm_W_shared` is the hex of its bincode
+/// encoding, so the Prepare and Show instances can be compared for linkage.
+pub fn instance_to_json(instance: &SplitR1CSInstance<E>) -> Value {
+    let public_values: Vec<String> = instance.public_values.iter().map(scalar_to_hex).collect();
+    let shared_commitment = instance.comm_W_shared.as_ref().map(commitment_hex);
+
+    json!({
+        "num_shared_rows": instance.num_shared_rows(),
+        "public_values": public_values,
+        "comm_W_shared": shared_commitment,
+    })
+}
+
+/// Hex of the bincode encoding of a shared commitment
+fn commitment_hex<T: serde::Serialize>(commitment: &T) -> String {
+    hex::encode(bincode::serialize(commitment).expect("commitment serialization is infallible"))
+}
@@ -0,0 +1,76 @@
+//! Buffers for witness values and decoded claims that are zeroized when dropped, so a
+//! credential does not linger in freed memory after proving.
+//!
+//! Copies handed to circom-scotia or Spartan2 are owned by those crates and are not covered.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use zeroize::{zeroize_flat_type, Zeroize, ZeroizeOnDrop};
+
+use crate::Scalar;
+
+/// Overwrite `scalars` with zeros in a way the compiler cannot elide
+pub fn zeroize_scalars(scalars: &mut [Scalar]) {
+    for scalar in scalars.iter_mut() {
+        // SAFETY: `Scalar` is a plain array of limbs without `Drop`, and the all-zero bit
+        // pattern is the field element zero, so it is valid after zeroizing
+        unsafe { zeroize_flat_type(scalar as *mut Scalar) };
+    }
+}
+
+/// `Vec<Scalar>` that zeroizes its contents on drop. Its `Debug` output omits the values.
+///
+/// It is deliberately neither `Clone` nor `PartialEq`, so secrets are not copied around by
+/// accident; `to_vec` makes the one copy a synthesizer needs explicit.
+#[derive(Default)]
+pub struct SecretScalars(Vec<Scalar>);
+
+impl SecretScalars {
+    /// Copy the values out, e.g. to hand them to a synthesizer that takes ownership
+    pub fn to_vec(&self) -> Vec<Scalar> {
+        self.0.clone()
+    }
+}
+
+impl From<Vec<Scalar>> for SecretScalars {
+    fn from(scalars: Vec<Scalar>) -> Self {
+        Self(scalars)
+    }
+}
+
+impl Deref for SecretScalars {
+    type Target = [Scalar];
+
+    fn deref(&self) -> &[Scalar] {
+        &self.0
+    }
+}
+
+impl DerefMut for SecretScalars {
+    fn deref_mut(&mut self) -> &mut [Scalar] {
+        &mut self.0
+    }
+}
+
+impl Zeroize for SecretScalars {
+    fn zeroize(&mut self) {
+        zeroize_scalars(&mut self.0);
+    }
+}
+
+impl Drop for SecretScalars {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretScalars {}
+
+impl fmt::Debug for SecretScalars {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretScalars([REDACTED; {}])", self.0.len())
+    }
+}
@@ -1,17 +1,29 @@
 use std::{
-    fs::{create_dir_all, File},
-    io::{BufReader, Cursor, Write},
-    time::Instant,
+    any::type_name,
+    env::var,
+    fs::{self, create_dir_all, File},
+    io::{self, BufReader, Cursor, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    thread::sleep,
+    time::{Duration, Instant},
 };
 
+use ff::{Field, PrimeField};
+use serde::{Deserialize, Serialize};
+
 use spartan2::{
     r1cs::{R1CSWitness, SplitR1CSInstance},
     traits::{circuit::SpartanCircuit, snark::R1CSSNARKTrait, Engine},
     zk_spartan::R1CSSNARK,
 };
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::E;
+use crate::{
+    errors::{ArtifactError, L8zkError},
+    manifest::{record_artifact, ArtifactKind},
+    version::check_build_version,
+    E,
+};
 use memmap2::MmapOptions;
 
 pub const PREPARE_PROVING_KEY: &str = "keys/prepare_proving.key";
@@ -26,103 +38,287 @@ pub const SHOW_WITNESS: &str = "keys/show_witness.bin";
 pub const SHOW_INSTANCE: &str = "keys/show_instance.bin";
 pub const SHARED_BLINDS: &str = "keys/shared_blinds.bin";
 
+/// Tag written before the bincode body of proving key files
+pub const PROVING_KEY_TAG: &[u8; 8] = b"L8ZK-PK1";
+/// Tag written before the bincode body of verifying key files
+pub const VERIFYING_KEY_TAG: &[u8; 8] = b"L8ZK-VK1";
+/// Tag written before the `SharedBlindsEnvelope` of shared blinds files; untagged blinds files
+/// from earlier versions are bare bincode
+pub const SHARED_BLINDS_TAG: &[u8; 8] = b"L8ZK-SB1";
+/// Tag written before the CBOR body of proof files; untagged proof files are bincode
+pub const CBOR_PROOF_TAG: &[u8; 8] = b"L8ZK-PC1";
+
+/// Attempts `WriteRetry::from_env` allows when `L8ZK_WRITE_ATTEMPTS` is unset
+pub const DEFAULT_WRITE_ATTEMPTS: u32 = 3;
+
+/// How artifact writes are retried after a transient failure (`EAGAIN`, `ETIMEDOUT`, `EINTR`),
+/// as networked filesystems and object-store mounts report them. Other errors fail at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteRetry {
+    /// Total attempts, the first included; at least 1
+    pub attempts: u32,
+    /// Wait before the first retry, doubled before each further retry
+    pub initial_backoff: Duration,
+}
+
+impl Default for WriteRetry {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_WRITE_ATTEMPTS,
+            initial_backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+impl WriteRetry {
+    /// The default policy, with the attempt count taken from `L8ZK_WRITE_ATTEMPTS` when it is
+    /// set to a positive integer
+    pub fn from_env() -> Self {
+        let attempts = var("L8ZK_WRITE_ATTEMPTS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|&attempts| attempts > 0)
+            .unwrap_or(DEFAULT_WRITE_ATTEMPTS);
+        Self {
+            attempts,
+            ..Self::default()
+        }
+    }
+}
+
+/// Write `bytes` to `path`, retrying transient failures as `retry` allows
+pub fn write_with_retry(path: &str, bytes: &[u8], retry: &WriteRetry) -> Result<(), ArtifactError> {
+    let mut backoff = retry.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match File::create(path).and_then(|mut file| file.write_all(bytes)) {
+            Ok(()) => return Ok(()),
+            Err(e) if is_transient(&e) && attempt < retry.attempts => {
+                warn!(path, attempt, error = %e, "Write failed, retrying in {:?}", backoff);
+                sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(source) => {
+                return Err(ArtifactError::WriteFailed {
+                    path: path.to_string(),
+                    attempts: attempt,
+                    source,
+                })
+            }
+        }
+    }
+}
+
+fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted
+    )
+}
+
+/// Write an artifact with the retry policy from the environment
+fn write_artifact(path: &str, bytes: &[u8]) -> Result<(), ArtifactError> {
+    write_with_retry(path, bytes, &WriteRetry::from_env())
+}
+
+/// Strip the key tag from `bytes`, checking that it names the `expected` kind of key.
+///
+/// The tag turns a swapped `*_proving.key`/`*_verifying.key` into a clear error instead of a
+/// bincode failure. Untagged key files, written before the tags were added, are returned as-is.
+pub fn strip_key_tag(bytes: &[u8], expected: ArtifactKind) -> Result<&[u8], ArtifactError> {
+    let (found, body) = if let Some(body) = bytes.strip_prefix(PROVING_KEY_TAG) {
+        (ArtifactKind::ProvingKey, body)
+    } else if let Some(body) = bytes.strip_prefix(VERIFYING_KEY_TAG) {
+        (ArtifactKind::VerifyingKey, body)
+    } else {
+        return Ok(bytes);
+    };
+
+    if found != expected {
+        return Err(ArtifactError::WrongArtifactKind { expected, found });
+    }
+    Ok(body)
+}
+
+/// Locations of the key, proof, witness and instance files.
+///
+/// Each path can be overridden with an `L8ZK_`-prefixed environment variable named after its
+/// constant (e.g. `L8ZK_PREPARE_PROVING_KEY`); unset variables fall back to the constants above.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    pub prepare_proving_key: String,
+    pub prepare_verifying_key: String,
+    pub show_proving_key: String,
+    pub show_verifying_key: String,
+    pub prepare_proof: String,
+    pub prepare_witness: String,
+    pub prepare_instance: String,
+    pub show_proof: String,
+    pub show_witness: String,
+    pub show_instance: String,
+    pub shared_blinds: String,
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Self {
+            prepare_proving_key: PREPARE_PROVING_KEY.to_string(),
+            prepare_verifying_key: PREPARE_VERIFYING_KEY.to_string(),
+            show_proving_key: SHOW_PROVING_KEY.to_string(),
+            show_verifying_key: SHOW_VERIFYING_KEY.to_string(),
+            prepare_proof: PREPARE_PROOF.to_string(),
+            prepare_witness: PREPARE_WITNESS.to_string(),
+            prepare_instance: PREPARE_INSTANCE.to_string(),
+            show_proof: SHOW_PROOF.to_string(),
+            show_witness: SHOW_WITNESS.to_string(),
+            show_instance: SHOW_INSTANCE.to_string(),
+            shared_blinds: SHARED_BLINDS.to_string(),
+        }
+    }
+}
+
+impl Paths {
+    /// Read overrides from the environment, keeping the defaults for unset variables
+    pub fn from_env() -> Self {
+        let env_or = |name: &str, default: &str| var(name).unwrap_or_else(|_| default.to_string());
+        Self {
+            prepare_proving_key: env_or("L8ZK_PREPARE_PROVING_KEY", PREPARE_PROVING_KEY),
+            prepare_verifying_key: env_or("L8ZK_PREPARE_VERIFYING_KEY", PREPARE_VERIFYING_KEY),
+            show_proving_key: env_or("L8ZK_SHOW_PROVING_KEY", SHOW_PROVING_KEY),
+            show_verifying_key: env_or("L8ZK_SHOW_VERIFYING_KEY", SHOW_VERIFYING_KEY),
+            prepare_proof: env_or("L8ZK_PREPARE_PROOF", PREPARE_PROOF),
+            prepare_witness: env_or("L8ZK_PREPARE_WITNESS", PREPARE_WITNESS),
+            prepare_instance: env_or("L8ZK_PREPARE_INSTANCE", PREPARE_INSTANCE),
+            show_proof: env_or("L8ZK_SHOW_PROOF", SHOW_PROOF),
+            show_witness: env_or("L8ZK_SHOW_WITNESS", SHOW_WITNESS),
+            show_instance: env_or("L8ZK_SHOW_INSTANCE", SHOW_INSTANCE),
+            shared_blinds: env_or("L8ZK_SHARED_BLINDS", SHARED_BLINDS),
+        }
+    }
+}
+
 pub fn save_keys(
     pk_path: &str,
     vk_path: &str,
     pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
     vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), L8zkError> {
     if let Some(parent) = std::path::Path::new(pk_path).parent() {
         create_dir_all(parent)?;
     }
-    if let Some(parent) = std::path::Path::new(vk_path).parent() {
-        create_dir_all(parent)?;
-    }
 
-    let pk_bytes = bincode::serialize(pk)?;
-    let mut pk_file = File::create(pk_path)?;
-    pk_file.write_all(&pk_bytes)?;
+    let mut pk_bytes = PROVING_KEY_TAG.to_vec();
+    bincode::serialize_into(&mut pk_bytes, pk)?;
+    write_artifact(pk_path, &pk_bytes)?;
+    record_artifact(pk_path, ArtifactKind::ProvingKey, &pk_bytes)?;
 
     info!("Saved ZK-Spartan proving key to: {}", pk_path);
 
-    let vk_bytes = bincode::serialize(vk)?;
-    let mut vk_file = File::create(vk_path)?;
-    vk_file.write_all(&vk_bytes)?;
-    info!("Saved ZK-Spartan verifying key to: {}", vk_path);
-
-    Ok(())
+    save_verifying_key(vk_path, vk)
 }
 
-#[allow(dead_code)]
-pub fn load_keys(
-    pk_path: &str,
+pub fn save_verifying_key(
     vk_path: &str,
-) -> Result<
-    (
-        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
-        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
-    ),
-    Box<dyn std::error::Error>,
-> {
-    let pk_file = File::open(pk_path)?;
-    let pk = bincode::deserialize_from(&mut BufReader::new(pk_file))?;
-
-    info!("Loaded ZK-Spartan proving key from: {}", pk_path);
+    vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+) -> Result<(), L8zkError> {
+    if let Some(parent) = std::path::Path::new(vk_path).parent() {
+        create_dir_all(parent)?;
+    }
 
-    let vk_file = File::open(vk_path)?;
-    let vk = bincode::deserialize_from(&mut BufReader::new(vk_file))?;
-    info!("Loaded ZK-Spartan verifying key from: {}", vk_path);
+    let mut vk_bytes = VERIFYING_KEY_TAG.to_vec();
+    bincode::serialize_into(&mut vk_bytes, vk)?;
+    write_artifact(vk_path, &vk_bytes)?;
+    record_artifact(vk_path, ArtifactKind::VerifyingKey, &vk_bytes)?;
+    info!("Saved ZK-Spartan verifying key to: {}", vk_path);
 
-    Ok((pk, vk))
+    Ok(())
 }
 
+/// Load a proving key. Only setup, proving and reblinding need it; verification takes the
+/// verifying key alone, so verifiers never read this much larger file.
 pub fn load_proving_key(
     pk_path: &str,
-) -> Result<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey, Box<dyn std::error::Error>> {
+) -> Result<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey, L8zkError> {
+    check_build_version(pk_path)?;
     let pk_file = File::open(pk_path)?;
     let pk_mmap = unsafe { MmapOptions::new().map(&pk_file)? };
+    let pk_bytes = strip_key_tag(&pk_mmap[..], ArtifactKind::ProvingKey)?;
     let pk: <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey =
-        bincode::deserialize_from(Cursor::new(&pk_mmap[..]))?;
+        bincode::deserialize_from(Cursor::new(pk_bytes))?;
     Ok(pk)
 }
 
+/// Load a verifying key, the only key any verify function needs
 pub fn load_verifying_key(
     vk_path: &str,
-) -> Result<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey, Box<dyn std::error::Error>> {
+) -> Result<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey, L8zkError> {
+    check_build_version(vk_path)?;
     let vk_file = File::open(vk_path)?;
     let vk_mmap = unsafe { MmapOptions::new().map(&vk_file)? };
+    // Check the tag before decoding, so a proving key in its place is reported as such
+    let vk_bytes = strip_key_tag(&vk_mmap[..], ArtifactKind::VerifyingKey)?;
     let vk: <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey =
-        bincode::deserialize_from(Cursor::new(&vk_mmap[..]))?;
+        bincode::deserialize_from(Cursor::new(vk_bytes))?;
     Ok(vk)
 }
 
+/// Header of a shared blinds file, recording what the blinds belong to.
+///
+/// Blinds are scalars of one engine and mean nothing to another, so `load_shared_blinds`
+/// checks the header against the engine it is asked for instead of letting reblind fail later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedBlindsEnvelope {
+    /// Type name of the engine the blinds were generated for
+    pub engine: String,
+    /// Size of one encoded scalar, in bytes
+    pub scalar_bytes: usize,
+    /// Number of blinds that follow
+    pub count: usize,
+}
+
+impl SharedBlindsEnvelope {
+    /// Envelope for `count` blinds of engine `E`
+    pub fn for_engine<E: Engine>(count: usize) -> Self {
+        Self {
+            engine: type_name::<E>().to_string(),
+            scalar_bytes: E::Scalar::ZERO.to_repr().as_ref().len(),
+            count,
+        }
+    }
+}
+
 pub fn save_shared_blinds<E: Engine>(
     shared_blinds_path: &str,
     shared_blinds: &[E::Scalar],
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), L8zkError> {
     if let Some(parent) = std::path::Path::new(shared_blinds_path).parent() {
         create_dir_all(parent)?;
     }
 
-    let shared_blinds_bytes = bincode::serialize(shared_blinds)?;
-    let mut shared_blinds_file = File::create(shared_blinds_path)?;
-    shared_blinds_file.write_all(&shared_blinds_bytes)?;
+    let mut shared_blinds_bytes = SHARED_BLINDS_TAG.to_vec();
+    let envelope = SharedBlindsEnvelope::for_engine::<E>(shared_blinds.len());
+    bincode::serialize_into(&mut shared_blinds_bytes, &envelope)?;
+    bincode::serialize_into(&mut shared_blinds_bytes, shared_blinds)?;
+    write_artifact(shared_blinds_path, &shared_blinds_bytes)?;
+    record_artifact(
+        shared_blinds_path,
+        ArtifactKind::SharedBlinds,
+        &shared_blinds_bytes,
+    )?;
     info!("Saved ZK-Spartan shared_blinds to: {}", shared_blinds_path);
 
     Ok(())
 }
 
-pub fn save_proof(
-    proof_path: &str,
-    proof: &R1CSSNARK<E>,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn save_proof(proof_path: &str, proof: &R1CSSNARK<E>) -> Result<(), L8zkError> {
     if let Some(parent) = std::path::Path::new(proof_path).parent() {
         create_dir_all(parent)?;
     }
 
     let proof_bytes = bincode::serialize(proof)?;
-    let mut proof_file = File::create(proof_path)?;
-    proof_file.write_all(&proof_bytes)?;
+    write_artifact(proof_path, &proof_bytes)?;
+    record_artifact(proof_path, ArtifactKind::Proof, &proof_bytes)?;
     info!("Saved ZK-Spartan proof to: {}", proof_path);
 
     Ok(())
@@ -131,41 +327,68 @@ pub fn save_proof(
 pub fn save_instance(
     instance_path: &str,
     instance: &SplitR1CSInstance<E>,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), L8zkError> {
     if let Some(parent) = std::path::Path::new(instance_path).parent() {
         create_dir_all(parent)?;
     }
 
     let instance_bytes = bincode::serialize(instance)?;
-    let mut instance_file = File::create(instance_path)?;
-    instance_file.write_all(&instance_bytes)?;
+    write_artifact(instance_path, &instance_bytes)?;
+    record_artifact(instance_path, ArtifactKind::Instance, &instance_bytes)?;
     info!("Saved ZK-Spartan instance to: {}", instance_path);
 
     Ok(())
 }
 
-pub fn save_witness(
-    witness_path: &str,
-    witness: &R1CSWitness<E>,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn save_witness(witness_path: &str, witness: &R1CSWitness<E>) -> Result<(), L8zkError> {
     if let Some(parent) = std::path::Path::new(witness_path).parent() {
         create_dir_all(parent)?;
     }
 
     let witness_bytes = bincode::serialize(witness)?;
-    let mut witness_file = File::create(witness_path)?;
-    witness_file.write_all(&witness_bytes)?;
+    write_artifact(witness_path, &witness_bytes)?;
+    record_artifact(witness_path, ArtifactKind::Witness, &witness_bytes)?;
     info!("Saved ZK-Spartan witness to: {}", witness_path);
 
     Ok(())
 }
 
+/// Load shared blinds saved for engine `E`, rejecting blinds saved for another engine
 pub fn load_shared_blinds<E: Engine>(
     shared_blinds_path: &str,
-) -> Result<Vec<E::Scalar>, Box<dyn std::error::Error>> {
-    let shared_blinds_file = File::open(shared_blinds_path)?;
-    let shared_blinds: Vec<E::Scalar> =
-        bincode::deserialize_from(&mut BufReader::new(shared_blinds_file))?;
+) -> Result<Vec<E::Scalar>, L8zkError> {
+    check_build_version(shared_blinds_path)?;
+    let mut reader = BufReader::new(File::open(shared_blinds_path)?);
+
+    let mut tag = [0u8; 8];
+    let tagged = reader.read_exact(&mut tag).is_ok() && tag == *SHARED_BLINDS_TAG;
+    if !tagged {
+        // Bare bincode written before the envelope existed
+        reader.seek(SeekFrom::Start(0))?;
+        let shared_blinds: Vec<E::Scalar> = bincode::deserialize_from(&mut reader)?;
+        return Ok(shared_blinds);
+    }
+
+    let envelope: SharedBlindsEnvelope = bincode::deserialize_from(&mut reader)?;
+    let expected = SharedBlindsEnvelope::for_engine::<E>(envelope.count);
+    if envelope.engine != expected.engine || envelope.scalar_bytes != expected.scalar_bytes {
+        return Err(ArtifactError::SharedBlindsEngineMismatch {
+            found: envelope.engine,
+            found_scalar_bytes: envelope.scalar_bytes,
+            expected: expected.engine,
+            expected_scalar_bytes: expected.scalar_bytes,
+        }
+        .into());
+    }
+
+    let shared_blinds: Vec<E::Scalar> = bincode::deserialize_from(&mut reader)?;
+    if shared_blinds.len() != envelope.count {
+        return Err(ArtifactError::SharedBlindsCountMismatch {
+            declared: envelope.count,
+            found: shared_blinds.len(),
+        }
+        .into());
+    }
     info!(
         "Loaded ZK-Spartan shared_blinds from: {}",
         shared_blinds_path
@@ -173,16 +396,138 @@ pub fn load_shared_blinds<E: Engine>(
     Ok(shared_blinds)
 }
 
-pub fn load_proof(proof_path: &str) -> Result<R1CSSNARK<E>, Box<dyn std::error::Error>> {
-    let proof_file = File::open(proof_path)?;
-    let proof: R1CSSNARK<E> = bincode::deserialize_from(&mut BufReader::new(proof_file))?;
+/// Load a proof, detecting from its tag whether it was written by `save_proof` (bincode) or
+/// `save_proof_cbor`.
+pub fn load_proof(proof_path: &str) -> Result<R1CSSNARK<E>, L8zkError> {
+    check_build_version(proof_path)?;
+    let mut proof_bytes = Vec::new();
+    File::open(proof_path)?.read_to_end(&mut proof_bytes)?;
+
+    let proof = match proof_bytes.strip_prefix(CBOR_PROOF_TAG) {
+        Some(body) => decode_cbor_proof(proof_path, body)?,
+        None => bincode::deserialize(&proof_bytes)?,
+    };
     info!("Loaded ZK-Spartan proof from: {}", proof_path);
     Ok(proof)
 }
 
-pub fn load_instance(
-    instance_path: &str,
-) -> Result<SplitR1CSInstance<E>, Box<dyn std::error::Error>> {
+#[cfg(feature = "cbor")]
+fn decode_cbor_proof(_proof_path: &str, body: &[u8]) -> Result<R1CSSNARK<E>, L8zkError> {
+    ciborium::from_reader(body).map_err(|e| L8zkError::Serialization(Box::new(e)))
+}
+
+#[cfg(not(feature = "cbor"))]
+fn decode_cbor_proof(proof_path: &str, _body: &[u8]) -> Result<R1CSSNARK<E>, L8zkError> {
+    Err(ArtifactError::EncodingNotEnabled {
+        path: proof_path.to_string(),
+        encoding: "CBOR",
+        feature: "cbor",
+    }
+    .into())
+}
+
+/// Save a proof as tagged CBOR, for verifiers outside Rust that cannot read bincode
+#[cfg(feature = "cbor")]
+pub fn save_proof_cbor(proof_path: &str, proof: &R1CSSNARK<E>) -> Result<(), L8zkError> {
+    if let Some(parent) = std::path::Path::new(proof_path).parent() {
+        create_dir_all(parent)?;
+    }
+
+    let mut proof_bytes = CBOR_PROOF_TAG.to_vec();
+    ciborium::into_writer(proof, &mut proof_bytes)
+        .map_err(|e| L8zkError::Serialization(Box::new(e)))?;
+    write_artifact(proof_path, &proof_bytes)?;
+    record_artifact(proof_path, ArtifactKind::Proof, &proof_bytes)?;
+    info!("Saved ZK-Spartan CBOR proof to: {}", proof_path);
+
+    Ok(())
+}
+
+/// Load a proof written by `save_proof_cbor`, rejecting bincode proofs
+#[cfg(feature = "cbor")]
+pub fn load_proof_cbor(proof_path: &str) -> Result<R1CSSNARK<E>, L8zkError> {
+    check_build_version(proof_path)?;
+    let mut proof_bytes = Vec::new();
+    File::open(proof_path)?.read_to_end(&mut proof_bytes)?;
+
+    let body = proof_bytes.strip_prefix(CBOR_PROOF_TAG).ok_or_else(|| {
+        L8zkError::Serialization(format!("{proof_path} is not a CBOR proof").into())
+    })?;
+    let proof = decode_cbor_proof(proof_path, body)?;
+    info!("Loaded ZK-Spartan CBOR proof from: {}", proof_path);
+    Ok(proof)
+}
+
+/// Content-addressed proof storage: each proof is kept in `<dir>/<blake3>.bin`, named after the
+/// BLAKE3 hash of its bincode encoding.
+///
+/// Storing the same proof twice writes it once, and a proof is looked up by its hash alone. A
+/// file is written under a temporary name and renamed into place, so a name in the store always
+/// holds a complete proof; `get` still checks the contents against the name.
+#[derive(Debug, Clone)]
+pub struct ProofStore {
+    dir: PathBuf,
+}
+
+impl ProofStore {
+    /// A store in `dir`, which is created on the first `put`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path a proof with `hash` is stored at
+    pub fn path_for(&self, hash: &blake3::Hash) -> PathBuf {
+        self.dir.join(format!("{}.bin", hash.to_hex()))
+    }
+
+    /// Store `proof`, returning the hash it can be fetched by. A proof already in the store
+    /// is not written again.
+    pub fn put(&self, proof: &R1CSSNARK<E>) -> Result<blake3::Hash, L8zkError> {
+        let proof_bytes = bincode::serialize(proof)?;
+        let hash = blake3::hash(&proof_bytes);
+        let path = self.path_for(&hash);
+        if path.exists() {
+            info!("ZK-Spartan proof {} is already stored", hash);
+            return Ok(hash);
+        }
+
+        create_dir_all(&self.dir)?;
+        let partial = path.with_extension("bin.partial");
+        write_with_retry(
+            &partial.to_string_lossy(),
+            &proof_bytes,
+            &WriteRetry::from_env(),
+        )?;
+        fs::rename(&partial, &path)?;
+        record_artifact(&path.to_string_lossy(), ArtifactKind::Proof, &proof_bytes)?;
+        info!("Stored ZK-Spartan proof at: {}", path.display());
+        Ok(hash)
+    }
+
+    /// Fetch the proof stored under `hash`, or `None` when the store does not hold it
+    pub fn get(&self, hash: &blake3::Hash) -> Result<Option<R1CSSNARK<E>>, L8zkError> {
+        let path = self.path_for(hash);
+        let proof_bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if blake3::hash(&proof_bytes) != *hash {
+            return Err(ArtifactError::ContentHashMismatch {
+                path: path.display().to_string(),
+            }
+            .into());
+        }
+        Ok(Some(bincode::deserialize(&proof_bytes)?))
+    }
+}
+
+pub fn load_instance(instance_path: &str) -> Result<SplitR1CSInstance<E>, L8zkError> {
+    check_build_version(instance_path)?;
     let instance_file = File::open(instance_path)?;
     let instance: SplitR1CSInstance<E> =
         bincode::deserialize_from(&mut BufReader::new(instance_file))?;
@@ -190,7 +535,8 @@ pub fn load_instance(
     Ok(instance)
 }
 
-pub fn load_witness(witness_path: &str) -> Result<R1CSWitness<E>, Box<dyn std::error::Error>> {
+pub fn load_witness(witness_path: &str) -> Result<R1CSWitness<E>, L8zkError> {
+    check_build_version(witness_path)?;
     let witness_file = File::open(witness_path)?;
     let witness: R1CSWitness<E> = bincode::deserialize_from(&mut BufReader::new(witness_file))?;
     info!("Loaded ZK-Spartan witness from: {}", witness_path);
@@ -201,9 +547,9 @@ pub fn setup_circuit_keys<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
     circuit: C,
     pk_path: &str,
     vk_path: &str,
-) {
+) -> Result<(), L8zkError> {
     let t0 = Instant::now();
-    let (pk, vk) = R1CSSNARK::<E>::setup(circuit.clone()).expect("setup failed");
+    let (pk, vk) = R1CSSNARK::<E>::setup(circuit.clone()).map_err(L8zkError::Setup)?;
     let setup_ms = t0.elapsed().as_millis();
     info!(
         elapsed_ms = setup_ms,
@@ -211,23 +557,24 @@ pub fn setup_circuit_keys<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
         setup_ms as f64 / 1000.0
     );
 
-    if let Err(e) = save_keys(pk_path, vk_path, &pk, &vk) {
-        eprintln!("Failed to save keys: {}", e);
-        std::process::exit(1);
-    }
+    save_keys(pk_path, vk_path, &pk, &vk)?;
 
     info!("Keys generated and saved successfully!");
     info!("Proving key: {}", pk_path);
     info!("Verifying key: {}", vk_path);
+    Ok(())
 }
 
 /// Setup circuit keys without saving to file - useful for benchmarking
 /// Returns the proving and verifying keys
 pub fn setup_circuit_keys_no_save<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
     circuit: C,
-) -> (
-    <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
-    <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
-) {
-    R1CSSNARK::<E>::setup(circuit.clone()).expect("setup failed")
+) -> Result<
+    (
+        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    ),
+    L8zkError,
+> {
+    R1CSSNARK::<E>::setup(circuit.clone()).map_err(L8zkError::Setup)
 }
@@ -0,0 +1,152 @@
+//! Shape dumps: what a circuit's `synthesize` produces during setup, for debugging shape
+//! mismatches after a circom circuit is recompiled.
+//!
+//! The circuit is synthesized the way Spartan2's setup does it, `shared`, then `precommitted`,
+//! then `synthesize`, into a constraint system that records nothing but where each variable
+//! and constraint was allocated. Like setup, it never asks for witness values.
+
+use std::collections::BTreeMap;
+
+use bellpepper_core::{
+    num::AllocatedNum, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable,
+};
+use ff::Field;
+use serde::Serialize;
+use spartan2::traits::circuit::SpartanCircuit;
+
+use crate::{Scalar, E};
+
+/// Allocations made directly in one namespace, not counting its child namespaces
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct NamespaceShape {
+    /// Namespace names joined with `/`, starting with the phase (`shared`, `precommitted`,
+    /// `challenges` or `synthesize`)
+    pub path: String,
+    pub inputs: usize,
+    pub aux: usize,
+    pub constraints: usize,
+}
+
+/// Constraint system structure produced by synthesizing a circuit in setup phase
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ShapeDump {
+    pub num_constraints: usize,
+    /// Input and aux variables, excluding the constant one
+    pub num_variables: usize,
+    pub num_inputs: usize,
+    pub num_aux: usize,
+    /// Namespaces that allocated anything, sorted by path
+    pub namespaces: Vec<NamespaceShape>,
+}
+
+/// Synthesize `circuit` in setup phase and summarize the constraint system it produced
+pub fn dump_shape<C: SpartanCircuit<E>>(circuit: &C) -> Result<ShapeDump, SynthesisError> {
+    let mut cs = ShapeRecorder::default();
+
+    let shared = circuit.shared(&mut cs.namespace(|| "shared"))?;
+    let precommitted = circuit.precommitted(&mut cs.namespace(|| "precommitted"), &shared)?;
+    let challenges = (0..circuit.num_challenges())
+        .map(|i| {
+            AllocatedNum::alloc_input(
+                cs.namespace(|| "challenges")
+                    .namespace(|| format!("challenge_{i}")),
+                || Ok(Scalar::ZERO),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    circuit.synthesize(
+        &mut cs.namespace(|| "synthesize"),
+        &shared,
+        &precommitted,
+        Some(&challenges),
+    )?;
+
+    Ok(cs.into_dump())
+}
+
+/// Constraint system that only counts what is allocated in each namespace
+#[derive(Default)]
+struct ShapeRecorder {
+    path: Vec<String>,
+    num_inputs: usize,
+    num_aux: usize,
+    namespaces: BTreeMap<String, NamespaceShape>,
+}
+
+impl ShapeRecorder {
+    fn current(&mut self) -> &mut NamespaceShape {
+        let path = self.path.join("/");
+        self.namespaces
+            .entry(path.clone())
+            .or_insert_with(|| NamespaceShape {
+                path,
+                ..NamespaceShape::default()
+            })
+    }
+
+    fn into_dump(self) -> ShapeDump {
+        let namespaces: Vec<NamespaceShape> = self.namespaces.into_values().collect();
+        ShapeDump {
+            num_constraints: namespaces.iter().map(|ns| ns.constraints).sum(),
+            num_variables: self.num_inputs + self.num_aux,
+            num_inputs: self.num_inputs,
+            num_aux: self.num_aux,
+            namespaces,
+        }
+    }
+}
+
+impl ConstraintSystem<Scalar> for ShapeRecorder {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index = self.num_aux;
+        self.num_aux += 1;
+        self.current().aux += 1;
+        Ok(Variable::new_unchecked(Index::Aux(index)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // Input 0 is the constant one
+        self.num_inputs += 1;
+        self.current().inputs += 1;
+        Ok(Variable::new_unchecked(Index::Input(self.num_inputs)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, _a: LA, _b: LB, _c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        self.current().constraints += 1;
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.path.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self) {
+        self.path.pop();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
@@ -1,11 +1,172 @@
-use base64::engine::general_purpose::{STANDARD, URL_SAFE, URL_SAFE_NO_PAD};
+use base64::alphabet;
+use base64::engine::general_purpose::{
+    GeneralPurpose, GeneralPurposeConfig, STANDARD, URL_SAFE, URL_SAFE_NO_PAD,
+};
+use base64::engine::DecodePaddingMode;
 use base64::Engine;
 use bellpepper_core::SynthesisError;
+use ff::{Field, PrimeField};
+use rayon::prelude::*;
 use rust_witness::BigInt;
-use serde_json::Value;
-use std::{collections::HashMap, ops::Range, str::FromStr};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, Read},
+    ops::Range,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    errors::InputError, secret::SecretScalars, strict_json::from_slice_rejecting_duplicates, Scalar,
+};
+
+/// Coefficient `b` of the P-256 curve `y^2 = x^3 - 3x + b`, in decimal.
+/// The engine's scalar field is the P-256 base field, so the curve equation can be checked on `Scalar`.
+const P256_B: &str =
+    "41058363725152142129326129780047268409114441015993725554835256314039467401291";
+
+/// Order `n` of the P-256 group, in decimal. ECDSA reduces message hashes modulo `n`.
+const P256_N: &str =
+    "115792089210356248762697446949407573529996955224135760342422259061068512044369";
+
+/// Coordinates of the P-256 generator `G`, in decimal
+const P256_GX: &str =
+    "48439561293906451759052585252797914202762949526041747995844080717082404635286";
+const P256_GY: &str =
+    "36134250956749795798585127919587881956611106672985015071877198253568414405109";
+
+/// Default upper bound on the size of a circuit input JSON file (4 MiB)
+pub const DEFAULT_MAX_INPUT_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Format of a circuit input file, chosen by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Json,
+    /// `.yaml` or `.yml`, read with the `yaml` feature
+    Yaml,
+    /// `.toml`, read with the `toml` feature
+    Toml,
+}
+
+impl InputFormat {
+    /// Pick the format from the file extension; anything that is not YAML or TOML is JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => {
+                InputFormat::Yaml
+            }
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => InputFormat::Toml,
+            _ => InputFormat::Json,
+        }
+    }
+}
 
-use crate::Scalar;
+/// Read and parse a circuit input file, refusing files larger than `limit` bytes
+/// before any of it is parsed.
+///
+/// YAML and TOML inputs (see `InputFormat`) are parsed into the same `Value` shape as JSON.
+pub fn read_input_json(path: &Path, limit: u64) -> Result<Value, InputError> {
+    let bytes = read_input_bytes(path, limit)?;
+    parse_input(&bytes, InputFormat::from_path(path), false)
+}
+
+/// Same as `read_input_json`, but fails with `InputError::DuplicateField` when any object in
+/// the input repeats a key instead of silently keeping the last value. The YAML and TOML
+/// parsers reject repeated keys on their own.
+pub fn read_input_json_strict(path: &Path, limit: u64) -> Result<Value, InputError> {
+    let bytes = read_input_bytes(path, limit)?;
+    parse_input(&bytes, InputFormat::from_path(path), true)
+}
+
+/// Where a circuit's input comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    /// A JSON, YAML or TOML file, relative to the working directory unless absolute
+    Path(PathBuf),
+    /// JSON given directly, e.g. with `--input-json`
+    Inline(String),
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::Path(path) => write!(f, "{}", path.display()),
+            InputSource::Inline(_) => write!(f, "inline JSON"),
+        }
+    }
+}
+
+impl InputSource {
+    /// Read and parse the input under the same size limit for files and inline JSON. With
+    /// `strict`, a repeated key fails as in `read_input_json_strict`.
+    pub fn read(&self, cwd: &Path, limit: u64, strict: bool) -> Result<Value, InputError> {
+        match self {
+            InputSource::Path(path) => {
+                let path = cwd.join(path);
+                let bytes = read_input_bytes(&path, limit)?;
+                parse_input(&bytes, InputFormat::from_path(&path), strict)
+            }
+            InputSource::Inline(json) => {
+                let size = json.len() as u64;
+                if size > limit {
+                    return Err(InputError::InputTooLarge { size, limit });
+                }
+                parse_input(json.as_bytes(), InputFormat::Json, strict)
+            }
+        }
+    }
+}
+
+/// Parse input bytes in `format`, or report the feature it needs when that is disabled
+fn parse_input(bytes: &[u8], format: InputFormat, strict: bool) -> Result<Value, InputError> {
+    match format {
+        InputFormat::Json if strict => from_slice_rejecting_duplicates(bytes),
+        InputFormat::Json => {
+            serde_json::from_slice(bytes).map_err(|_| SynthesisError::AssignmentMissing.into())
+        }
+        #[cfg(feature = "yaml")]
+        InputFormat::Yaml => {
+            serde_yaml::from_slice(bytes).map_err(|_| SynthesisError::AssignmentMissing.into())
+        }
+        #[cfg(not(feature = "yaml"))]
+        InputFormat::Yaml => Err(InputError::UnsupportedInputFormat { feature: "yaml" }),
+        #[cfg(feature = "toml")]
+        InputFormat::Toml => std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|text| toml::from_str(text).ok())
+            .ok_or_else(|| SynthesisError::AssignmentMissing.into()),
+        #[cfg(not(feature = "toml"))]
+        InputFormat::Toml => Err(InputError::UnsupportedInputFormat { feature: "toml" }),
+    }
+}
+
+fn read_input_bytes(path: &Path, limit: u64) -> Result<Vec<u8>, InputError> {
+    let file = File::open(path).map_err(|_| SynthesisError::AssignmentMissing)?;
+    let size = file
+        .metadata()
+        .map_err(|_| SynthesisError::AssignmentMissing)?
+        .len();
+    if size > limit {
+        return Err(InputError::InputTooLarge { size, limit });
+    }
+
+    // Bound the read as well, in case the file grows or is not a regular file
+    let mut bytes = Vec::with_capacity(size as usize);
+    file.take(limit + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|_| SynthesisError::AssignmentMissing)?;
+    if bytes.len() as u64 > limit {
+        return Err(InputError::InputTooLarge {
+            size: bytes.len() as u64,
+            limit,
+        });
+    }
+    Ok(bytes)
+}
 
 #[derive(Clone, Copy)]
 pub enum FieldParser {
@@ -16,6 +177,130 @@ pub enum FieldParser {
     BigInt2DArray,
 }
 
+impl FieldParser {
+    /// JSON Schema of the values this parser accepts
+    fn json_schema(self) -> Value {
+        let u64_schema = json!({ "type": "integer", "minimum": 0 });
+        match self {
+            FieldParser::BigIntScalar => bigint_schema(),
+            FieldParser::U64Scalar => u64_schema,
+            FieldParser::BigIntArray => json!({ "type": "array", "items": bigint_schema() }),
+            FieldParser::U64Array => json!({ "type": "array", "items": u64_schema }),
+            FieldParser::BigInt2DArray => json!({
+                "type": "array",
+                "items": { "type": "array", "items": bigint_schema() },
+            }),
+        }
+    }
+}
+
+/// Schema of an integer accepted by `parse_bigint_value`: a decimal string, or a JSON number
+/// that fits in a u64
+fn bigint_schema() -> Value {
+    json!({
+        "oneOf": [
+            { "type": "string", "pattern": "^-?[0-9]+$" },
+            { "type": "integer", "minimum": 0 },
+        ]
+    })
+}
+
+/// Build a JSON Schema object requiring every field of `field_defs`, plus `optional` fields
+fn input_schema(
+    title: &str,
+    field_defs: &[(&str, FieldParser)],
+    optional: &[(&str, Value)],
+) -> Value {
+    let mut properties = Map::new();
+    for (name, parser) in field_defs {
+        properties.insert(name.to_string(), parser.json_schema());
+    }
+    for (name, schema) in optional {
+        properties.insert(name.to_string(), schema.clone());
+    }
+    let required: Vec<&str> = field_defs.iter().map(|(name, _)| *name).collect();
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": title,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// JSON Schema of a Prepare input, derived from the field definitions `parse_jwt_inputs` uses
+pub fn prepare_input_schema() -> Value {
+    let mut schema = input_schema(
+        "Prepare (jwt.circom) input",
+        &jwt_field_defs(&Value::Null),
+        &[
+            ("multiSig", json!({ "type": "boolean", "default": false })),
+            (
+                DISCLOSE_CLAIM_FIELD,
+                json!({ "type": "boolean", "default": true }),
+            ),
+            (
+                CLAIM_ENCODING_FIELD,
+                json!({ "enum": ["base64url", "hex", "raw"], "default": "base64url" }),
+            ),
+            (
+                DECODED_CLAIM_LENGTHS_FIELD,
+                json!({
+                    "type": "array",
+                    "items": { "type": ["integer", "null"], "minimum": 0 },
+                }),
+            ),
+            (DISCLOSED_CLAIM_RANGE_FIELD, claim_range_schema()),
+        ],
+    );
+    // The signature fields are arrays when `multiSig` is set
+    let signature_schema = json!({
+        "oneOf": [
+            FieldParser::BigIntScalar.json_schema(),
+            FieldParser::BigIntArray.json_schema(),
+        ]
+    });
+    for field in ["sig_r", "sig_s_inverse"] {
+        schema["properties"][field] = signature_schema.clone();
+    }
+    schema
+}
+
+/// JSON Schema of a Show input, derived from the field definitions `parse_show_inputs` uses
+pub fn show_input_schema() -> Value {
+    input_schema(
+        "Show (show.circom) input",
+        SHOW_FIELD_DEFS,
+        &[
+            (SHOW_NONCE_FIELD, bigint_schema()),
+            (
+                DISCLOSE_CLAIM_FIELD,
+                json!({ "type": "boolean", "default": true }),
+            ),
+            (DISCLOSED_CLAIM_RANGE_FIELD, claim_range_schema()),
+        ],
+    )
+}
+
+/// `[start, end]` byte offsets into the decoded claim
+fn claim_range_schema() -> Value {
+    json!({
+        "type": "array",
+        "items": { "type": "integer", "minimum": 0 },
+        "minItems": 2,
+        "maxItems": 2,
+    })
+}
+
+/// Report a field that failed to parse, keeping the reason so it reaches the user
+fn invalid_field(field_name: &str, reason: String) -> SynthesisError {
+    SynthesisError::IoError(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("input field '{field_name}': {reason}"),
+    ))
+}
+
 /// Generic function to parse input fields from JSON based on field definitions
 pub fn parse_inputs(
     json_value: &Value,
@@ -25,20 +310,17 @@ pub fn parse_inputs(
 
     for (field_name, parser) in field_defs {
         let value = match parser {
-            FieldParser::BigIntScalar => {
-                vec![parse_bigint_scalar(json_value, field_name)
-                    .map_err(|_| SynthesisError::AssignmentMissing)?]
-            }
-            FieldParser::U64Scalar => {
-                vec![parse_u64_scalar(json_value, field_name)
-                    .map_err(|_| SynthesisError::AssignmentMissing)?]
-            }
+            FieldParser::BigIntScalar => vec![parse_bigint_scalar(json_value, field_name)
+                .map_err(|e| invalid_field(field_name, e))?],
+            FieldParser::U64Scalar => vec![parse_u64_scalar(json_value, field_name)
+                .map_err(|e| invalid_field(field_name, e))?],
             FieldParser::BigIntArray => parse_bigint_string_array(json_value, field_name)
-                .map_err(|_| SynthesisError::AssignmentMissing)?,
-            FieldParser::U64Array => parse_u64_array(json_value, field_name)
-                .map_err(|_| SynthesisError::AssignmentMissing)?,
+                .map_err(|e| invalid_field(field_name, e))?,
+            FieldParser::U64Array => {
+                parse_u64_array(json_value, field_name).map_err(|e| invalid_field(field_name, e))?
+            }
             FieldParser::BigInt2DArray => parse_2d_bigint_array(json_value, field_name)
-                .map_err(|_| SynthesisError::AssignmentMissing)?,
+                .map_err(|e| invalid_field(field_name, e))?,
         };
         inputs.insert(field_name.to_string(), value);
     }
@@ -46,15 +328,34 @@ pub fn parse_inputs(
     Ok(inputs)
 }
 
+/// Number of signatures `jwt.circom` verifies per proof.
+///
+/// Inputs with `"multiSig": true` carry `sig_r`/`sig_s_inverse` as equal-length arrays, one
+/// entry per signed component, and the witness receives them as arrays in that order. The
+/// circuit currently consumes exactly one pair, so longer arrays are rejected until it grows.
+pub const JWT_MAX_SIGNATURES: usize = 1;
+
 // Circuit-specific input parsers
-/// Parse JWT circuit inputs from JSON
-pub fn parse_jwt_inputs(
-    json_value: &Value,
-) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
-    let field_defs: &[(&str, FieldParser)] = &[
+/// Field definitions of the JWT circuit inputs. The signature fields are arrays when the
+/// input sets `multiSig`.
+fn jwt_field_defs(json_value: &Value) -> Vec<(&'static str, FieldParser)> {
+    let multi_sig = json_value
+        .get("multiSig")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    let sig_parser = if multi_sig {
+        FieldParser::BigIntArray
+    } else {
+        FieldParser::BigIntScalar
+    };
+
+    vec![
+        // Signature fields: scalars, or arrays when `multiSig` is set
+        ("sig_r", sig_parser),
+        ("sig_s_inverse", sig_parser),
         // BigInt scalar fields (wrapped in vec)
-        ("sig_r", FieldParser::BigIntScalar),
-        ("sig_s_inverse", FieldParser::BigIntScalar),
+        // pubKeyX/Y is the issuer key that `ES256` checks the JWT signature against. It is not
+        // the key binding from `cnf.jwk` (the holder's device key), and the two normally differ.
         ("pubKeyX", FieldParser::BigIntScalar),
         ("pubKeyY", FieldParser::BigIntScalar),
         // U64 scalar fields (wrapped in vec)
@@ -66,37 +367,390 @@ pub fn parse_jwt_inputs(
         ("matchIndex", FieldParser::U64Array),
         ("matchLength", FieldParser::U64Array),
         ("claimLengths", FieldParser::BigIntArray),
-        ("decodeFlags", FieldParser::U64Array),
+        ("decodeFlags", FieldParser::BigIntArray),
         // 2D array fields (flattened)
         ("matchSubstring", FieldParser::BigInt2DArray),
         ("claims", FieldParser::BigInt2DArray),
         ("ageClaimIndex", FieldParser::U64Scalar),
-    ];
+    ]
+}
 
-    parse_inputs(json_value, field_defs)
+/// Parse JWT circuit inputs from JSON
+pub fn parse_jwt_inputs(
+    json_value: &Value,
+) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
+    let inputs = parse_inputs(json_value, &jwt_field_defs(json_value))?;
+    validate_signature_counts(&inputs["sig_r"], &inputs["sig_s_inverse"])?;
+    validate_period_index(json_value)?;
+
+    let flags = parse_decode_flags(json_value)?;
+    let claims = json_value
+        .get("claims")
+        .and_then(|value| value.as_array())
+        .ok_or(SynthesisError::AssignmentMissing)?;
+    validate_decode_flags(&flags, claims)?;
+
+    Ok(inputs)
 }
 
-/// Parse Show circuit inputs from JSON
+/// Validate a JWT circuit input in one pass, collecting every violation instead of stopping
+/// at the first.
+///
+/// Runs the field checks of `parse_jwt_inputs` plus the relationships between fields:
+/// `messageLength` fits in `message`, `claims` and `claimLengths` have one entry per claim,
+/// the match arrays have one entry per match, and `ageClaimIndex` names a claim. A
+/// relationship is only checked once the fields it involves parse, so a missing field is
+/// reported once.
+pub fn validate_jwt_input_schema(json_value: &Value) -> Result<(), Vec<InputError>> {
+    let mut errors = Vec::new();
+
+    let mut inputs = HashMap::new();
+    for field_def in jwt_field_defs(json_value) {
+        match parse_inputs(json_value, &[field_def]) {
+            Ok(parsed) => inputs.extend(parsed),
+            Err(e) => errors.push(e.into()),
+        }
+    }
+    let parsed = |fields: &[&str]| fields.iter().all(|field| inputs.contains_key(*field));
+    let u64_field = |field: &str| json_value.get(field).and_then(|value| value.as_u64());
+    let array_len = |field: &str| {
+        json_value
+            .get(field)
+            .and_then(|value| value.as_array())
+            .map_or(0, |array| array.len())
+    };
+
+    if parsed(&["sig_r", "sig_s_inverse"]) {
+        if let Err(e) = validate_signature_counts(&inputs["sig_r"], &inputs["sig_s_inverse"]) {
+            errors.push(e);
+        }
+    }
+
+    if parsed(&["message", "messageLength"]) {
+        let message_length = u64_field("messageLength").unwrap_or_default();
+        let max = array_len("message");
+        if message_length > max as u64 {
+            errors.push(InputError::MessageLengthOutOfRange {
+                message_length,
+                max,
+            });
+        } else if parsed(&["periodIndex"]) {
+            if let Err(e) = validate_period_index(json_value) {
+                errors.push(e);
+            }
+        }
+    }
+
+    if parsed(&["claims", "claimLengths"]) {
+        let (claims, claim_lengths) = (array_len("claims"), array_len("claimLengths"));
+        if claims != claim_lengths {
+            errors.push(InputError::ClaimLengthsCountMismatch {
+                claims,
+                claim_lengths,
+            });
+        }
+    }
+
+    if parsed(&["claims", "decodeFlags"]) {
+        let claims = json_value["claims"]
+            .as_array()
+            .map_or(&[][..], Vec::as_slice);
+        let checked =
+            parse_decode_flags(json_value).and_then(|flags| validate_decode_flags(&flags, claims));
+        if let Err(e) = checked {
+            errors.push(e);
+        }
+    }
+
+    if parsed(&["claims", "ageClaimIndex"]) {
+        let index = u64_field("ageClaimIndex").unwrap_or_default();
+        let claims = array_len("claims");
+        if index >= claims as u64 {
+            errors.push(InputError::AgeClaimIndexOutOfRange { index, claims });
+        }
+    }
+
+    if parsed(&["matchIndex", "matchLength", "matchSubstring"]) {
+        let (match_index, match_length, match_substring) = (
+            array_len("matchIndex"),
+            array_len("matchLength"),
+            array_len("matchSubstring"),
+        );
+        if match_index != match_length || match_index != match_substring {
+            errors.push(InputError::MatchArraysLengthMismatch {
+                match_index,
+                match_length,
+                match_substring,
+            });
+        } else if parsed(&["matchesCount"]) {
+            let matches_count = u64_field("matchesCount").unwrap_or_default();
+            if matches_count > match_index as u64 {
+                errors.push(InputError::MatchesCountOutOfRange {
+                    matches_count,
+                    max: match_index,
+                });
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Check that `periodIndex` points at the `.` separating the JWT header from the payload,
+/// within the first `messageLength` bytes of `message`
+pub fn validate_period_index(json_value: &Value) -> Result<(), InputError> {
+    let claimed = json_value
+        .get("periodIndex")
+        .and_then(|value| value.as_u64())
+        .ok_or(SynthesisError::AssignmentMissing)?;
+    let message_length = json_value
+        .get("messageLength")
+        .and_then(|value| value.as_u64())
+        .ok_or(SynthesisError::AssignmentMissing)?;
+    let message = json_value
+        .get("message")
+        .and_then(|value| value.as_array())
+        .ok_or(SynthesisError::AssignmentMissing)?
+        .iter()
+        .take(message_length as usize)
+        .map(parse_byte)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let separator = split_jwt(&message, message.len())
+        .ok()
+        .map(|jwt| jwt.payload.offset - 1);
+    if separator != Some(claimed as usize) {
+        return Err(InputError::BadPeriodIndex {
+            claimed,
+            actual_byte: message.get(claimed as usize).copied(),
+        });
+    }
+    Ok(())
+}
+
+/// Check that every `sig_r` has a matching `sig_s_inverse` and the circuit can verify them all
+fn validate_signature_counts(sig_r: &[BigInt], sig_s_inverse: &[BigInt]) -> Result<(), InputError> {
+    if sig_r.len() != sig_s_inverse.len() {
+        return Err(InputError::SignatureCountMismatch {
+            sig_r: sig_r.len(),
+            sig_s_inverse: sig_s_inverse.len(),
+        });
+    }
+    if sig_r.is_empty() || sig_r.len() > JWT_MAX_SIGNATURES {
+        return Err(InputError::UnsupportedSignatureCount {
+            count: sig_r.len(),
+            max: JWT_MAX_SIGNATURES,
+        });
+    }
+    Ok(())
+}
+
+/// How the JWT circuit's `ClaimDecoder` treats a claim, selected per claim by `decodeFlags`.
+///
+/// The circuit constrains each flag to be boolean. With `Decode` the claim is base64-decoded
+/// into `decodedClaims`; with `Skip` the decoder is fed constant padding and the decoded claim
+/// is zeroed, which is what unused claim slots should carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeFlag {
+    Skip = 0,
+    Decode = 1,
+}
+
+impl TryFrom<u64> for DecodeFlag {
+    type Error = u64;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DecodeFlag::Skip),
+            1 => Ok(DecodeFlag::Decode),
+            other => Err(other),
+        }
+    }
+}
+
+/// Read `decodeFlags`, whose entries may be JSON numbers or decimal strings like the other
+/// integer fields
+pub fn parse_decode_flags(json_value: &Value) -> Result<Vec<u64>, InputError> {
+    let flags = json_value
+        .get("decodeFlags")
+        .and_then(|value| value.as_array())
+        .ok_or(SynthesisError::AssignmentMissing)?;
+    flags
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let flag = match value {
+                Value::Number(n) => n.as_u64(),
+                Value::String(s) => s.parse::<u64>().ok(),
+                _ => None,
+            };
+            flag.ok_or_else(|| InputError::MalformedArrayEntry {
+                field: "decodeFlags",
+                index,
+                value: value.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Check that there is one recognised `DecodeFlag` per claim row
+pub fn validate_decode_flags(flags: &[u64], claims: &[Value]) -> Result<(), InputError> {
+    if flags.len() != claims.len() {
+        return Err(InputError::DecodeFlagsLengthMismatch {
+            flags: flags.len(),
+            claims: claims.len(),
+        });
+    }
+
+    for (index, &flag) in flags.iter().enumerate() {
+        DecodeFlag::try_from(flag)
+            .map_err(|value| InputError::InvalidDecodeFlag { index, value })?;
+    }
+
+    Ok(())
+}
+
+/// Optional Prepare and Show input; `false` proves possession of the key-bound credential
+/// without disclosing a claim.
+///
+/// The claim's shared values are then all zero, so only `KeyBindingX`/`KeyBindingY` carry
+/// information in `comm_W_shared`. The claim still takes its full decoded length, keeping the
+/// circuit shape the keys were set up with. Prepare and Show must both set the flag for their
+/// proofs to link.
+pub const DISCLOSE_CLAIM_FIELD: &str = "discloseClaim";
+
+/// Whether the input discloses a claim; true unless `discloseClaim` is `false`
+pub fn discloses_claim(json_value: &Value) -> bool {
+    json_value
+        .get(DISCLOSE_CLAIM_FIELD)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true)
+}
+
+/// Optional Prepare and Show input disclosing only the bytes `[start, end)` of the decoded
+/// claim, e.g. the year of a full birthdate.
+///
+/// The slice is committed at the start of the claim's shared values and the rest is zero, so
+/// the claim still takes its full decoded length in `comm_W_shared`. Prepare and Show must set
+/// the same range for their proofs to link.
+pub const DISCLOSED_CLAIM_RANGE_FIELD: &str = "disclosedClaimRange";
+
+/// Byte range of the decoded claim the input discloses: its `disclosedClaimRange`, or the
+/// whole `decoded_len` bytes when unset
+pub fn disclosed_claim_range(
+    json_value: &Value,
+    decoded_len: usize,
+) -> Result<Range<usize>, InputError> {
+    let Some(value) = json_value.get(DISCLOSED_CLAIM_RANGE_FIELD) else {
+        return Ok(0..decoded_len);
+    };
+    let bounds = value
+        .as_array()
+        .filter(|bounds| bounds.len() == 2)
+        .and_then(|bounds| {
+            let start = bounds[0].as_u64()? as usize;
+            let end = bounds[1].as_u64()? as usize;
+            Some(start..end)
+        });
+    match bounds {
+        Some(range) if range.start <= range.end && range.end <= decoded_len => Ok(range),
+        _ => Err(invalid_field(
+            DISCLOSED_CLAIM_RANGE_FIELD,
+            format!(
+                "expected [start, end] within the {decoded_len}-byte decoded claim, got {value}"
+            ),
+        )
+        .into()),
+    }
+}
+
+/// Reduce the Show input's `claim` scalars to what it discloses: all zero without
+/// `discloseClaim`, otherwise its `disclosedClaimRange`
+pub fn disclose_show_claim(
+    show_json: &Value,
+    claim_scalars: &mut [Scalar],
+) -> Result<(), InputError> {
+    if !discloses_claim(show_json) {
+        claim_scalars.fill(Scalar::ZERO);
+        return Ok(());
+    }
+    let range = disclosed_claim_range(show_json, claim_scalars.len())?;
+    disclose_claim_range(claim_scalars, range);
+    Ok(())
+}
+
+/// Keep the `range` bytes of a decoded claim, moved to its start, and zero the rest
+fn disclose_claim_range(claim_scalars: &mut [Scalar], range: Range<usize>) {
+    let len = range.len();
+    claim_scalars.copy_within(range, 0);
+    claim_scalars[len..].fill(Scalar::ZERO);
+}
+
+/// Optional Show input holding a verifier-issued challenge. It is not a `show.circom` signal;
+/// `ShowCircuit` exposes it as the circuit's public value so a proof can be bound to one session.
+pub const SHOW_NONCE_FIELD: &str = "nonce";
+
+/// Fields only a Prepare input carries
+const PREPARE_MARKER_FIELDS: &[&str] = &["message", "claims"];
+/// Fields only a Show input carries
+const SHOW_MARKER_FIELDS: &[&str] = &["messageHash", "currentYear"];
+
+/// Circuit an input file is for, as told by `detect_circuit_kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputCircuit {
+    Prepare,
+    Show,
+}
+
+/// Guess which circuit an input is for from the fields it carries: `message`/`claims` for
+/// Prepare, `messageHash`/`currentYear` for Show. `None` when it has both sets or neither.
+///
+/// This only picks a circuit; the input still has to parse for it.
+pub fn detect_circuit_kind(json_value: &Value) -> Option<InputCircuit> {
+    let has_any = |fields: &[&str]| fields.iter().any(|field| json_value.get(field).is_some());
+    match (has_any(PREPARE_MARKER_FIELDS), has_any(SHOW_MARKER_FIELDS)) {
+        (true, false) => Some(InputCircuit::Prepare),
+        (false, true) => Some(InputCircuit::Show),
+        _ => None,
+    }
+}
+
+/// Field definitions of the Show circuit inputs
+const SHOW_FIELD_DEFS: &[(&str, FieldParser)] = &[
+    // BigInt scalar fields (wrapped in vec)
+    ("deviceKeyX", FieldParser::BigIntScalar),
+    ("deviceKeyY", FieldParser::BigIntScalar),
+    ("sig_r", FieldParser::BigIntScalar),
+    ("sig_s_inverse", FieldParser::BigIntScalar),
+    ("messageHash", FieldParser::BigIntScalar),
+    ("claim", FieldParser::BigIntArray),
+    ("currentYear", FieldParser::BigIntScalar),
+    ("currentMonth", FieldParser::BigIntScalar),
+    ("currentDay", FieldParser::BigIntScalar),
+];
+
+/// Parse Show circuit inputs from JSON.
+/// The optional `nonce` is included under `SHOW_NONCE_FIELD` when present.
 pub fn parse_show_inputs(
     json_value: &Value,
 ) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
-    let field_defs: &[(&str, FieldParser)] = &[
-        // BigInt scalar fields (wrapped in vec)
-        ("deviceKeyX", FieldParser::BigIntScalar),
-        ("deviceKeyY", FieldParser::BigIntScalar),
-        ("sig_r", FieldParser::BigIntScalar),
-        ("sig_s_inverse", FieldParser::BigIntScalar),
-        ("messageHash", FieldParser::BigIntScalar),
-        ("claim", FieldParser::BigIntArray),
-        ("currentYear", FieldParser::BigIntScalar),
-        ("currentMonth", FieldParser::BigIntScalar),
-        ("currentDay", FieldParser::BigIntScalar),
-    ];
-
-    parse_inputs(json_value, field_defs)
+    let mut inputs = parse_inputs(json_value, SHOW_FIELD_DEFS)?;
+    if json_value.get(SHOW_NONCE_FIELD).is_some() {
+        let nonce = parse_bigint_scalar(json_value, SHOW_NONCE_FIELD)
+            .map_err(|e| invalid_field(SHOW_NONCE_FIELD, e))?;
+        inputs.insert(SHOW_NONCE_FIELD.to_string(), vec![nonce]);
+    }
+    Ok(inputs)
 }
 
-/// Convert a single BigInt to Scalar
+/// Convert a single BigInt to Scalar.
+///
+/// Both steps are little-endian by definition rather than by host: `to_bytes_le` and the
+/// field's `from_bytes` fix the byte order, so the same input gives the same scalar everywhere.
 pub fn bigint_to_scalar(bigint_val: BigInt) -> Result<Scalar, SynthesisError> {
     let bytes = bigint_val.to_bytes_le().1;
 
@@ -126,6 +780,154 @@ pub struct PrepareSharedScalars {
     pub claim_scalars: Vec<Scalar>,
 }
 
+/// Derive the shared scalars Show commits from its parsed inputs: `deviceKeyX`/`deviceKeyY`
+/// and the `claim` array
+pub fn show_shared_scalars(
+    witness_inputs: &HashMap<String, Vec<BigInt>>,
+) -> Result<PrepareSharedScalars, SynthesisError> {
+    let first_value = |key: &str| {
+        witness_inputs
+            .get(key)
+            .and_then(|values| values.first())
+            .cloned()
+            .ok_or(SynthesisError::AssignmentMissing)
+    };
+    let keybinding_x = bigint_to_scalar(first_value("deviceKeyX")?)?;
+    let keybinding_y = bigint_to_scalar(first_value("deviceKeyY")?)?;
+    let claim_bigints = witness_inputs
+        .get("claim")
+        .cloned()
+        .ok_or(SynthesisError::AssignmentMissing)?;
+    let claim_scalars = convert_bigint_to_scalar(claim_bigints)?;
+
+    Ok(PrepareSharedScalars {
+        keybinding_x,
+        keybinding_y,
+        claim_scalars,
+    })
+}
+
+/// Derive the shared scalars Show commits from its input JSON
+pub fn compute_show_shared_scalars(
+    show_json: &Value,
+) -> Result<PrepareSharedScalars, SynthesisError> {
+    let mut shared = show_shared_scalars(&parse_show_inputs(show_json)?)?;
+    disclose_show_claim(show_json, &mut shared.claim_scalars)?;
+    Ok(shared)
+}
+
+/// Which parts of `comm_W_shared` a Prepare and a Show input agree on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkCheck {
+    pub keybinding_x: bool,
+    pub keybinding_y: bool,
+    pub claims: bool,
+    pub prepare_claim_len: usize,
+    pub show_claim_len: usize,
+}
+
+impl LinkCheck {
+    /// Whether proofs from the two inputs will have matching shared commitments
+    pub fn is_linked(&self) -> bool {
+        self.keybinding_x && self.keybinding_y && self.claims
+    }
+}
+
+/// Compare the shared values a Prepare input and a Show input would commit, without proving
+pub fn check_link(prepare_json: &Value, show_json: &Value) -> Result<LinkCheck, SynthesisError> {
+    let prepare = compute_prepare_shared_scalars(prepare_json)?;
+    let show = compute_show_shared_scalars(show_json)?;
+
+    Ok(LinkCheck {
+        keybinding_x: prepare.keybinding_x == show.keybinding_x,
+        keybinding_y: prepare.keybinding_y == show.keybinding_y,
+        claims: prepare.claim_scalars == show.claim_scalars,
+        prepare_claim_len: prepare.claim_scalars.len(),
+        show_claim_len: show.claim_scalars.len(),
+    })
+}
+
+/// One `.`-separated part of a compact JWT
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtPart {
+    /// Index of the part's first byte in `message`
+    pub offset: usize,
+    /// The part's base64url text, undecoded
+    pub bytes: Vec<u8>,
+}
+
+/// A compact JWT split into its header, payload and signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtParts {
+    pub header: JwtPart,
+    pub payload: JwtPart,
+    /// `None` when the message holds only the signed `header.payload`, as the Prepare input does
+    pub signature: Option<JwtPart>,
+}
+
+/// Split the first `message_length` bytes of `message` into the parts of a compact JWT.
+///
+/// The message ends at the first zero byte (the circuit input is zero-padded) and non-ASCII
+/// bytes are skipped; offsets still refer to positions in `message`. Fails with
+/// `MalformedJwt` unless there are two or three parts.
+pub fn split_jwt(message: &[u8], message_length: usize) -> Result<JwtParts, InputError> {
+    let mut parts = vec![JwtPart {
+        offset: 0,
+        bytes: Vec::new(),
+    }];
+    for (index, &byte) in message
+        .iter()
+        .enumerate()
+        .take(message_length)
+        .take_while(|(_, byte)| **byte != 0)
+        .filter(|(_, byte)| byte.is_ascii())
+    {
+        if byte == b'.' {
+            parts.push(JwtPart {
+                offset: index + 1,
+                bytes: Vec::new(),
+            });
+        } else {
+            parts
+                .last_mut()
+                .expect("parts is never empty")
+                .bytes
+                .push(byte);
+        }
+    }
+
+    let count = parts.len();
+    let mut parts = parts.into_iter();
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(payload), signature, None) => Ok(JwtParts {
+            header,
+            payload,
+            signature,
+        }),
+        _ => Err(InputError::MalformedJwt { parts: count }),
+    }
+}
+
+/// Lay out `jwt_bytes` as the Prepare input's `message` array: exactly `max_len` bytes (the
+/// circuit's `maxMessageLength`), zero-padded after the JWT. Returns the array and the
+/// `messageLength` to put next to it, the JWT's own length, which is where
+/// `compute_prepare_shared_scalars` cuts the message again.
+pub fn build_message_array(
+    jwt_bytes: &[u8],
+    max_len: usize,
+) -> Result<(Vec<u8>, usize), InputError> {
+    if jwt_bytes.len() > max_len {
+        return Err(InputError::MessageLengthOutOfRange {
+            message_length: jwt_bytes.len() as u64,
+            max: max_len,
+        });
+    }
+
+    let mut message = vec![0u8; max_len];
+    message[..jwt_bytes.len()].copy_from_slice(jwt_bytes);
+    Ok((message, jwt_bytes.len()))
+}
+
 pub fn compute_prepare_shared_scalars(
     root_json: &Value,
 ) -> Result<PrepareSharedScalars, SynthesisError> {
@@ -134,42 +936,33 @@ pub fn compute_prepare_shared_scalars(
         .and_then(|value| value.as_u64())
         .ok_or(SynthesisError::AssignmentMissing)? as usize;
 
-    let message_values = root_json
+    let message = root_json
         .get("message")
         .and_then(|value| value.as_array())
-        .ok_or(SynthesisError::AssignmentMissing)?;
-
-    let mut truncated_message = Vec::with_capacity(message_length);
-    for value in message_values.iter().take(message_length) {
-        truncated_message.push(parse_byte(value)?);
-    }
-
-    let jwt_ascii: Vec<u8> = truncated_message
+        .ok_or(SynthesisError::AssignmentMissing)?
         .iter()
-        .take_while(|byte| **byte != 0)
-        .filter(|byte| byte.is_ascii())
-        .copied()
-        .collect();
-
-    let jwt_string = String::from_utf8(jwt_ascii).map_err(|_| SynthesisError::AssignmentMissing)?;
+        .take(message_length)
+        .map(parse_byte)
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let jwt_parts: Vec<&str> = jwt_string.split('.').collect();
-    if jwt_parts.len() < 2 {
-        return Err(SynthesisError::AssignmentMissing);
-    }
-    let payload_b64 = jwt_parts[1];
+    let jwt = split_jwt(&message, message_length)?;
+    let payload_b64 =
+        String::from_utf8(jwt.payload.bytes).map_err(|_| SynthesisError::AssignmentMissing)?;
 
-    let payload_bytes = decode_base64(payload_b64)?;
+    let payload_bytes = decode_base64(&payload_b64)?;
     let payload_json: Value =
         serde_json::from_slice(&payload_bytes).map_err(|_| SynthesisError::AssignmentMissing)?;
 
-    extract_prepare_shared_data(&payload_json, root_json)
+    extract_prepare_shared_data(&payload_json, root_json).map_err(SynthesisError::from)
 }
 
+/// Extract the shared scalars committed by Prepare: the holder's key binding from the payload's
+/// `cnf.jwk` and the decoded age claim, which is all zero when `discloseClaim` is `false`. The
+/// key binding is independent of the issuer key in `pubKeyX`/`pubKeyY`.
 pub fn extract_prepare_shared_data(
     payload_json: &Value,
     root_json: &Value,
-) -> Result<PrepareSharedScalars, SynthesisError> {
+) -> Result<PrepareSharedScalars, InputError> {
     let jwk = payload_json
         .get("cnf")
         .and_then(|value| value.get("jwk"))
@@ -188,6 +981,31 @@ pub fn extract_prepare_shared_data(
     let keybinding_x_bigint = bytes_to_bigint(&decode_base64(keybinding_x_b64)?);
     let keybinding_y_bigint = bytes_to_bigint(&decode_base64(keybinding_y_b64)?);
 
+    let claim_scalars = if discloses_claim(root_json) {
+        extract_disclosed_claim(root_json)?
+    } else {
+        vec![Scalar::ZERO; prepare_claim_scalar_len(root_json)?]
+    };
+
+    let keybinding_x = bigint_to_scalar(keybinding_x_bigint)?;
+    let keybinding_y = bigint_to_scalar(keybinding_y_bigint)?;
+
+    if !is_on_p256_curve(&keybinding_x, &keybinding_y) {
+        return Err(InputError::PointNotOnCurve);
+    }
+
+    Ok(PrepareSharedScalars {
+        keybinding_x,
+        keybinding_y,
+        claim_scalars,
+    })
+}
+
+/// Decode the claim at `ageClaimIndex` with its `claimEncoding`, zero-padded to its
+/// `decodedClaimLengths` entry or else the circuit's decoded length of its claim row, keeping
+/// only the `disclosedClaimRange` bytes when set
+fn extract_disclosed_claim(root_json: &Value) -> Result<Vec<Scalar>, InputError> {
+    let encoding = ClaimEncoding::from_input(root_json)?;
     let age_claim_index = root_json
         .get("ageClaimIndex")
         .and_then(|value| value.as_u64())
@@ -203,67 +1021,510 @@ pub fn extract_prepare_shared_data(
         .and_then(|value| value.as_array())
         .ok_or(SynthesisError::AssignmentMissing)?;
 
-    let claim_bytes = claim_values
-        .iter()
-        .map(parse_byte)
-        .collect::<Result<Vec<_>, _>>()?;
-
     let max_claim_length = claim_values.len();
     if max_claim_length == 0 {
-        return Err(SynthesisError::AssignmentMissing);
+        return Err(SynthesisError::AssignmentMissing.into());
     }
+    let decoded_len = claim_decoded_len(root_json, age_claim_index, max_claim_length)?;
 
     let claim_lengths = root_json
         .get("claimLengths")
         .and_then(|value| value.as_array())
         .ok_or(SynthesisError::AssignmentMissing)?;
 
-    let encoded_claim_len_value = claim_lengths
+    let encoded_claim_len = claim_lengths
         .get(age_claim_index)
-        .ok_or(SynthesisError::AssignmentMissing)?;
+        .ok_or(SynthesisError::AssignmentMissing)
+        .and_then(parse_claim_length)?;
+
+    let decoded_claim_bytes = decode_claim_row(claim_values, encoded_claim_len, encoding)?;
+
+    if decoded_claim_bytes.len() > decoded_len {
+        return Err(InputError::ClaimExceedsDecodedLength {
+            index: age_claim_index,
+            decoded: decoded_claim_bytes.len(),
+            length: decoded_len,
+        });
+    }
 
-    let encoded_claim_len = match encoded_claim_len_value {
+    let mut claim_scalars: Vec<Scalar> = decoded_claim_bytes
+        .into_iter()
+        .map(|byte| Scalar::from(byte as u64))
+        .collect();
+
+    while claim_scalars.len() < decoded_len {
+        claim_scalars.push(Scalar::from(0u64));
+    }
+
+    let range = disclosed_claim_range(root_json, decoded_len)?;
+    disclose_claim_range(&mut claim_scalars, range);
+
+    Ok(claim_scalars)
+}
+
+/// Parse a `claimLengths` entry, given as a number or a decimal string
+fn parse_claim_length(value: &Value) -> Result<usize, SynthesisError> {
+    match value {
         Value::String(s) => s
             .parse::<usize>()
-            .map_err(|_| SynthesisError::AssignmentMissing)?,
+            .map_err(|_| SynthesisError::AssignmentMissing),
         Value::Number(n) => n
             .as_u64()
             .map(|value| value as usize)
-            .ok_or(SynthesisError::AssignmentMissing)?,
-        _ => return Err(SynthesisError::AssignmentMissing),
+            .ok_or(SynthesisError::AssignmentMissing),
+        _ => Err(SynthesisError::AssignmentMissing),
+    }
+}
+
+/// Optional Prepare input giving each claim's decoded length, one entry (or `null`) per claim.
+///
+/// By default a claim decodes into as many bytes as its whole `claims` row can hold. Circuits
+/// with a different cap per claim set the real length here, so a row padded past the claim's
+/// capacity does not change how many scalars are shared.
+pub const DECODED_CLAIM_LENGTHS_FIELD: &str = "decodedClaimLengths";
+
+/// Decoded length of the claim at `index`: its `decodedClaimLengths` entry when set, otherwise
+/// the `decodedLen` that `claim-decoder.circom` gives its `row_length`-byte claim row
+fn claim_decoded_len(
+    json_value: &Value,
+    index: usize,
+    row_length: usize,
+) -> Result<usize, InputError> {
+    let entry = json_value
+        .get(DECODED_CLAIM_LENGTHS_FIELD)
+        .map(|lengths| {
+            lengths.as_array().ok_or_else(|| {
+                invalid_field(DECODED_CLAIM_LENGTHS_FIELD, "expected an array".to_string())
+            })
+        })
+        .transpose()?
+        .and_then(|lengths| lengths.get(index));
+
+    match entry {
+        None | Some(Value::Null) => decoded_claim_len(row_length),
+        Some(value) => value.as_u64().map(|length| length as usize).ok_or_else(|| {
+            invalid_field(
+                DECODED_CLAIM_LENGTHS_FIELD,
+                format!("entry {index} is {value}; expected a length or null"),
+            )
+            .into()
+        }),
+    }
+}
+
+/// Optional Prepare input naming how the disclosed claim (the row at `ageClaimIndex`) is
+/// encoded: `"base64url"` (the default, as in SD-JWT disclosures), `"hex"` or `"raw"`
+pub const CLAIM_ENCODING_FIELD: &str = "claimEncoding";
+
+/// Encoding of a claim row, set for the disclosed claim with `claimEncoding`.
+///
+/// `claim-decoder.circom` decodes base64url only; other encodings change how the shared claim
+/// scalars are extracted, so the Show input's `claim` must carry the bytes they decode to. The
+/// number of shared claim scalars does not depend on the encoding: the decoded claim is always
+/// zero-padded to the circuit's `decodedLen`, so both circuits keep the shape they were set up
+/// with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimEncoding {
+    #[default]
+    Base64Url,
+    Hex,
+    Raw,
+}
+
+impl FromStr for ClaimEncoding {
+    type Err = InputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base64url" => Ok(ClaimEncoding::Base64Url),
+            "hex" => Ok(ClaimEncoding::Hex),
+            "raw" => Ok(ClaimEncoding::Raw),
+            other => Err(InputError::UnknownClaimEncoding(other.to_string())),
+        }
+    }
+}
+
+impl ClaimEncoding {
+    /// Read `claimEncoding` from a Prepare input, defaulting to base64url
+    pub fn from_input(json_value: &Value) -> Result<Self, InputError> {
+        match json_value.get(CLAIM_ENCODING_FIELD) {
+            None => Ok(ClaimEncoding::default()),
+            Some(Value::String(s)) => s.parse(),
+            Some(other) => Err(InputError::UnknownClaimEncoding(other.to_string())),
+        }
+    }
+
+    /// Decode the encoded bytes of a claim
+    pub fn decode(self, encoded: &[u8]) -> Result<Vec<u8>, InputError> {
+        match self {
+            ClaimEncoding::Base64Url => {
+                let encoded = String::from_utf8(encoded.to_vec())
+                    .map_err(|_| SynthesisError::AssignmentMissing)?;
+                decode_base64url_strict(&encoded)
+            }
+            ClaimEncoding::Hex => {
+                hex::decode(encoded).map_err(|e| InputError::InvalidHexClaim(e.to_string()))
+            }
+            ClaimEncoding::Raw => Ok(encoded.to_vec()),
+        }
+    }
+}
+
+/// Decode the first `encoded_len` bytes of a `claims` row
+fn decode_claim_row(
+    claim_values: &[Value],
+    encoded_len: usize,
+    encoding: ClaimEncoding,
+) -> Result<Vec<u8>, InputError> {
+    let claim_bytes = claim_values
+        .iter()
+        .map(parse_byte)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if encoded_len > claim_bytes.len() {
+        return Err(SynthesisError::AssignmentMissing.into());
+    }
+
+    encoding.decode(&claim_bytes[..encoded_len])
+}
+
+/// Number of decoded bytes kept in `ClaimInfo::prefix`
+const CLAIM_PREFIX_LEN: usize = 32;
+
+/// A claim in a Prepare input's `claims` array, as listed by `list_jwt_claims`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimInfo {
+    /// Position in `claims`, as used by `ageClaimIndex`
+    pub index: usize,
+    /// Encoded length declared in `claimLengths`
+    pub length: usize,
+    /// Claim name, when the claim decodes to an SD-JWT disclosure `[salt, name, value]`
+    pub name: Option<String>,
+    /// Start of the decoded claim, lossily converted to UTF-8
+    pub prefix: String,
+}
+
+/// List the claims of a Prepare input in order, decoding each one so a caller can choose
+/// `ageClaimIndex` without decoding the `claims` rows by hand.
+pub fn list_jwt_claims(json_value: &Value) -> Result<Vec<ClaimInfo>, InputError> {
+    let claims = json_value
+        .get("claims")
+        .and_then(|value| value.as_array())
+        .ok_or(SynthesisError::AssignmentMissing)?;
+    let claim_lengths = json_value
+        .get("claimLengths")
+        .and_then(|value| value.as_array())
+        .ok_or(SynthesisError::AssignmentMissing)?;
+    if claims.len() != claim_lengths.len() {
+        return Err(InputError::ClaimLengthsCountMismatch {
+            claims: claims.len(),
+            claim_lengths: claim_lengths.len(),
+        });
+    }
+    // `claimEncoding` applies to the disclosed claim; the others are SD-JWT disclosures
+    let disclosed_index = json_value
+        .get("ageClaimIndex")
+        .and_then(|value| value.as_u64());
+    let disclosed_encoding = ClaimEncoding::from_input(json_value)?;
+
+    claims
+        .iter()
+        .zip(claim_lengths)
+        .enumerate()
+        .map(
+            |(index, (claim, length))| -> Result<ClaimInfo, InputError> {
+                let claim_values = claim.as_array().ok_or(SynthesisError::AssignmentMissing)?;
+                let length = parse_claim_length(length)?;
+                let encoding = if disclosed_index == Some(index as u64) {
+                    disclosed_encoding
+                } else {
+                    ClaimEncoding::Base64Url
+                };
+                let decoded = decode_claim_row(claim_values, length, encoding)?;
+
+                let name = serde_json::from_slice::<Vec<Value>>(&decoded)
+                    .ok()
+                    .and_then(|disclosure| disclosure.get(1)?.as_str().map(str::to_string));
+                let prefix_len = decoded.len().min(CLAIM_PREFIX_LEN);
+                let prefix = String::from_utf8_lossy(&decoded[..prefix_len]).into_owned();
+
+                Ok(ClaimInfo {
+                    index,
+                    length,
+                    name,
+                    prefix,
+                })
+            },
+        )
+        .collect()
+}
+
+/// Parse disclosed claim bytes into their key and value.
+///
+/// The claim is usually a bare JSON member as it appears in the payload,
+/// `"birthdate":"1990-01-01"`, possibly with its trailing comma or the enclosing braces. An
+/// SD-JWT disclosure `[salt, name, value]` gives its name and value. Zero padding after the
+/// claim is ignored.
+pub fn parse_disclosed_claim(bytes: &[u8]) -> Result<(String, Value), InputError> {
+    let end = bytes
+        .iter()
+        .rposition(|&byte| byte != 0)
+        .map_or(0, |last| last + 1);
+    let text = std::str::from_utf8(&bytes[..end])
+        .map_err(|e| InputError::MalformedClaim(e.to_string()))?
+        .trim();
+
+    if text.starts_with('[') {
+        let disclosure: Vec<Value> =
+            serde_json::from_str(text).map_err(|e| InputError::MalformedClaim(e.to_string()))?;
+        return match <[Value; 3]>::try_from(disclosure) {
+            Ok([_salt, Value::String(name), value]) => Ok((name, value)),
+            _ => Err(InputError::MalformedClaim(
+                "SD-JWT disclosure is not [salt, name, value]".into(),
+            )),
+        };
+    }
+
+    let member = text.strip_suffix(',').unwrap_or(text);
+    let object = if member.starts_with('{') {
+        member.to_string()
+    } else {
+        format!("{{{member}}}")
     };
+    let fields: Map<String, Value> =
+        serde_json::from_str(&object).map_err(|e| InputError::MalformedClaim(e.to_string()))?;
+    if fields.len() != 1 {
+        return Err(InputError::MalformedClaim(format!(
+            "expected one member, found {}",
+            fields.len()
+        )));
+    }
+    Ok(fields.into_iter().next().expect("one member"))
+}
 
-    if encoded_claim_len > claim_bytes.len() {
-        return Err(SynthesisError::AssignmentMissing);
+/// Decoded length of a claim row of `max_claim_length` base64url characters, matching
+/// `(maxClaimsLength * 3) / 4` in `claim-decoder.circom`. Circom's `/` is field division, so the
+/// circuit only exists for multiples of 4; other lengths are rejected rather than rounded down.
+pub fn decoded_claim_len(max_claim_length: usize) -> Result<usize, InputError> {
+    if max_claim_length % 4 != 0 {
+        return Err(InputError::UnalignedClaimLength {
+            length: max_claim_length,
+        });
     }
+    Ok(max_claim_length / 4 * 3)
+}
 
-    let encoded_claim = String::from_utf8(claim_bytes[..encoded_claim_len].to_vec())
-        .map_err(|_| SynthesisError::AssignmentMissing)?;
+/// Number of claim scalars Prepare commits: the decoded length of the age claim, to which
+/// `extract_prepare_shared_data` zero-pads the decoded claim, with or without disclosure
+fn prepare_claim_scalar_len(prepare_json: &Value) -> Result<usize, InputError> {
+    let age_claim_index = prepare_json
+        .get("ageClaimIndex")
+        .and_then(|value| value.as_u64())
+        .ok_or(SynthesisError::AssignmentMissing)? as usize;
+
+    let max_claim_length = prepare_json
+        .get("claims")
+        .and_then(|value| value.as_array())
+        .and_then(|claims| claims.get(age_claim_index))
+        .and_then(|value| value.as_array())
+        .ok_or(SynthesisError::AssignmentMissing)?
+        .len();
 
-    let decoded_claim_bytes = decode_base64(&encoded_claim)?;
-    let decoded_len = (max_claim_length * 3) / 4;
+    claim_decoded_len(prepare_json, age_claim_index, max_claim_length)
+}
 
-    if decoded_claim_bytes.len() > decoded_len {
-        return Err(SynthesisError::AssignmentMissing);
+/// Pre-flight check that the Prepare and Show inputs commit the same number of claim scalars.
+///
+/// Both proofs succeed on their own when these differ, but their `comm_W_shared` cannot match.
+pub fn assert_prepare_show_claim_lengths_match(
+    prepare_json: &Value,
+    show_json: &Value,
+) -> Result<(), InputError> {
+    let prepare = prepare_claim_scalar_len(prepare_json)?;
+    let show = show_json
+        .get("claim")
+        .and_then(|value| value.as_array())
+        .ok_or(SynthesisError::AssignmentMissing)?
+        .len();
+
+    if prepare != show {
+        return Err(InputError::ClaimLengthMismatch { prepare, show });
     }
+    Ok(())
+}
 
-    let mut claim_scalars: Vec<Scalar> = decoded_claim_bytes
-        .into_iter()
-        .map(|byte| Scalar::from(byte as u64))
-        .collect();
+/// A decimal P-256 curve constant as a `Scalar`
+fn p256_constant(decimal: &str) -> Scalar {
+    BigInt::from_str(decimal)
+        .ok()
+        .and_then(|value| bigint_to_scalar(value).ok())
+        .expect("P-256 curve constants fit in the scalar field")
+}
 
-    while claim_scalars.len() < decoded_len {
-        claim_scalars.push(Scalar::from(0u64));
+/// Check that `(x, y)` satisfies the P-256 curve equation `y^2 = x^3 - 3x + b`
+pub fn is_on_p256_curve(x: &Scalar, y: &Scalar) -> bool {
+    let b = p256_constant(P256_B);
+
+    let lhs = y.square();
+    let rhs = x.square() * x - (x.double() + x) + b;
+    lhs == rhs
+}
+
+/// Compute the Show input `messageHash` for `message`, the bytes the device key signed (the
+/// verifier's nonce in `show.ts`): SHA-256 read as a big-endian integer and reduced modulo the
+/// P-256 group order, the value the circuit's ECDSA check takes as `m`
+pub fn compute_message_hash(message: &[u8]) -> Scalar {
+    let order = BigInt::from_str(P256_N).expect("P-256 group order parses");
+    let hash = bytes_to_bigint(&Sha256::digest(message)) % order;
+    bigint_to_scalar(hash).expect("a value below the P-256 group order fits in the scalar field")
+}
+
+/// Affine P-256 point with coordinates in `Scalar`, the P-256 base field; `None` is the point
+/// at infinity
+type P256Point = Option<(Scalar, Scalar)>;
+
+fn p256_add(a: P256Point, b: P256Point) -> P256Point {
+    let ((x1, y1), (x2, y2)) = match (a, b) {
+        (None, point) | (point, None) => return point,
+        (Some(a), Some(b)) => (a, b),
+    };
+
+    let lambda = if x1 == x2 {
+        if y1 != y2 || y1.is_zero_vartime() {
+            return None;
+        }
+        // Tangent slope (3x^2 + a) / 2y with a = -3
+        let three = Scalar::from(3u64);
+        (x1.square() * three - three) * y1.double().invert().unwrap()
+    } else {
+        (y2 - y1) * (x2 - x1).invert().unwrap()
+    };
+
+    let x3 = lambda.square() - x1 - x2;
+    let y3 = lambda * (x1 - x3) - y1;
+    Some((x3, y3))
+}
+
+/// Double-and-add multiplication by a non-negative `k`
+fn p256_mul(point: P256Point, k: &BigInt) -> P256Point {
+    let mut acc = None;
+    for byte in k.to_bytes_be().1 {
+        for bit in (0..8).rev() {
+            acc = p256_add(acc, acc);
+            if (byte >> bit) & 1 == 1 {
+                acc = p256_add(acc, point);
+            }
+        }
     }
+    acc
+}
 
-    let keybinding_x = bigint_to_scalar(keybinding_x_bigint)?;
-    let keybinding_y = bigint_to_scalar(keybinding_y_bigint)?;
+fn scalar_to_bigint(scalar: &Scalar) -> BigInt {
+    let mut bytes = scalar.to_repr().as_ref().to_vec();
+    bytes.reverse();
+    bytes_to_bigint(&bytes)
+}
 
-    Ok(PrepareSharedScalars {
-        keybinding_x,
-        keybinding_y,
-        claim_scalars,
-    })
+/// Canonical big-endian hex of `scalar`, `0x`-prefixed and zero-padded to the field size
+pub fn scalar_to_hex(scalar: &Scalar) -> String {
+    let mut bytes = scalar.to_repr().as_ref().to_vec();
+    bytes.reverse();
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// `scalar` as canonical big-endian hex followed by its decimal value, for comparing scalars
+/// in diagnostics; `{:?}` prints the internal Montgomery form instead
+pub fn format_scalar(scalar: &Scalar) -> String {
+    format!("{} ({})", scalar_to_hex(scalar), scalar_to_bigint(scalar))
+}
+
+/// Check the ECDSA signature `(r, s_inverse)` over `message_hash` against the P-256 key
+/// `(pub_x, pub_y)`, as the circuits' `ECDSA` template does, without proving.
+///
+/// Takes the values as they appear in the circuit inputs: `s_inverse` is `s^-1 mod n` and
+/// `message_hash` is the reduced hash from `compute_message_hash`. An invalid signature makes
+/// the witness unsatisfiable, so checking first avoids a doomed prove.
+pub fn verify_ecdsa_offcircuit(
+    pub_x: &BigInt,
+    pub_y: &BigInt,
+    r: &BigInt,
+    s_inverse: &BigInt,
+    message_hash: &BigInt,
+) -> bool {
+    let order = BigInt::from_str(P256_N).expect("P-256 group order parses");
+    let zero = BigInt::from(0u8);
+    if *r <= zero || *r >= order || *s_inverse <= zero || *s_inverse >= order {
+        return false;
+    }
+    if *message_hash < zero {
+        return false;
+    }
+
+    let (Ok(x), Ok(y)) = (
+        bigint_to_scalar(pub_x.clone()),
+        bigint_to_scalar(pub_y.clone()),
+    ) else {
+        return false;
+    };
+    if !is_on_p256_curve(&x, &y) {
+        return false;
+    }
+
+    let generator = Some((p256_constant(P256_GX), p256_constant(P256_GY)));
+
+    let u1 = message_hash * s_inverse % &order;
+    let u2 = r * s_inverse % &order;
+    match p256_add(p256_mul(generator, &u1), p256_mul(Some((x, y)), &u2)) {
+        Some((rx, _)) => scalar_to_bigint(&rx) % &order == *r,
+        None => false,
+    }
+}
+
+/// Check off-circuit that the issuer key `(pub_x, pub_y)`, the Prepare input's
+/// `pubKeyX`/`pubKeyY`, signed the JWT in the first `message_length` bytes of `message`.
+///
+/// The message must hold the whole compact JWT, including its base64url ES256 signature
+/// (`r || s`, 32 bytes each), which is checked over the `header.payload` bytes. This is
+/// separate from the key binding in `cnf.jwk`, the holder's device key. A tampered JWT fails
+/// here with a clear error instead of as an unsatisfiable witness.
+pub fn validate_jwt_issuer_signature(
+    message: &[u8],
+    message_length: usize,
+    pub_x: &BigInt,
+    pub_y: &BigInt,
+) -> Result<(), InputError> {
+    let jwt = split_jwt(message, message_length)?;
+    let signature = jwt.signature.ok_or_else(|| {
+        InputError::MalformedJwtSignature("the message ends after the payload".to_string())
+    })?;
+    let signature = decode_base64url_strict(&String::from_utf8_lossy(&signature.bytes))
+        .map_err(|e| InputError::MalformedJwtSignature(e.to_string()))?;
+    if signature.len() != 64 {
+        return Err(InputError::MalformedJwtSignature(format!(
+            "{} bytes; an ES256 signature is 64",
+            signature.len()
+        )));
+    }
+    let (r, s) = signature.split_at(32);
+    let (r, s) = (bytes_to_bigint(r), bytes_to_bigint(s));
+
+    let order = BigInt::from_str(P256_N).expect("P-256 group order parses");
+    if s <= BigInt::from(0u8) || s >= order {
+        return Err(InputError::JwtSignatureMismatch);
+    }
+    // The group order is prime, so s^(n-2) is s^-1 mod n
+    let s_inverse = s.modpow(&(&order - BigInt::from(2u8)), &order);
+
+    let mut signing_input = jwt.header.bytes;
+    signing_input.push(b'.');
+    signing_input.extend_from_slice(&jwt.payload.bytes);
+    let message_hash = scalar_to_bigint(&compute_message_hash(&signing_input));
+
+    if verify_ecdsa_offcircuit(pub_x, pub_y, &r, &s_inverse, &message_hash) {
+        Ok(())
+    } else {
+        Err(InputError::JwtSignatureMismatch)
+    }
 }
 
 pub fn parse_byte(value: &Value) -> Result<u8, SynthesisError> {
@@ -315,14 +1576,41 @@ pub fn decode_base64(encoded: &str) -> Result<Vec<u8>, SynthesisError> {
     Err(SynthesisError::AssignmentMissing)
 }
 
+/// URL-safe alphabet only, with or without trailing `=` padding
+const BASE64URL_STRICT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Decode base64url as used for JWT claims, rejecting anything outside the URL-safe alphabet.
+///
+/// Unlike `decode_base64`, this never falls back to the STANDARD alphabet, so a claim cannot
+/// decode to different bytes than the circuit would commit to.
+pub fn decode_base64url_strict(encoded: &str) -> Result<Vec<u8>, InputError> {
+    BASE64URL_STRICT
+        .decode(encoded.as_bytes())
+        .map_err(|e| InputError::InvalidBase64Url(e.to_string()))
+}
+
 // JSON Parsing Helpers
-/// Parse a single BigInt from a string field
+/// Parse a BigInt from a decimal string, or from a JSON number that fits in a u64.
+/// `serde_json` reads larger numbers as `f64` and loses digits, so those must be strings.
+fn parse_bigint_value(value: &Value) -> Result<BigInt, String> {
+    match value {
+        Value::String(s) => {
+            BigInt::from_str(s).map_err(|_| format!("Failed to parse '{s}' as BigInt"))
+        }
+        Value::Number(n) => n
+            .as_u64()
+            .map(BigInt::from)
+            .ok_or_else(|| format!("{n} is not a u64; write larger integers as decimal strings")),
+        _ => Err("Value must be a decimal string or a number".to_string()),
+    }
+}
+
+/// Parse a single BigInt from a string or number field
 fn parse_bigint_scalar(json: &Value, key: &str) -> Result<BigInt, String> {
-    let s = json
-        .get(key)
-        .and_then(|v| v.as_str())
-        .ok_or("Field must be a string")?;
-    BigInt::from_str(s).map_err(|_| "Failed to parse as BigInt".to_string())
+    parse_bigint_value(json.get(key).ok_or("Field is missing")?)
 }
 
 /// Parse a single u64 from a number field and convert to BigInt
@@ -333,20 +1621,14 @@ fn parse_u64_scalar(json: &Value, key: &str) -> Result<BigInt, String> {
         .ok_or("Field must be a number".to_string())
 }
 
-/// Parse an array of BigInt strings
+/// Parse an array of BigInt strings or numbers
 fn parse_bigint_string_array(json: &Value, key: &str) -> Result<Vec<BigInt>, String> {
     let array = json
         .get(key)
         .and_then(|v| v.as_array())
         .ok_or("Field must be an array")?;
 
-    array
-        .iter()
-        .map(|v| {
-            let s = v.as_str().ok_or("Array element must be a string")?;
-            BigInt::from_str(s).map_err(|_| "Failed to parse array element as BigInt".to_string())
-        })
-        .collect()
+    array.iter().map(parse_bigint_value).collect()
 }
 
 /// Parse an array of u64 numbers and convert to BigInt
@@ -363,36 +1645,32 @@ fn parse_u64_array(json: &Value, key: &str) -> Result<Vec<BigInt>, String> {
         .collect()
 }
 
-/// Parse a 2D array of BigInt strings and flatten into 1D vector
+/// Element count above which `parse_2d_bigint_array` splits the parsing across all cores
+pub const PARALLEL_PARSE_THRESHOLD: usize = 4096;
+
+/// Parse a 2D array of BigInt strings or numbers and flatten into 1D vector.
+///
+/// Arrays of more than `PARALLEL_PARSE_THRESHOLD` elements are parsed on the rayon pool and
+/// kept in order. An invalid element fails the parse, though with several it is not
+/// necessarily the first one that is reported.
 fn parse_2d_bigint_array(json: &Value, key: &str) -> Result<Vec<BigInt>, String> {
     let outer_array = json
         .get(key)
         .and_then(|v| v.as_array())
         .ok_or("Field must be an array")?;
 
-    // Pre-calculate total capacity
-    let total_capacity: usize = outer_array
-        .iter()
-        .filter_map(|v| v.as_array())
-        .map(|arr| arr.len())
-        .sum();
-
-    let mut result = Vec::with_capacity(total_capacity);
-
+    let mut values = Vec::new();
     for inner_value in outer_array.iter() {
         let inner_array = inner_value
             .as_array()
             .ok_or("Outer array element must be an array")?;
-
-        for v in inner_array.iter() {
-            let s = v.as_str().ok_or("Inner array element must be a string")?;
-            let bigint =
-                BigInt::from_str(s).map_err(|_| "Failed to parse inner array element as BigInt")?;
-            result.push(bigint);
-        }
+        values.extend(inner_array.iter());
     }
 
-    Ok(result)
+    if values.len() <= PARALLEL_PARSE_THRESHOLD {
+        return values.into_iter().map(parse_bigint_value).collect();
+    }
+    values.into_par_iter().map(parse_bigint_value).collect()
 }
 
 fn bytes_to_bigint(bytes: &[u8]) -> BigInt {
@@ -408,6 +1686,10 @@ fn bytes_to_bigint(bytes: &[u8]) -> BigInt {
 pub struct JwtOutputLayout {
     pub age_claim_start: usize,
     pub age_claim_len: usize,
+    /// Offset of the disclosed bytes within the age claim; 0 unless a sub-range is disclosed
+    pub disclosed_start: usize,
+    /// Number of disclosed bytes; `age_claim_len` unless a sub-range is disclosed
+    pub disclosed_len: usize,
     pub keybinding_x_index: usize,
     pub keybinding_y_index: usize,
 }
@@ -416,6 +1698,179 @@ impl JwtOutputLayout {
     pub fn age_claim_range(&self) -> Range<usize> {
         self.age_claim_start..self.age_claim_start + self.age_claim_len
     }
+
+    /// Witness indices of the age claim bytes that are disclosed
+    pub fn disclosed_claim_range(&self) -> Range<usize> {
+        let start = self.age_claim_start + self.disclosed_start;
+        start..start + self.disclosed_len
+    }
+
+    /// Disclose only the bytes `range` of the age claim, as set by `disclosedClaimRange`
+    pub fn with_disclosed_range(mut self, range: Range<usize>) -> Result<Self, InputError> {
+        if range.start > range.end || range.end > self.age_claim_len {
+            return Err(invalid_field(
+                DISCLOSED_CLAIM_RANGE_FIELD,
+                format!(
+                    "{range:?} is outside the {}-byte decoded claim",
+                    self.age_claim_len
+                ),
+            )
+            .into());
+        }
+        self.disclosed_start = range.start;
+        self.disclosed_len = range.len();
+        Ok(self)
+    }
+}
+
+/// What a Prepare proof reveals: the holder's key binding and the disclosed claim bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisclosedData {
+    pub keybinding_x: Scalar,
+    pub keybinding_y: Scalar,
+    /// Disclosed claim bytes, without the zero padding after the claim
+    pub claim: Vec<u8>,
+}
+
+/// Read the key binding and disclosed claim bytes from a Prepare witness (the circom wires,
+/// as `generate_prepare_witness` returns them) at the positions given by `layout`
+pub fn extract_jwt_outputs(
+    witness: &[Scalar],
+    layout: &JwtOutputLayout,
+) -> Result<DisclosedData, InputError> {
+    let wire = |index: usize| {
+        witness
+            .get(index)
+            .copied()
+            .ok_or(SynthesisError::AssignmentMissing)
+    };
+
+    let mut claim = layout
+        .disclosed_claim_range()
+        .map(|index| {
+            let value = wire(index)?;
+            let repr = value.to_repr();
+            let (byte, high) = repr.as_ref().split_first().expect("scalars are not empty");
+            if high.iter().any(|&b| b != 0) {
+                return Err(InputError::ClaimOutputNotAByte { index });
+            }
+            Ok(*byte)
+        })
+        .collect::<Result<Vec<u8>, InputError>>()?;
+    let end = claim
+        .iter()
+        .rposition(|&b| b != 0)
+        .map_or(0, |last| last + 1);
+    claim.truncate(end);
+
+    Ok(DisclosedData {
+        keybinding_x: wire(layout.keybinding_x_index)?,
+        keybinding_y: wire(layout.keybinding_y_index)?,
+        claim,
+    })
+}
+
+/// Rebuild the part of the JWT payload a proof disclosed, for an audit record: `cnf.jwk`
+/// with the base64url key-binding coordinates, and the claim as a payload member when
+/// `parse_disclosed_claim` can read it, otherwise as a `disclosedClaim` string
+pub fn reconstruct_disclosure(outputs: &DisclosedData) -> Value {
+    let coordinate = |scalar: &Scalar| {
+        let mut bytes = scalar.to_repr().as_ref().to_vec();
+        bytes.reverse();
+        URL_SAFE_NO_PAD.encode(bytes)
+    };
+
+    let mut payload = Map::new();
+    payload.insert(
+        "cnf".to_string(),
+        json!({
+            "jwk": {
+                "kty": "EC",
+                "crv": "P-256",
+                "x": coordinate(&outputs.keybinding_x),
+                "y": coordinate(&outputs.keybinding_y),
+            }
+        }),
+    );
+    match parse_disclosed_claim(&outputs.claim) {
+        Ok((key, value)) if key != "cnf" => {
+            payload.insert(key, value);
+        }
+        _ if outputs.claim.is_empty() => {}
+        _ => {
+            payload.insert(
+                "disclosedClaim".to_string(),
+                Value::String(String::from_utf8_lossy(&outputs.claim).into_owned()),
+            );
+        }
+    }
+    Value::Object(payload)
+}
+
+/// Position of each disclosed claim in the shared values both circuits commit to
+/// `comm_W_shared`.
+///
+/// The shared values are `KeyBindingX` at index 0, `KeyBindingY` at index 1, then the decoded
+/// scalars of each disclosed claim, contiguously and in disclosure order. A claim of length 0
+/// takes no shared values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiClaimLayout {
+    claims: Vec<Range<usize>>,
+}
+
+impl MultiClaimLayout {
+    pub const KEYBINDING_X_INDEX: usize = 0;
+    pub const KEYBINDING_Y_INDEX: usize = 1;
+    /// Index of the first claim scalar
+    pub const CLAIMS_START: usize = 2;
+
+    /// Lay out claims of the given decoded lengths, in order
+    pub fn new(claim_lens: &[usize]) -> Self {
+        let mut start = Self::CLAIMS_START;
+        let claims = claim_lens
+            .iter()
+            .map(|&len| {
+                let range = start..start + len;
+                start = range.end;
+                range
+            })
+            .collect();
+        MultiClaimLayout { claims }
+    }
+
+    /// Shared-value indices of claim `claim`, if it exists
+    pub fn claim_range(&self, claim: usize) -> Option<Range<usize>> {
+        self.claims.get(claim).cloned()
+    }
+
+    pub fn claim_ranges(&self) -> &[Range<usize>] {
+        &self.claims
+    }
+
+    /// Total number of shared values, key binding included
+    pub fn num_shared(&self) -> usize {
+        self.claims
+            .last()
+            .map_or(Self::CLAIMS_START, |range| range.end)
+    }
+}
+
+/// Pack the key binding and the disclosed claims into the shared values laid out by
+/// `MultiClaimLayout`
+pub fn pack_shared_scalars(
+    keybinding_x: Scalar,
+    keybinding_y: Scalar,
+    claims: &[&[Scalar]],
+) -> (SecretScalars, MultiClaimLayout) {
+    let layout = MultiClaimLayout::new(&claims.iter().map(|claim| claim.len()).collect::<Vec<_>>());
+
+    let mut packed = Vec::with_capacity(layout.num_shared());
+    packed.push(keybinding_x);
+    packed.push(keybinding_y);
+    for claim in claims {
+        packed.extend_from_slice(claim);
+    }
+    (SecretScalars::from(packed), layout)
 }
 
 /// Calculate output signal indices for JWT circuit based on circuit parameters.
@@ -426,19 +1881,26 @@ impl JwtOutputLayout {
 /// 3. `KeyBindingY`
 ///
 /// Parameters: `[maxMessageLength, maxB64PayloadLength, maxMatches, maxSubstringLength, maxClaimsLength]`
+///
+/// A circuit compiled with `maxClaimsLength = 0` has no `ageClaim` outputs; its layout has
+/// `age_claim_len == 0` and the key binding directly after the constant signal. A
+/// `max_claims_length` that is not a multiple of 4 fails with `UnalignedClaimLength`, as in
+/// `decoded_claim_len`.
 pub fn calculate_jwt_output_indices(
     _max_matches: usize,
     max_claims_length: usize,
-) -> JwtOutputLayout {
-    let decoded_len = (max_claims_length * 3) / 4;
+) -> Result<JwtOutputLayout, InputError> {
+    let decoded_len = decoded_claim_len(max_claims_length)?;
     let age_claim_start = 1; // Index 0 is reserved for the constant signal in Circom witness
     let keybinding_x_index = age_claim_start + decoded_len;
     let keybinding_y_index = keybinding_x_index + 1;
 
-    JwtOutputLayout {
+    Ok(JwtOutputLayout {
         age_claim_start,
         age_claim_len: decoded_len,
+        disclosed_start: 0,
+        disclosed_len: decoded_len,
         keybinding_x_index,
         keybinding_y_index,
-    }
+    })
 }
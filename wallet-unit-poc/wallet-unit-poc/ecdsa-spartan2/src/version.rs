@@ -0,0 +1,107 @@
+//! One version tag for a compiled circuit, checked before its keys and proofs are used.
+//!
+//! Keys, instances and proofs are only valid for the circuit they were made with, but a
+//! recompiled circom circuit, a rebuilt witness generator and regenerated keys are often
+//! distributed separately. `CircuitVersion` combines the SHA-256 of the circuit's R1CS with
+//! `CIRCOM_ARTIFACTS_SHA256`, which `build.rs` computes over every compiled circom artifact
+//! the binary was built from. Artifacts are stamped with it in `manifest.json` when saved and
+//! checked against it before they are loaded, with `record_circuit_version` and
+//! `check_circuit_version`. The `load_*` functions check the part they can without the R1CS,
+//! that a stamped artifact was made from the circom build this binary runs, with
+//! `check_build_version`.
+
+use std::{fmt, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    errors::{ArtifactError, L8zkError},
+    manifest::{load_manifest, manifest_path_for},
+};
+
+/// SHA-256 over the `.r1cs` and `.wasm` files of the circom build the binary was built from
+pub const CIRCOM_ARTIFACTS_SHA256: &str = env!("L8ZK_CIRCOM_ARTIFACTS_SHA256");
+
+/// Version of a compiled circuit: its R1CS and the circom artifacts built into the binary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitVersion {
+    /// Hex-encoded SHA-256 of the circuit's `.r1cs` file
+    pub r1cs_sha256: String,
+    /// `CIRCOM_ARTIFACTS_SHA256` of the binary that stamped the artifact
+    pub artifacts_sha256: String,
+}
+
+impl CircuitVersion {
+    /// The version of the circuit compiled to `r1cs_path`, as run by this binary
+    pub fn from_r1cs(r1cs_path: &Path) -> Result<Self, L8zkError> {
+        Ok(Self {
+            r1cs_sha256: hex::encode(Sha256::digest(fs::read(r1cs_path)?)),
+            artifacts_sha256: CIRCOM_ARTIFACTS_SHA256.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for CircuitVersion {
+    /// Short form for messages: the first 12 hex digits of each hash
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let short = |hash: &str| hash.get(..12).unwrap_or(hash).to_string();
+        write!(
+            f,
+            "r1cs {}, artifacts {}",
+            short(&self.r1cs_sha256),
+            short(&self.artifacts_sha256)
+        )
+    }
+}
+
+/// The version the manifest records for `artifact_path`, or `None` when it is not in the
+/// manifest or was saved without a version
+pub fn recorded_circuit_version(artifact_path: &str) -> Result<Option<CircuitVersion>, L8zkError> {
+    let manifest = load_manifest(&manifest_path_for(artifact_path))?;
+    Ok(manifest
+        .artifacts
+        .into_iter()
+        .find(|entry| entry.path == artifact_path)
+        .and_then(|entry| entry.circuit_version))
+}
+
+/// Check the version the manifest records for `artifact_path` against `expected`.
+///
+/// Artifacts that are not in the manifest, or were saved without a version, pass: they were
+/// written before versions were recorded or by code that does not stamp them.
+pub fn check_circuit_version(
+    artifact_path: &str,
+    expected: &CircuitVersion,
+) -> Result<(), L8zkError> {
+    match recorded_circuit_version(artifact_path)? {
+        Some(found) if found != *expected => Err(ArtifactError::CircuitVersionMismatch {
+            path: artifact_path.to_string(),
+            found: found.to_string(),
+            expected: expected.to_string(),
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Check that a stamped `artifact_path` was made from the circom build this binary runs,
+/// `CIRCOM_ARTIFACTS_SHA256`. Unlike `check_circuit_version` this needs no R1CS, so every
+/// `load_*` function runs it. Unstamped artifacts pass.
+pub fn check_build_version(artifact_path: &str) -> Result<(), L8zkError> {
+    match recorded_circuit_version(artifact_path)? {
+        Some(found) if found.artifacts_sha256 != CIRCOM_ARTIFACTS_SHA256 => {
+            let expected = CircuitVersion {
+                r1cs_sha256: found.r1cs_sha256.clone(),
+                artifacts_sha256: CIRCOM_ARTIFACTS_SHA256.to_string(),
+            };
+            Err(ArtifactError::CircuitVersionMismatch {
+                path: artifact_path.to_string(),
+                found: found.to_string(),
+                expected: expected.to_string(),
+            }
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
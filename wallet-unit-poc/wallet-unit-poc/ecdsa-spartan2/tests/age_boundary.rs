@@ -0,0 +1,25 @@
+//! `find_age_boundary` sweeps `currentDate` over the default Show input, whose claim is the
+//! ROC birthday 104-06-05 (2015-06-05), and finds where `ageAbove18` flips to 1.
+
+use ecdsa_spartan2::find_age_boundary;
+use serde_json::Value;
+use std::fs;
+
+const SHOW_INPUT: &str = "../circom/inputs/show/default.json";
+
+fn show_input() -> Value {
+    serde_json::from_str(&fs::read_to_string(SHOW_INPUT).unwrap()).unwrap()
+}
+
+#[test]
+fn flips_on_the_nineteenth_birthday() {
+    // `ageAbove18` is `age > 18`, so the holder qualifies on turning 19
+    let boundary = find_age_boundary(&show_input(), (2034, 5, 30)..=(2034, 6, 10)).unwrap();
+    assert_eq!(boundary, Some((2034, 6, 5)));
+}
+
+#[test]
+fn none_when_never_satisfied() {
+    let boundary = find_age_boundary(&show_input(), (2034, 6, 1)..=(2034, 6, 4)).unwrap();
+    assert_eq!(boundary, None);
+}
@@ -0,0 +1,120 @@
+//! `check_circuit_version` rejects an artifact whose manifest entry records another
+//! `CircuitVersion`, and lets through artifacts that were never stamped.
+//! The `load_*` functions reject stamped artifacts made from another circom build.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ecdsa_spartan2::{
+    check_circuit_version, load_proving_key, manifest::record_artifact, record_circuit_version,
+    ArtifactError, ArtifactKind, CircuitVersion, L8zkError, CIRCOM_ARTIFACTS_SHA256,
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The version of a stand-in R1CS file holding `contents`
+fn version(dir: &Path, contents: &[u8]) -> CircuitVersion {
+    let r1cs = dir.join("circuit.r1cs");
+    fs::write(&r1cs, contents).unwrap();
+    CircuitVersion::from_r1cs(&r1cs).unwrap()
+}
+
+/// Save a stand-in proving key and record it in the manifest
+fn saved_key(dir: &Path) -> String {
+    let path = dir.join("proving.key").to_string_lossy().into_owned();
+    fs::write(&path, b"key").unwrap();
+    record_artifact(&path, ArtifactKind::ProvingKey, b"key").unwrap();
+    path
+}
+
+#[test]
+fn version_covers_r1cs_and_build_artifacts() {
+    let dir = temp_dir("circuit_version_hash");
+    let version = version(&dir, b"r1cs");
+
+    assert_eq!(
+        version.r1cs_sha256,
+        "e2f858cb06f4d9cf5860b44f2d9e7a19d9b540e2523d6436515becadc69c6b90"
+    );
+    assert_eq!(version.artifacts_sha256, CIRCOM_ARTIFACTS_SHA256);
+}
+
+#[test]
+fn stamped_artifacts_must_match_the_current_version() {
+    let dir = temp_dir("circuit_version_stamped");
+    let key = saved_key(&dir);
+    let setup_version = version(&dir, b"r1cs compiled for setup");
+    record_circuit_version(&key, &setup_version).unwrap();
+
+    check_circuit_version(&key, &setup_version).expect("same circuit");
+
+    let recompiled = version(&dir, b"r1cs after recompiling");
+    match check_circuit_version(&key, &recompiled) {
+        Err(L8zkError::Artifact(ArtifactError::CircuitVersionMismatch {
+            path,
+            found,
+            expected,
+        })) => {
+            assert_eq!(path, key);
+            assert_eq!(found, setup_version.to_string());
+            assert_eq!(expected, recompiled.to_string());
+        }
+        other => panic!("expected CircuitVersionMismatch, got {other:?}"),
+    }
+}
+
+#[test]
+fn unstamped_artifacts_pass() {
+    let dir = temp_dir("circuit_version_unstamped");
+    let key = saved_key(&dir);
+    let current = version(&dir, b"r1cs");
+
+    check_circuit_version(&key, &current).expect("no version recorded");
+    let missing = dir.join("never_saved.bin").to_string_lossy().into_owned();
+    check_circuit_version(&missing, &current).expect("not in the manifest");
+}
+
+#[test]
+fn re_saving_an_artifact_drops_its_stamp() {
+    let dir = temp_dir("circuit_version_resaved");
+    let key = saved_key(&dir);
+    record_circuit_version(&key, &version(&dir, b"old r1cs")).unwrap();
+
+    let key = saved_key(&dir);
+    check_circuit_version(&key, &version(&dir, b"new r1cs")).expect("stamp was replaced");
+}
+
+#[test]
+fn loading_an_artifact_from_another_build_fails() {
+    let dir = temp_dir("circuit_version_load");
+    let key = saved_key(&dir);
+    let other_build = CircuitVersion {
+        artifacts_sha256: "0".repeat(64),
+        ..version(&dir, b"r1cs")
+    };
+    record_circuit_version(&key, &other_build).unwrap();
+
+    match load_proving_key(&key) {
+        Err(L8zkError::Artifact(ArtifactError::CircuitVersionMismatch { path, found, .. })) => {
+            assert_eq!(path, key);
+            assert_eq!(found, other_build.to_string());
+        }
+        other => panic!("expected CircuitVersionMismatch, got {:?}", other.err()),
+    }
+
+    // The same key stamped by this build gets past the version check to the key tag
+    record_circuit_version(&key, &version(&dir, b"r1cs")).unwrap();
+    assert!(!matches!(
+        load_proving_key(&key),
+        Err(L8zkError::Artifact(
+            ArtifactError::CircuitVersionMismatch { .. }
+        ))
+    ));
+}
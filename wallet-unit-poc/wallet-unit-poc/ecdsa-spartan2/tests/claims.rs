@@ -0,0 +1,226 @@
+//! Decoded claim sizing in `extract_prepare_shared_data` for every claim-row length mod 4.
+//!
+//! `claim-decoder.circom` decodes into `(maxClaimsLength * 3) / 4` bytes using circom's field
+//! division, so only multiples of 4 give a circuit. Those must decode to exactly that many
+//! scalars (claim bytes, then zero padding); other lengths must be rejected, not rounded.
+//! Without disclosure the claim is shared as zeros of the same length, keeping the shape.
+//! A `claimEncoding` of `hex` or `raw` decodes the claim row accordingly, into the same number
+//! of scalars as base64url so the circuit shape does not change.
+//! A `decodedClaimLengths` entry sizes the claim independently of its padded row.
+//! `pack_shared_scalars` places several claims contiguously after the key binding.
+//! `list_jwt_claims` reports each claim's index, declared length and disclosure name.
+//! A `disclosedClaimRange` shares only that slice of the decoded claim, zero-padded.
+
+use ecdsa_spartan2::{
+    calculate_jwt_output_indices, extract_prepare_shared_data, list_jwt_claims,
+    pack_shared_scalars, ClaimInfo, InputError, MultiClaimLayout, Scalar, CLAIM_ENCODING_FIELD,
+    DECODED_CLAIM_LENGTHS_FIELD, DISCLOSED_CLAIM_RANGE_FIELD, DISCLOSE_CLAIM_FIELD,
+};
+use serde_json::{json, Value};
+
+/// The P-256 generator, base64url-encoded, as a stand-in key binding
+const KEYBINDING_X: &str = "axfR8uEsQkf4vOblY6RA8ncDfYEt6zOg9KE5RdiYwpY";
+const KEYBINDING_Y: &str = "T-NC4v4af5uO5-tKfA-eFivOM1drMV7Oy7ZAaDe_UfU";
+
+const CLAIM: &[u8] = br#"["Lp5Z42b6mbszsa_G0zsyGqQ","roc_birthday","1040605"]"#;
+const ENCODED_CLAIM: &str =
+    "WyJMcDVaNDJiNm1ic3pzYV9HMHpzeUdxUSIsInJvY19iaXJ0aGRheSIsIjEwNDA2MDUiXQ==";
+
+fn payload() -> Value {
+    json!({ "cnf": { "jwk": { "x": KEYBINDING_X, "y": KEYBINDING_Y } } })
+}
+
+/// A Prepare input whose only claim row holds `ENCODED_CLAIM` zero-padded to `row_length`
+fn root_with_claim_row(row_length: usize) -> Value {
+    let mut row: Vec<String> = ENCODED_CLAIM.bytes().map(|b| b.to_string()).collect();
+    row.resize(row_length, "0".to_string());
+    json!({
+        "ageClaimIndex": 0,
+        "claims": [row],
+        "claimLengths": [ENCODED_CLAIM.len().to_string()],
+    })
+}
+
+#[test]
+fn aligned_claim_rows_decode_without_truncation_or_extra_padding() {
+    for row_length in [96, 100, 104, 128] {
+        let shared = extract_prepare_shared_data(&payload(), &root_with_claim_row(row_length))
+            .unwrap_or_else(|e| panic!("row length {row_length}: {e}"));
+
+        let mut expected: Vec<Scalar> = CLAIM.iter().map(|&b| Scalar::from(b as u64)).collect();
+        expected.resize(row_length / 4 * 3, Scalar::from(0u64));
+        assert_eq!(
+            shared.claim_scalars, expected,
+            "row length {row_length} must decode to the claim followed by zero padding"
+        );
+    }
+}
+
+#[test]
+fn unaligned_claim_rows_are_rejected() {
+    for row_length in [97, 98, 99] {
+        match extract_prepare_shared_data(&payload(), &root_with_claim_row(row_length)) {
+            Err(InputError::UnalignedClaimLength { length }) => assert_eq!(length, row_length),
+            other => {
+                panic!("row length {row_length}: expected UnalignedClaimLength, got {other:?}")
+            }
+        }
+    }
+}
+
+#[test]
+fn undisclosed_claims_are_shared_as_zeros_of_the_decoded_length() {
+    let mut root = root_with_claim_row(96);
+    root[DISCLOSE_CLAIM_FIELD] = json!(false);
+
+    let shared = extract_prepare_shared_data(&payload(), &root).expect("key binding is valid");
+    assert_eq!(shared.claim_scalars, vec![Scalar::from(0u64); 72]);
+
+    let disclosed = extract_prepare_shared_data(&payload(), &root_with_claim_row(96))
+        .expect("claim row decodes");
+    assert_eq!(shared.claim_scalars.len(), disclosed.claim_scalars.len());
+}
+
+#[test]
+fn claims_are_listed_with_their_disclosure_names() {
+    let claims = list_jwt_claims(&root_with_claim_row(96)).expect("claim row decodes");
+
+    assert_eq!(
+        claims,
+        [ClaimInfo {
+            index: 0,
+            length: ENCODED_CLAIM.len(),
+            name: Some("roc_birthday".to_string()),
+            prefix: String::from_utf8_lossy(&CLAIM[..32]).into_owned(),
+        }]
+    );
+}
+
+#[test]
+fn hex_and_raw_claim_rows_are_decoded_as_declared() {
+    let claim = b"1040605";
+    for (encoding, encoded) in [("hex", hex::encode(claim)), ("raw", "1040605".to_string())] {
+        let mut row: Vec<String> = encoded.bytes().map(|b| b.to_string()).collect();
+        row.resize(16, "0".to_string());
+        let root = json!({
+            "ageClaimIndex": 0,
+            "claims": [row],
+            "claimLengths": [encoded.len().to_string()],
+            CLAIM_ENCODING_FIELD: encoding,
+        });
+
+        let shared = extract_prepare_shared_data(&payload(), &root)
+            .unwrap_or_else(|e| panic!("{encoding}: {e}"));
+        let mut expected: Vec<Scalar> = claim.iter().map(|&b| Scalar::from(b as u64)).collect();
+        expected.resize(12, Scalar::from(0u64));
+        assert_eq!(shared.claim_scalars, expected, "{encoding}");
+    }
+}
+
+#[test]
+fn hex_and_raw_claims_that_do_not_fit_the_decoded_length_are_rejected() {
+    let claim_root = |encoding: &str, encoded: &str| {
+        let mut row: Vec<String> = encoded.bytes().map(|b| b.to_string()).collect();
+        row.resize(16, "0".to_string());
+        json!({
+            "ageClaimIndex": 0,
+            "claims": [row],
+            "claimLengths": [encoded.len().to_string()],
+            CLAIM_ENCODING_FIELD: encoding,
+        })
+    };
+
+    match extract_prepare_shared_data(&payload(), &claim_root("raw", "0123456789abcdef")) {
+        Err(InputError::ClaimExceedsDecodedLength {
+            decoded, length, ..
+        }) => {
+            assert_eq!((decoded, length), (16, 12))
+        }
+        other => panic!("expected ClaimExceedsDecodedLength, got {other:?}"),
+    }
+    assert!(matches!(
+        extract_prepare_shared_data(&payload(), &claim_root("hex", "31303")),
+        Err(InputError::InvalidHexClaim(_))
+    ));
+}
+
+#[test]
+fn decoded_claim_lengths_override_the_padded_row() {
+    let mut root = root_with_claim_row(256);
+    root[DECODED_CLAIM_LENGTHS_FIELD] = json!([60]);
+    let shared = extract_prepare_shared_data(&payload(), &root).expect("claim fits in 60 bytes");
+
+    let mut expected: Vec<Scalar> = CLAIM.iter().map(|&b| Scalar::from(b as u64)).collect();
+    expected.resize(60, Scalar::from(0u64));
+    assert_eq!(shared.claim_scalars, expected);
+
+    root[DECODED_CLAIM_LENGTHS_FIELD] = json!([null]);
+    let shared = extract_prepare_shared_data(&payload(), &root).expect("row length applies");
+    assert_eq!(shared.claim_scalars.len(), 192);
+
+    root[DECODED_CLAIM_LENGTHS_FIELD] = json!([CLAIM.len() - 1]);
+    match extract_prepare_shared_data(&payload(), &root) {
+        Err(InputError::ClaimExceedsDecodedLength {
+            index,
+            decoded,
+            length,
+        }) => assert_eq!((index, decoded, length), (0, CLAIM.len(), CLAIM.len() - 1)),
+        other => panic!("expected ClaimExceedsDecodedLength, got {other:?}"),
+    }
+}
+
+#[test]
+fn disclosed_claim_ranges_share_only_the_slice() {
+    // The year "104" of the ROC birthday "1040605"
+    let start = CLAIM
+        .windows(7)
+        .position(|window| window == b"1040605")
+        .expect("claim holds the birthday");
+    let mut root = root_with_claim_row(96);
+    root[DISCLOSED_CLAIM_RANGE_FIELD] = json!([start, start + 3]);
+
+    let shared = extract_prepare_shared_data(&payload(), &root).expect("range is in the claim");
+    let mut expected: Vec<Scalar> = b"104".iter().map(|&b| Scalar::from(b as u64)).collect();
+    expected.resize(72, Scalar::from(0u64));
+    assert_eq!(shared.claim_scalars, expected);
+
+    let layout = calculate_jwt_output_indices(0, 96)
+        .unwrap()
+        .with_disclosed_range(start..start + 3)
+        .expect("range is in the claim");
+    assert_eq!(
+        layout.disclosed_claim_range(),
+        layout.age_claim_start + start..layout.age_claim_start + start + 3
+    );
+
+    for range in [json!([0, 73]), json!([5, 4]), json!([0]), json!("0..4")] {
+        root[DISCLOSED_CLAIM_RANGE_FIELD] = range.clone();
+        assert!(
+            extract_prepare_shared_data(&payload(), &root).is_err(),
+            "expected an error for {range}"
+        );
+    }
+    assert!(calculate_jwt_output_indices(0, 96)
+        .unwrap()
+        .with_disclosed_range(0..73)
+        .is_err());
+}
+
+#[test]
+fn claims_are_packed_after_the_key_binding_in_order() {
+    let scalars = |values: &[u64]| values.iter().map(|&v| Scalar::from(v)).collect::<Vec<_>>();
+    let (first, empty, second) = (scalars(&[1, 2, 3]), scalars(&[]), scalars(&[4, 5]));
+
+    let (packed, layout) = pack_shared_scalars(
+        Scalar::from(8u64),
+        Scalar::from(9u64),
+        &[&first, &empty, &second],
+    );
+
+    assert_eq!(*packed, scalars(&[8, 9, 1, 2, 3, 4, 5]));
+    assert_eq!(layout, MultiClaimLayout::new(&[3, 0, 2]));
+    assert_eq!(layout.claim_ranges(), [2..5, 5..5, 5..7]);
+    assert_eq!(layout.num_shared(), packed.len());
+    assert_eq!(layout.claim_range(3), None);
+    assert_eq!(MultiClaimLayout::new(&[]).num_shared(), 2);
+}
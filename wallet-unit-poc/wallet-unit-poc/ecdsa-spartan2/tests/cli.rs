@@ -0,0 +1,444 @@
+use ecdsa_spartan2::cli::{
+    parse_command, CircuitAction, CircuitKind, CommandOptions, OutputFormat, ParsedCommand,
+};
+use ecdsa_spartan2::{InputSource, ProofNaming};
+use std::path::PathBuf;
+
+fn parse(line: &str) -> Result<ParsedCommand, String> {
+    let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+    parse_command(&args)
+}
+
+fn with_input(path: &str) -> CommandOptions {
+    CommandOptions {
+        input: Some(InputSource::Path(PathBuf::from(path))),
+        ..CommandOptions::default()
+    }
+}
+
+#[test]
+fn legacy_aliases_map_to_circuit_actions() {
+    use CircuitAction::*;
+    use CircuitKind::*;
+
+    let cases = [
+        ("prepare", Prepare, Run),
+        ("show", Show, Run),
+        ("setup_prepare", Prepare, Setup),
+        ("setup_show", Show, Setup),
+        ("prove_prepare", Prepare, Prove),
+        ("prove_show", Show, Prove),
+        ("verify_prepare", Prepare, Verify),
+        ("verify_show", Show, Verify),
+        ("reblind_prepare", Prepare, Reblind),
+        ("reblind_show", Show, Reblind),
+        ("generate_shared_blinds", Prepare, GenerateSharedBlinds),
+        ("benchmark", Prepare, Benchmark),
+    ];
+
+    for (line, circuit, action) in cases {
+        let expected = ParsedCommand {
+            circuit,
+            action,
+            options: CommandOptions::default(),
+        };
+        assert_eq!(parse(line), Ok(expected), "command: {line}");
+    }
+}
+
+#[test]
+fn circuit_action_forms_map_to_circuit_actions() {
+    use CircuitAction::*;
+    use CircuitKind::*;
+
+    let cases = [
+        ("prepare run", Prepare, Run),
+        ("prepare setup", Prepare, Setup),
+        ("prepare prove", Prepare, Prove),
+        ("prepare verify", Prepare, Verify),
+        ("prepare reblind", Prepare, Reblind),
+        (
+            "prepare generate_shared_blinds",
+            Prepare,
+            GenerateSharedBlinds,
+        ),
+        ("prepare benchmark", Prepare, Benchmark),
+        ("show run", Show, Run),
+        ("show setup", Show, Setup),
+        ("show prove", Show, Prove),
+        ("show verify", Show, Verify),
+        ("show reblind", Show, Reblind),
+        ("show benchmark", Show, Benchmark),
+        ("prepare setup-and-prove", Prepare, SetupAndProve),
+        ("show setup-and-prove", Show, SetupAndProve),
+        ("prepare estimate-proof", Prepare, EstimateProof),
+        ("show estimate-proof", Show, EstimateProof),
+    ];
+
+    for (line, circuit, action) in cases {
+        let expected = ParsedCommand {
+            circuit,
+            action,
+            options: CommandOptions::default(),
+        };
+        assert_eq!(parse(line), Ok(expected), "command: {line}");
+    }
+}
+
+#[test]
+fn options_are_parsed() {
+    let cases = [
+        (
+            "prepare prove --input in.json",
+            CircuitKind::Prepare,
+            CircuitAction::Prove,
+            with_input("in.json"),
+        ),
+        (
+            "show -i in.json",
+            CircuitKind::Show,
+            CircuitAction::Run,
+            with_input("in.json"),
+        ),
+        (
+            "prove_show --input=in.json",
+            CircuitKind::Show,
+            CircuitAction::Prove,
+            with_input("in.json"),
+        ),
+        (
+            "verify_prepare --format json --metrics-out out.json",
+            CircuitKind::Prepare,
+            CircuitAction::Verify,
+            CommandOptions {
+                format: OutputFormat::Json,
+                metrics_out: Some(PathBuf::from("out.json")),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "benchmark --keep-going --format=prometheus",
+            CircuitKind::Prepare,
+            CircuitAction::Benchmark,
+            CommandOptions {
+                format: OutputFormat::Prometheus,
+                keep_going: true,
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "verify-dir --dir proofs --vk=show.key",
+            CircuitKind::Prepare,
+            CircuitAction::VerifyDir,
+            CommandOptions {
+                dir: Some(PathBuf::from("proofs")),
+                vk: Some(PathBuf::from("show.key")),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "prove_show --no-save",
+            CircuitKind::Show,
+            CircuitAction::Prove,
+            CommandOptions {
+                no_save: true,
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "prepare prove --no-save --format json --metrics-out m.json",
+            CircuitKind::Prepare,
+            CircuitAction::Prove,
+            CommandOptions {
+                no_save: true,
+                format: OutputFormat::Json,
+                metrics_out: Some(PathBuf::from("m.json")),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "reblind_prepare --verify-after",
+            CircuitKind::Prepare,
+            CircuitAction::Reblind,
+            CommandOptions {
+                verify_after: true,
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "show reblind --verify-after",
+            CircuitKind::Show,
+            CircuitAction::Reblind,
+            CommandOptions {
+                verify_after: true,
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "show verify --expected-nonce 42",
+            CircuitKind::Show,
+            CircuitAction::Verify,
+            CommandOptions {
+                expected_nonce: Some("42".to_string()),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "check-link --prepare p.json --show=s.json",
+            CircuitKind::Prepare,
+            CircuitAction::CheckLink,
+            CommandOptions {
+                prepare_input: Some(PathBuf::from("p.json")),
+                show_input: Some(PathBuf::from("s.json")),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "derive-vk --pk keys/prepare_proving.key --out=keys/prepare_verifying.key",
+            CircuitKind::Prepare,
+            CircuitAction::DeriveVk,
+            CommandOptions {
+                pk: Some(PathBuf::from("keys/prepare_proving.key")),
+                out: Some(PathBuf::from("keys/prepare_verifying.key")),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "inspect-instance --input keys/prepare_instance.bin",
+            CircuitKind::Prepare,
+            CircuitAction::InspectInstance,
+            with_input("keys/prepare_instance.bin"),
+        ),
+        (
+            "show prove --input-json={\"nonce\":\"7\"}",
+            CircuitKind::Show,
+            CircuitAction::Prove,
+            CommandOptions {
+                input: Some(InputSource::Inline(r#"{"nonce":"7"}"#.to_string())),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "doctor",
+            CircuitKind::Prepare,
+            CircuitAction::Doctor,
+            CommandOptions::default(),
+        ),
+        (
+            "show prove-dir --dir inputs --out-dir=proofs --naming stem",
+            CircuitKind::Show,
+            CircuitAction::ProveDir,
+            CommandOptions {
+                dir: Some(PathBuf::from("inputs")),
+                out_dir: Some(PathBuf::from("proofs")),
+                naming: ProofNaming::Stem,
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "auto prove --input in.json",
+            CircuitKind::Auto,
+            CircuitAction::Prove,
+            with_input("in.json"),
+        ),
+        (
+            "benchmark-verify --proof keys/show_proof.bin --vk=show.key --iterations 500 --format json",
+            CircuitKind::Prepare,
+            CircuitAction::BenchmarkVerify,
+            CommandOptions {
+                proof: Some(PathBuf::from("keys/show_proof.bin")),
+                vk: Some(PathBuf::from("show.key")),
+                iterations: Some(500),
+                format: OutputFormat::Json,
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "minimize-proof --in keys/show_proof.bin --out min.bin --vk=show.key",
+            CircuitKind::Prepare,
+            CircuitAction::MinimizeProof,
+            CommandOptions {
+                proof: Some(PathBuf::from("keys/show_proof.bin")),
+                out: Some(PathBuf::from("min.bin")),
+                vk: Some(PathBuf::from("show.key")),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "prepare generate_shared_blinds --seed 0x00ff",
+            CircuitKind::Prepare,
+            CircuitAction::GenerateSharedBlinds,
+            CommandOptions {
+                seed: Some(vec![0x00, 0xff]),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "generate_shared_blinds --seed=c0ffee",
+            CircuitKind::Prepare,
+            CircuitAction::GenerateSharedBlinds,
+            CommandOptions {
+                seed: Some(vec![0xc0, 0xff, 0xee]),
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "benchmark --estimate --format json",
+            CircuitKind::Prepare,
+            CircuitAction::Benchmark,
+            CommandOptions {
+                estimate: true,
+                format: OutputFormat::Json,
+                ..CommandOptions::default()
+            },
+        ),
+        (
+            "show benchmark --estimate --input in.json",
+            CircuitKind::Show,
+            CircuitAction::Benchmark,
+            CommandOptions {
+                estimate: true,
+                ..with_input("in.json")
+            },
+        ),
+        (
+            "check-witness --witness keys/prepare_witness.bin --input in.json",
+            CircuitKind::Prepare,
+            CircuitAction::CheckWitness,
+            CommandOptions {
+                witness: Some(PathBuf::from("keys/prepare_witness.bin")),
+                ..with_input("in.json")
+            },
+        ),
+        (
+            "show dump-shape --input in.json --out=shape.json",
+            CircuitKind::Show,
+            CircuitAction::DumpShape,
+            CommandOptions {
+                out: Some(PathBuf::from("shape.json")),
+                ..with_input("in.json")
+            },
+        ),
+    ];
+
+    for (line, circuit, action, options) in cases {
+        let expected = ParsedCommand {
+            circuit,
+            action,
+            options,
+        };
+        assert_eq!(parse(line), Ok(expected), "command: {line}");
+    }
+}
+
+#[test]
+fn help_flags_select_show_help() {
+    for line in [
+        "--help",
+        "-h",
+        "prepare --help",
+        "show prove -h",
+        "reblind_show --help",
+        "generate_shared_blinds -h",
+    ] {
+        let command = parse(line).unwrap_or_else(|err| panic!("command: {line}: {err}"));
+        assert_eq!(command.action, CircuitAction::ShowHelp, "command: {line}");
+    }
+}
+
+#[test]
+fn invalid_commands_are_rejected() {
+    for line in [
+        "",
+        "unknown",
+        "prepare frobnicate",
+        "show generate_shared_blinds",
+        "reblind_prepare --input in.json",
+        "prepare reblind extra",
+        "generate_shared_blinds --keep-going",
+        "generate_shared_blinds --verify-after",
+        "prepare run --no-save",
+        "prove_prepare --no-save --format json",
+        "prove_prepare --input",
+        "prove_prepare --bogus",
+        "verify_show --input in.json",
+        "benchmark --format xml",
+        "verify-dir --dir proofs",
+        "verify-dir --vk show.key",
+        "inspect-instance",
+        "inspect-instance --input-json {}",
+        "prepare prove --input-json",
+        "check-link --prepare p.json",
+        "check-link --show s.json",
+        "prepare verify --expected-nonce 42",
+        "show verify --expected-nonce 0x2a",
+        "show verify --expected-nonce",
+        "derive-vk --pk keys/prepare_proving.key",
+        "derive-vk --out keys/prepare_verifying.key",
+        "prepare prove-dir --dir inputs",
+        "show prove-dir --out-dir proofs",
+        "show prove-dir --dir inputs --out-dir proofs --naming uuid",
+        "benchmark-verify --proof keys/show_proof.bin",
+        "auto prove",
+        "auto verify",
+        "auto prove-dir --dir inputs --out-dir proofs",
+        "benchmark-verify --vk show.key",
+        "benchmark-verify --proof p.bin --vk show.key --iterations 0",
+        "benchmark-verify --proof p.bin --vk show.key --iterations=many",
+        "prepare dump-shape",
+        "minimize-proof --in p.bin --out min.bin",
+        "minimize-proof --in p.bin --vk show.key",
+        "minimize-proof --out min.bin --vk show.key",
+        "minimize-proof --in p.bin --out min.bin --vk show.key --format json",
+        "prepare dump-shape --out shape.json --format json",
+        "check-witness --input in.json",
+        "generate_shared_blinds --seed",
+        "generate_shared_blinds --seed=",
+        "generate_shared_blinds --seed xyz",
+        "check-witness --witness w.bin",
+        "check-witness --witness w.bin --input in.json --format json",
+        "benchmark --estimate --format prometheus",
+        "benchmark --estimate --keep-going",
+        "prepare run --estimate",
+    ] {
+        assert!(parse(line).is_err(), "expected an error for: {line:?}");
+    }
+}
+
+#[test]
+fn every_option_reports_a_missing_value_the_same_way() {
+    for (line, flag) in [
+        ("verify-dir --dir proofs --vk", "--vk"),
+        ("verify-dir --dir= --vk show.key", "--dir"),
+        ("prepare prove --input", "--input"),
+        ("prepare prove -i", "-i"),
+        (
+            "benchmark-verify --proof p.bin --vk show.key --iterations=",
+            "--iterations",
+        ),
+        ("derive-vk --pk keys/prepare_proving.key --out", "--out"),
+        ("check-link --prepare p.json --show=", "--show"),
+    ] {
+        assert_eq!(
+            parse(line),
+            Err(format!("Missing value for {flag}")),
+            "command: {line}"
+        );
+    }
+}
+
+#[test]
+fn required_options_are_checked_after_parsing() {
+    assert_eq!(
+        parse("check-link --show s.json"),
+        Err("check-link requires --prepare <path>".to_string())
+    );
+    assert_eq!(
+        parse("derive-vk --pk keys/prepare_proving.key"),
+        Err("derive-vk requires --out <path>".to_string())
+    );
+    assert_eq!(
+        parse("inspect-instance --input-json {}"),
+        Err("inspect-instance requires --input <path>".to_string())
+    );
+}
@@ -0,0 +1,18 @@
+//! `estimate_commit_memory` counts the padded witness plus one blind and one row commitment
+//! per Hyrax row, so it grows with the witness rounded up to a power of two.
+
+use ecdsa_spartan2::estimate_commit_memory;
+
+#[test]
+fn witness_is_padded_to_a_power_of_two() {
+    // 1000 variables pad to 1024: one Hyrax row
+    assert_eq!(estimate_commit_memory(1000), 1024 * 32 + (32 + 96));
+    assert_eq!(estimate_commit_memory(1024), estimate_commit_memory(1000));
+    // 1025 pad to 2048: two rows
+    assert_eq!(estimate_commit_memory(1025), 2048 * 32 + 2 * (32 + 96));
+}
+
+#[test]
+fn empty_witness_still_takes_one_row() {
+    assert_eq!(estimate_commit_memory(0), 32 + (32 + 96));
+}
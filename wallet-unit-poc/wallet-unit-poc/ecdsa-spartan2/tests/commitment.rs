@@ -0,0 +1,39 @@
+//! Pedersen commitments to claim scalars open only to the committed claim and blind.
+
+use ecdsa_spartan2::{commit_claim, open_claim_commitment, Scalar};
+
+fn claim(bytes: &[u8]) -> Vec<Scalar> {
+    bytes.iter().map(|&b| Scalar::from(b as u64)).collect()
+}
+
+#[test]
+fn commitment_opens_to_the_committed_claim_only() {
+    let claim_scalars = claim(br#"["salt","roc_birthday","1040605"]"#);
+    let (commitment, blind) = commit_claim(&claim_scalars, None);
+
+    assert!(open_claim_commitment(&commitment, &claim_scalars, blind));
+    assert!(!open_claim_commitment(
+        &commitment,
+        &claim(br#"["salt","roc_birthday","1040606"]"#),
+        blind
+    ));
+    assert!(!open_claim_commitment(
+        &commitment,
+        &claim_scalars,
+        blind + Scalar::from(1u64)
+    ));
+}
+
+#[test]
+fn given_blind_is_reused_and_fresh_blinds_hide_the_claim() {
+    let claim_scalars = claim(b"1040605");
+    let blind = Scalar::from(7u64);
+
+    let (first, returned) = commit_claim(&claim_scalars, Some(blind));
+    assert_eq!(returned, blind);
+    assert_eq!(commit_claim(&claim_scalars, Some(blind)).0, first);
+
+    let (a, _) = commit_claim(&claim_scalars, None);
+    let (b, _) = commit_claim(&claim_scalars, None);
+    assert_ne!(a, b, "fresh blinds must give unlinkable commitments");
+}
@@ -0,0 +1,119 @@
+//! `l8zk.toml` fills in the options a command takes but the flags leave out; explicit flags
+//! always win, and values never reach commands that do not take them.
+
+use std::path::PathBuf;
+
+use ecdsa_spartan2::{
+    cli::{parse_command_with_config, CircuitAction, OutputFormat},
+    take_config_flag, Config, InputSource, ProofNaming,
+};
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+fn config() -> Config {
+    Config::from_toml(
+        r#"
+        input = "inputs/default.json"
+        format = "json"
+        out-dir = "proofs"
+        naming = "stem"
+        vk = "keys/show_verifying.key"
+        iterations = 250
+        "#,
+    )
+    .expect("config parses")
+}
+
+#[test]
+fn config_values_fill_unset_options() {
+    let config = config();
+
+    let prove = parse_command_with_config(&args("show prove"), Some(&config)).unwrap();
+    assert_eq!(
+        prove.options.input,
+        Some(InputSource::Path(PathBuf::from("inputs/default.json")))
+    );
+    assert_eq!(prove.options.format, OutputFormat::Json);
+
+    // Required options may come from the config alone
+    let prove_dir =
+        parse_command_with_config(&args("prepare prove-dir --dir inputs"), Some(&config)).unwrap();
+    assert_eq!(prove_dir.options.out_dir, Some(PathBuf::from("proofs")));
+    assert_eq!(prove_dir.options.naming, ProofNaming::Stem);
+
+    let bench = parse_command_with_config(
+        &args("benchmark-verify --proof keys/show_proof.bin"),
+        Some(&config),
+    )
+    .unwrap();
+    assert_eq!(bench.action, CircuitAction::BenchmarkVerify);
+    assert_eq!(
+        bench.options.vk,
+        Some(PathBuf::from("keys/show_verifying.key"))
+    );
+    assert_eq!(bench.options.iterations, Some(250));
+}
+
+#[test]
+fn flags_override_the_config() {
+    let config = config();
+
+    let prove = parse_command_with_config(
+        &args("show prove --input other.json --format table"),
+        Some(&config),
+    )
+    .unwrap();
+    assert_eq!(
+        prove.options.input,
+        Some(InputSource::Path(PathBuf::from("other.json")))
+    );
+    assert_eq!(prove.options.format, OutputFormat::Table);
+
+    let prove_dir = parse_command_with_config(
+        &args("show prove-dir --dir inputs --naming=hash"),
+        Some(&config),
+    )
+    .unwrap();
+    assert_eq!(prove_dir.options.naming, ProofNaming::ContentHash);
+}
+
+#[test]
+fn config_values_skip_commands_that_do_not_take_them() {
+    let verify = parse_command_with_config(&args("show verify"), Some(&config())).unwrap();
+    assert_eq!(verify.options.input, None);
+
+    let doctor = parse_command_with_config(&args("doctor"), Some(&config())).unwrap();
+    assert_eq!(doctor.options.format, OutputFormat::Table);
+    assert_eq!(doctor.options.vk, None);
+}
+
+#[test]
+fn invalid_configs_are_rejected() {
+    for contents in [
+        "threads = 4",
+        "format = \"xml\"",
+        "naming = \"uuid\"",
+        "iterations = 0",
+        "input = 3",
+    ] {
+        assert!(
+            Config::from_toml(contents).is_err(),
+            "expected an error for: {contents}"
+        );
+    }
+}
+
+#[test]
+fn config_flag_is_taken_from_anywhere_in_the_arguments() {
+    let (path, rest) = take_config_flag(&args("show prove --config ci.toml --no-save")).unwrap();
+    assert_eq!(path, Some(PathBuf::from("ci.toml")));
+    assert_eq!(rest, args("show prove --no-save"));
+
+    let (path, rest) = take_config_flag(&args("--config=ci.toml doctor")).unwrap();
+    assert_eq!(path, Some(PathBuf::from("ci.toml")));
+    assert_eq!(rest, args("doctor"));
+
+    assert!(take_config_flag(&args("doctor --config")).is_err());
+}
@@ -0,0 +1,51 @@
+//! A public-input descriptor records labelled values that read back as the same scalars.
+
+use ecdsa_spartan2::{
+    descriptor_path, load_descriptor, save_descriptor, DescriptorError, PublicInputsDescriptor,
+    Scalar, SHOW_PUBLIC_INPUTS,
+};
+
+#[test]
+fn values_round_trip_through_a_file() {
+    let nonce = Scalar::from(0xdead_beef_u64);
+    let descriptor = PublicInputsDescriptor::new(SHOW_PUBLIC_INPUTS, &[nonce]).unwrap();
+    assert_eq!(descriptor.inputs[0].label, "nonce");
+
+    let proof = format!("{}/descriptor_proof.bin", env!("CARGO_TARGET_TMPDIR"));
+    let path = descriptor_path(&proof);
+    assert!(path.ends_with("descriptor_proof.bin.public.json"));
+    save_descriptor(&path, &descriptor).unwrap();
+
+    let loaded = load_descriptor(&path).unwrap();
+    assert_eq!(loaded, descriptor);
+    assert_eq!(loaded.values().unwrap(), vec![nonce]);
+    assert_eq!(loaded.get("nonce").unwrap().unwrap(), nonce);
+    assert!(loaded.get("currentDate").is_none());
+}
+
+#[test]
+fn every_value_needs_a_label() {
+    let err = PublicInputsDescriptor::new(SHOW_PUBLIC_INPUTS, &[]).unwrap_err();
+    assert!(matches!(
+        err,
+        DescriptorError::LabelCountMismatch {
+            labels: 1,
+            values: 0
+        }
+    ));
+}
+
+#[test]
+fn malformed_values_are_rejected() {
+    let mut descriptor =
+        PublicInputsDescriptor::new(SHOW_PUBLIC_INPUTS, &[Scalar::from(1)]).unwrap();
+    descriptor.inputs[0].value = "0x1234".to_string();
+
+    match descriptor.values() {
+        Err(DescriptorError::InvalidValue { label, value }) => {
+            assert_eq!(label, "nonce");
+            assert_eq!(value, "0x1234");
+        }
+        other => panic!("expected InvalidValue, got {other:?}"),
+    }
+}
@@ -0,0 +1,59 @@
+//! `parse_disclosed_claim` turns disclosed claim bytes into a key and a JSON value, whether
+//! the claim is a bare payload member or an SD-JWT disclosure, and ignores zero padding.
+
+use ecdsa_spartan2::{parse_disclosed_claim, InputError};
+use serde_json::{json, Value};
+
+fn parsed(bytes: &[u8]) -> (String, Value) {
+    parse_disclosed_claim(bytes).expect("claim parses")
+}
+
+#[test]
+fn bare_member_gives_key_and_value() {
+    let expected = ("birthdate".to_string(), json!("1990-01-01"));
+    assert_eq!(parsed(br#""birthdate":"1990-01-01""#), expected);
+    assert_eq!(parsed(br#""birthdate": "1990-01-01","#), expected);
+    assert_eq!(parsed(br#"{"birthdate":"1990-01-01"}"#), expected);
+    assert_eq!(parsed(b"\"birthdate\":\"1990-01-01\"\0\0\0\0"), expected);
+}
+
+#[test]
+fn non_string_values_are_kept_as_json() {
+    assert_eq!(
+        parsed(br#""age_over_18":true"#),
+        ("age_over_18".to_string(), json!(true))
+    );
+    assert_eq!(
+        parsed(br#""address":{"country":"DE"}"#),
+        ("address".to_string(), json!({ "country": "DE" }))
+    );
+}
+
+#[test]
+fn sd_jwt_disclosure_gives_name_and_value() {
+    assert_eq!(
+        parsed(br#"["Lp5Z42b6mbszsa_G0zsyGqQ","roc_birthday","1040605"]"#),
+        ("roc_birthday".to_string(), json!("1040605"))
+    );
+}
+
+#[test]
+fn other_fragments_are_rejected() {
+    for bytes in [
+        &b""[..],
+        b"1990-01-01",
+        br#""a":1,"b":2"#,
+        br#"["salt","name"]"#,
+        br#"["salt",7,"value"]"#,
+        b"\"birthdate\":\"\xff\"",
+    ] {
+        assert!(
+            matches!(
+                parse_disclosed_claim(bytes),
+                Err(InputError::MalformedClaim(_))
+            ),
+            "expected MalformedClaim for {:?}",
+            String::from_utf8_lossy(bytes)
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! `extract_jwt_outputs` reads the key binding and disclosed claim from a Prepare witness, and
+//! `reconstruct_disclosure` turns them back into the `cnf.jwk` and claim of the JWT payload.
+
+use ecdsa_spartan2::{
+    calculate_jwt_output_indices, extract_jwt_outputs, reconstruct_disclosure, DisclosedData,
+    InputError, Scalar,
+};
+use ff::PrimeField;
+use serde_json::json;
+
+/// The P-256 generator, as a stand-in key binding
+const GX: &str = "48439561293906451759052585252797914202762949526041747995844080717082404635286";
+const GY: &str = "36134250956749795798585127919587881956611106672985015071877198253568414405109";
+const KEYBINDING_X: &str = "axfR8uEsQkf4vOblY6RA8ncDfYEt6zOg9KE5RdiYwpY";
+const KEYBINDING_Y: &str = "T-NC4v4af5uO5-tKfA-eFivOM1drMV7Oy7ZAaDe_UfU";
+
+const CLAIM: &[u8] = br#""birthdate":"1990-01-01""#;
+
+fn scalar(decimal: &str) -> Scalar {
+    Scalar::from_str_vartime(decimal).expect("valid decimal scalar")
+}
+
+/// A witness with the constant one, `CLAIM` zero-padded to `claim_len` bytes, then the key
+/// binding
+fn witness(claim_len: usize) -> Vec<Scalar> {
+    let mut witness = vec![Scalar::from(1u64)];
+    witness.extend(
+        CLAIM
+            .iter()
+            .chain(std::iter::repeat(&0))
+            .take(claim_len)
+            .map(|&byte| Scalar::from(byte as u64)),
+    );
+    witness.extend([scalar(GX), scalar(GY)]);
+    witness
+}
+
+#[test]
+fn outputs_are_read_at_the_layout_positions() {
+    // maxClaimsLength 40 decodes into 30 bytes
+    let layout = calculate_jwt_output_indices(0, 40).unwrap();
+    let outputs = extract_jwt_outputs(&witness(30), &layout).unwrap();
+
+    assert_eq!(
+        outputs,
+        DisclosedData {
+            keybinding_x: scalar(GX),
+            keybinding_y: scalar(GY),
+            claim: CLAIM.to_vec(),
+        }
+    );
+}
+
+#[test]
+fn disclosure_has_the_payload_shape() {
+    let layout = calculate_jwt_output_indices(0, 40).unwrap();
+    let outputs = extract_jwt_outputs(&witness(30), &layout).unwrap();
+
+    assert_eq!(
+        reconstruct_disclosure(&outputs),
+        json!({
+            "cnf": { "jwk": { "kty": "EC", "crv": "P-256", "x": KEYBINDING_X, "y": KEYBINDING_Y } },
+            "birthdate": "1990-01-01",
+        })
+    );
+}
+
+#[test]
+fn unparseable_claims_are_kept_as_text() {
+    let outputs = DisclosedData {
+        keybinding_x: scalar(GX),
+        keybinding_y: scalar(GY),
+        claim: b"1990".to_vec(),
+    };
+    assert_eq!(reconstruct_disclosure(&outputs)["disclosedClaim"], "1990");
+}
+
+#[test]
+fn malformed_witnesses_are_rejected() {
+    let layout = calculate_jwt_output_indices(0, 40).unwrap();
+
+    let mut not_a_byte = witness(30);
+    not_a_byte[3] = Scalar::from(256u64);
+    assert!(matches!(
+        extract_jwt_outputs(&not_a_byte, &layout),
+        Err(InputError::ClaimOutputNotAByte { index: 3 })
+    ));
+
+    let short = &witness(30)[..layout.keybinding_y_index];
+    assert!(extract_jwt_outputs(short, &layout).is_err());
+}
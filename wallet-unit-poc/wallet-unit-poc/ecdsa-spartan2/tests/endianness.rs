@@ -0,0 +1,76 @@
+//! Byte order of saved artifacts and scalar conversions is fixed, not the host's, so a proof
+//! written on a little-endian host reads the same on a big-endian one.
+//!
+//! These checks compare against explicit little-endian bytes, so they fail on any host where a
+//! path falls back to native byte order. A Show proof made for a nonce does not verify against
+//! the byte-swapped nonce.
+
+use ecdsa_spartan2::{
+    bigint_to_scalar, prove_circuit_in_memory_with_public_inputs, setup_circuit_keys_no_save,
+    verify_with_public_inputs, L8zkError, Scalar, ShowCircuit,
+};
+use ff::PrimeField;
+use rust_witness::BigInt;
+use std::{error::Error, path::PathBuf};
+
+const SHOW_INPUT: &str = "tests/fixtures/show.json";
+
+/// `scalar` with the bytes of its representation reversed
+fn byte_swapped(scalar: Scalar) -> Scalar {
+    let mut repr = scalar.to_repr();
+    repr.as_mut().reverse();
+    Option::from(Scalar::from_repr(repr)).expect("a small scalar reversed is still canonical")
+}
+
+#[test]
+fn bincode_writes_integers_and_lengths_little_endian() {
+    let value = 0x0102_0304_0506_0708u64;
+    assert_eq!(bincode::serialize(&value).unwrap(), value.to_le_bytes());
+
+    // Vectors carry a u64 length prefix, as every proof, key and witness field does
+    let encoded = bincode::serialize(&vec![0xabu8, 0xcd]).unwrap();
+    assert_eq!(encoded, [2, 0, 0, 0, 0, 0, 0, 0, 0xab, 0xcd]);
+}
+
+#[test]
+fn bigints_convert_to_scalars_little_endian() {
+    let value = 0x0102_0304u64;
+    assert_eq!(
+        bigint_to_scalar(BigInt::from(value)).unwrap(),
+        Scalar::from(value)
+    );
+    // Reading the same bytes in the other order would give a different scalar
+    assert_ne!(
+        bigint_to_scalar(BigInt::from(value.swap_bytes())).unwrap(),
+        Scalar::from(value)
+    );
+}
+
+#[test]
+fn scalars_round_trip_through_bincode() {
+    let scalar = Scalar::from(0x0102_0304_0506_0708u64);
+    let encoded = bincode::serialize(&scalar).unwrap();
+    assert_eq!(bincode::deserialize::<Scalar>(&encoded).unwrap(), scalar);
+
+    let mut swapped = encoded.clone();
+    swapped.reverse();
+    assert_ne!(bincode::deserialize::<Scalar>(&swapped).ok(), Some(scalar));
+}
+
+#[test]
+fn proofs_do_not_verify_for_byte_swapped_public_inputs() -> Result<(), Box<dyn Error>> {
+    let circuit = ShowCircuit::new(Some(PathBuf::from(SHOW_INPUT)));
+    let (pk, vk) = setup_circuit_keys_no_save(circuit.clone())?;
+    let nonce = Scalar::from(0x0102_0304u64);
+    let swapped = byte_swapped(nonce);
+    assert_ne!(swapped, nonce);
+
+    let proved =
+        prove_circuit_in_memory_with_public_inputs(circuit.with_nonce(nonce), &pk, vec![nonce])?;
+    verify_with_public_inputs(&proved.proof, &vk, &[nonce])?;
+    assert!(matches!(
+        verify_with_public_inputs(&proved.proof, &vk, &[swapped]),
+        Err(L8zkError::PublicInputsMismatch)
+    ));
+    Ok(())
+}
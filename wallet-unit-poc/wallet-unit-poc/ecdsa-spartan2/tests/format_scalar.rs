@@ -0,0 +1,19 @@
+//! `format_scalar` prints a scalar's canonical value, big-endian hex then decimal, not its
+//! internal representation.
+
+use ecdsa_spartan2::{format_scalar, scalar_to_hex, Scalar};
+
+#[test]
+fn scalars_print_as_canonical_hex_and_decimal() {
+    let value = Scalar::from(0x1234_5678u64);
+
+    assert_eq!(
+        scalar_to_hex(&value),
+        format!("0x{}12345678", "0".repeat(56))
+    );
+    assert_eq!(
+        format_scalar(&value),
+        format!("0x{}12345678 (305419896)", "0".repeat(56))
+    );
+    assert!(format_scalar(&-Scalar::from(1u64)).starts_with("0xffffffff00000001"));
+}
@@ -0,0 +1,79 @@
+//! YAML inputs parse into the same `Value` as the equivalent JSON, and are refused with a clear
+//! error when the `yaml` feature is off. Inline JSON parses like a JSON file under the same
+//! size limit.
+
+use ecdsa_spartan2::{read_input_json, InputError, InputSource, DEFAULT_MAX_INPUT_SIZE};
+use std::{fs, path::PathBuf};
+
+const YAML_INPUT: &str = "\
+# Issuer key
+pubKeyX: \"98061909492058364035111048019882274619202725064600646935165851115135261780351\"
+messageLength: 3
+message: [97, 46, 98]
+claims:
+  - [1, 2]
+  - [3, 4]
+";
+
+fn write_input(name: &str, contents: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("input_format");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn yaml_input_matches_json() {
+    let yaml = read_input_json(
+        &write_input("input.yaml", YAML_INPUT),
+        DEFAULT_MAX_INPUT_SIZE,
+    )
+    .expect("YAML input parses");
+
+    assert_eq!(
+        yaml,
+        serde_json::json!({
+            "pubKeyX": "98061909492058364035111048019882274619202725064600646935165851115135261780351",
+            "messageLength": 3,
+            "message": [97, 46, 98],
+            "claims": [[1, 2], [3, 4]],
+        })
+    );
+}
+
+#[test]
+#[cfg(not(feature = "yaml"))]
+fn yaml_input_requires_the_yaml_feature() {
+    let result = read_input_json(
+        &write_input("input.yml", YAML_INPUT),
+        DEFAULT_MAX_INPUT_SIZE,
+    );
+    assert!(matches!(
+        result,
+        Err(ecdsa_spartan2::InputError::UnsupportedInputFormat { feature: "yaml" })
+    ));
+}
+
+#[test]
+fn inline_json_matches_a_json_file() {
+    let json = r#"{"messageLength": 3, "message": [97, 46, 98]}"#;
+    let path = write_input("inline.json", json);
+    let cwd = PathBuf::from(".");
+
+    let inline = InputSource::Inline(json.to_string())
+        .read(&cwd, DEFAULT_MAX_INPUT_SIZE, true)
+        .expect("inline JSON parses");
+    assert_eq!(
+        inline,
+        InputSource::Path(path)
+            .read(&cwd, DEFAULT_MAX_INPUT_SIZE, true)
+            .unwrap()
+    );
+
+    match InputSource::Inline(json.to_string()).read(&cwd, 8, false) {
+        Err(InputError::InputTooLarge { size, limit: 8 }) => assert_eq!(size, json.len() as u64),
+        other => panic!("expected InputTooLarge, got {other:?}"),
+    }
+}
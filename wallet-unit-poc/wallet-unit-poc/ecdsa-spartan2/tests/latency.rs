@@ -0,0 +1,29 @@
+//! `LatencySummary` reports nearest-rank percentiles and throughput over all samples.
+
+use std::time::Duration;
+
+use ecdsa_spartan2::LatencySummary;
+
+#[test]
+fn percentiles_are_nearest_rank_samples() {
+    // 1..=200 ms, shuffled so the summary cannot rely on input order
+    let samples: Vec<Duration> = (1..=200u64)
+        .map(|i| Duration::from_millis((i * 37) % 200 + 1))
+        .collect();
+
+    let summary = LatencySummary::from_samples(samples).expect("samples are not empty");
+    assert_eq!(summary.iterations, 200);
+    assert_eq!(summary.total, Duration::from_millis(20_100));
+    assert_eq!(summary.mean, Duration::from_micros(100_500));
+    assert_eq!(summary.median, Duration::from_millis(100));
+    assert_eq!(summary.p99, Duration::from_millis(198));
+    assert!((summary.per_second() - 200.0 / 20.1).abs() < 1e-9);
+}
+
+#[test]
+fn a_single_sample_is_every_percentile() {
+    let summary = LatencySummary::from_samples(vec![Duration::from_micros(750)]).unwrap();
+    assert_eq!(summary.median, summary.p99);
+    assert_eq!(summary.mean, Duration::from_micros(750));
+    assert!(LatencySummary::from_samples(Vec::new()).is_none());
+}
@@ -0,0 +1,59 @@
+//! `build_message_array` pads a JWT to the circuit's message size and reports its length.
+//! `split_jwt` takes two or three parts, and `validate_period_index` only accepts the `.`
+//! after the header.
+
+use ecdsa_spartan2::{build_message_array, split_jwt, validate_period_index, InputError};
+use serde_json::json;
+
+#[test]
+fn pads_with_zeros_after_the_jwt() {
+    let (message, length) = build_message_array(b"eyJ.eyJ.sig", 16).unwrap();
+
+    assert_eq!(length, 11);
+    assert_eq!(message.len(), 16);
+    assert_eq!(&message[..11], b"eyJ.eyJ.sig");
+    assert!(message[11..].iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn a_jwt_filling_the_array_is_not_padded() {
+    let (message, length) = build_message_array(b"eyJ.eyJ", 7).unwrap();
+    assert_eq!((message.as_slice(), length), (&b"eyJ.eyJ"[..], 7));
+}
+
+#[test]
+fn rejects_a_jwt_longer_than_the_circuit_takes() {
+    let err = build_message_array(b"eyJ.eyJ.sig", 8).unwrap_err();
+    assert!(matches!(
+        err,
+        InputError::MessageLengthOutOfRange {
+            message_length: 11,
+            max: 8
+        }
+    ));
+}
+
+#[test]
+fn split_jwt_rejects_more_than_three_parts() {
+    let jwt = split_jwt(b"eyJ.eyJ.sig", 11).unwrap();
+    assert_eq!((jwt.payload.offset, jwt.signature.unwrap().offset), (4, 8));
+
+    let err = split_jwt(b"eyJ.eyJ.sig.x", 13).unwrap_err();
+    assert!(matches!(err, InputError::MalformedJwt { parts: 4 }));
+}
+
+#[test]
+fn period_index_must_be_the_header_separator() {
+    let (message, length) = build_message_array(b"eyJ.eyJ.sig", 16).unwrap();
+    let input = |period_index: u64| json!({ "message": message, "messageLength": length, "periodIndex": period_index });
+
+    validate_period_index(&input(3)).unwrap();
+    let err = validate_period_index(&input(7)).unwrap_err();
+    assert!(matches!(
+        err,
+        InputError::BadPeriodIndex {
+            claimed: 7,
+            actual_byte: Some(b'.')
+        }
+    ));
+}
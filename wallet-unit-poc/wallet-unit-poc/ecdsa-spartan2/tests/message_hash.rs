@@ -0,0 +1,19 @@
+//! `compute_message_hash` matches the `messageHash` that `show.ts` derives for the circuit.
+
+use ecdsa_spartan2::{bigint_to_scalar, compute_message_hash};
+use rust_witness::BigInt;
+use std::str::FromStr;
+
+#[test]
+fn message_hash_is_sha256_reduced_mod_group_order() {
+    // int(sha256(b"abc"), big-endian) % n, computed independently
+    let expected = BigInt::from_str(
+        "84342368487090800366523834928142263660104883695016514377462985829716817089965",
+    )
+    .unwrap();
+
+    assert_eq!(
+        compute_message_hash(b"abc"),
+        bigint_to_scalar(expected).unwrap()
+    );
+}
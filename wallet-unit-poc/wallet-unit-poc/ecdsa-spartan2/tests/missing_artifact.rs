@@ -0,0 +1,77 @@
+//! Running a step before the one that writes its inputs names the step to run first.
+//! A missing compiled R1CS is reported as `MissingArtifact` rather than a panic, also when
+//! estimating a proof size from it.
+
+use ecdsa_spartan2::{
+    circuits::common::load_r1cs_file, estimate_proof_size_from_r1cs, minimize_proof,
+    verify_circuit, L8zkError, ProverContext,
+};
+use std::path::Path;
+
+#[test]
+fn verify_before_setup_asks_for_setup() {
+    let err = verify_circuit("missing/show_proof.bin", "missing/show_verifying.key").unwrap_err();
+
+    match err {
+        L8zkError::MissingArtifact { path, hint } => {
+            assert_eq!(path, "missing/show_verifying.key");
+            assert_eq!(hint, "run `show setup` first");
+        }
+        other => panic!("expected MissingArtifact, got {other:?}"),
+    }
+}
+
+#[test]
+fn minimize_before_prove_asks_for_prove() {
+    let vk = format!("{}/minimize_verifying.key", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&vk, b"").unwrap();
+    let err = minimize_proof("missing/prepare_proof.bin", "missing/min.bin", &vk).unwrap_err();
+
+    match err {
+        L8zkError::MissingArtifact { path, hint } => {
+            assert_eq!(path, "missing/prepare_proof.bin");
+            assert_eq!(hint, "run `prepare prove` first");
+        }
+        other => panic!("expected MissingArtifact, got {other:?}"),
+    }
+}
+
+#[test]
+fn prover_context_reads_the_proving_key_only_when_asked() {
+    let context = ProverContext::new(
+        "missing/prepare_proving.key",
+        "missing/prepare_verifying.key",
+    );
+    assert!(!context.proving_key_loaded());
+
+    match context.proving_key() {
+        Err(L8zkError::MissingArtifact { path, hint }) => {
+            assert_eq!(path, "missing/prepare_proving.key");
+            assert_eq!(hint, "run `prepare setup` first");
+        }
+        Err(other) => panic!("expected MissingArtifact, got {other:?}"),
+        Ok(_) => panic!("the proving key does not exist"),
+    }
+    assert!(!context.proving_key_loaded());
+}
+
+#[test]
+fn missing_r1cs_is_an_error() {
+    match load_r1cs_file(Path::new("missing/jwt.r1cs")) {
+        Err(L8zkError::MissingArtifact { path, hint }) => {
+            assert_eq!(path, "missing/jwt.r1cs");
+            assert!(hint.contains("L8ZK_CIRCOM_BUILD"), "{hint}");
+        }
+        Err(other) => panic!("expected MissingArtifact, got {other:?}"),
+        Ok(_) => panic!("the R1CS does not exist"),
+    }
+}
+
+#[test]
+fn estimating_from_a_missing_r1cs_is_an_error() {
+    let err = estimate_proof_size_from_r1cs(Path::new("missing/show.r1cs")).unwrap_err();
+    assert!(
+        matches!(&err, L8zkError::MissingArtifact { path, .. } if path == "missing/show.r1cs"),
+        "{err:?}"
+    );
+}
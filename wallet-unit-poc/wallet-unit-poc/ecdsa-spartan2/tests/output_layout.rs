@@ -0,0 +1,44 @@
+//! `check_jwt_output_layout` compares the decoded claim length formula with the public
+//! outputs of the compiled `jwt.circom`, and `calculate_jwt_output_indices` rejects claim
+//! lengths the circuit cannot be compiled for.
+
+use circom_scotia::r1cs::R1CS;
+use ecdsa_spartan2::{calculate_jwt_output_indices, check_jwt_output_layout, InputError, Scalar};
+
+/// A `jwt.r1cs` stand-in with only the public wire count set
+fn r1cs_with_outputs(num_outputs: usize) -> R1CS<Scalar> {
+    R1CS {
+        num_inputs: 1 + num_outputs,
+        num_aux: 0,
+        num_variables: 1 + num_outputs,
+        constraints: vec![],
+    }
+}
+
+#[test]
+fn matching_circuit_passes() {
+    // maxClaimsLength = 128 decodes to 96 bytes, followed by KeyBindingX and KeyBindingY
+    let check = check_jwt_output_layout(&r1cs_with_outputs(96 + 2), 128).unwrap();
+    assert_eq!(check.computed, 96);
+    assert_eq!(check.inferred, 96);
+    assert!(check.matches());
+}
+
+#[test]
+fn circuit_compiled_for_other_claim_length_mismatches() {
+    let check = check_jwt_output_layout(&r1cs_with_outputs(48 + 2), 128).unwrap();
+    assert_eq!(check.inferred, 48);
+    assert!(!check.matches());
+}
+
+#[test]
+fn unaligned_claim_length_is_rejected() {
+    assert!(matches!(
+        calculate_jwt_output_indices(0, 130),
+        Err(InputError::UnalignedClaimLength { length: 130 })
+    ));
+    assert!(matches!(
+        check_jwt_output_layout(&r1cs_with_outputs(97 + 2), 130),
+        Err(InputError::UnalignedClaimLength { length: 130 })
+    ));
+}
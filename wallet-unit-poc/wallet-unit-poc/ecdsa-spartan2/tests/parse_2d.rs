@@ -0,0 +1,40 @@
+//! 2D input arrays too large to parse on one thread still flatten in row order.
+
+use ecdsa_spartan2::{parse_jwt_inputs, PARALLEL_PARSE_THRESHOLD};
+use rust_witness::BigInt;
+use serde_json::{json, Value};
+use std::fs;
+
+const PREPARE_INPUT: &str = "../circom/inputs/jwt/default.json";
+
+/// The default Prepare input with `rows` rows of 50 bytes in `matchSubstring`
+fn input_with_match_rows(rows: usize) -> Value {
+    let mut input: Value =
+        serde_json::from_str(&fs::read_to_string(PREPARE_INPUT).unwrap()).unwrap();
+    input["matchSubstring"] = (0..rows)
+        .map(|row| {
+            (0..50)
+                .map(|col| json!((row * 50 + col) % 256))
+                .collect::<Value>()
+        })
+        .collect::<Value>();
+    input
+}
+
+#[test]
+fn large_arrays_keep_their_order() {
+    let rows = PARALLEL_PARSE_THRESHOLD / 50 + 10;
+    let inputs = parse_jwt_inputs(&input_with_match_rows(rows)).unwrap();
+
+    let expected: Vec<BigInt> = (0..rows * 50).map(|i| BigInt::from(i % 256)).collect();
+    assert_eq!(inputs["matchSubstring"], expected);
+}
+
+#[test]
+fn large_arrays_report_invalid_elements() {
+    let rows = PARALLEL_PARSE_THRESHOLD / 50 + 10;
+    let mut input = input_with_match_rows(rows);
+    input["matchSubstring"][rows - 1][49] = json!("not a number");
+
+    assert!(parse_jwt_inputs(&input).is_err());
+}
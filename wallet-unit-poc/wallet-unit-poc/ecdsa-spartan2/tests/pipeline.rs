@@ -0,0 +1,311 @@
+//! End-to-end check of the linked Prepare/Show flow against the inputs in `tests/fixtures`:
+//! setup → prove → verify for both circuits, then reblind both with the same shared blinds
+//! and check the reblinded proofs verify, keep their `comm_W_shared` and share it.
+//! A Show nonce supplied at prove time is what the verifier must supply to accept the proof.
+//! Reblinding with fixed blinds re-randomizes a proof but keeps its `comm_W_shared` byte for
+//! byte.
+//! `verify_against_any` picks out the key a proof was made under from an allowlist.
+//! `prove_within_memory` refuses to start when the commitment would exceed its budget.
+//! `verify_dir` skips the `.bin` artifacts the manifest records as non-proofs and fails any
+//! other file that does not decode as a proof, so `verify-dir` exits 1 on a truncated proof.
+//! `reblind_batch` reblinds several proofs with one set of blinds, serially or in parallel,
+//! and every result verifies with the same `comm_W_shared`.
+
+use ecdsa_spartan2::{
+    circuit_public_values, generate_shared_blinds, load_instance, load_proof, load_shared_blinds,
+    load_witness, prove_circuit_in_memory, prove_circuit_in_memory_with_public_inputs,
+    prove_circuit_with_pk, prove_within_memory, reblind_batch, reblind_bundle,
+    reblind_with_loaded_data, save_verifying_key, setup_circuit_keys_no_save, verify_against_any,
+    verify_circuit_with_loaded_data, verify_dir, verify_with_public_inputs, L8zkError,
+    PrepareCircuit, Scalar, ShowCircuit, VerifyError, E,
+};
+use spartan2::{r1cs::SplitR1CSInstance, traits::circuit::SpartanCircuit};
+use std::{
+    error::Error,
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// Copies of the circom default inputs, kept here so the pipeline does not change when those do
+const PREPARE_INPUT: &str = "tests/fixtures/prepare.json";
+const SHOW_INPUT: &str = "tests/fixtures/show.json";
+
+struct Artifacts {
+    instance: String,
+    witness: String,
+    proof: String,
+}
+
+impl Artifacts {
+    fn new(dir: &Path, name: &str) -> Self {
+        let path = |kind: &str| dir.join(format!("{name}_{kind}.bin")).display().to_string();
+        Self {
+            instance: path("instance"),
+            witness: path("witness"),
+            proof: path("proof"),
+        }
+    }
+}
+
+fn shared_commitment(instance: &SplitR1CSInstance<E>) -> Vec<u8> {
+    bincode::serialize(&instance.comm_W_shared).expect("commitment serializes")
+}
+
+/// Set up, prove and verify one circuit, then reblind it with `blinds` and verify again.
+/// Returns the reblinded instance.
+fn prove_and_reblind<C: SpartanCircuit<E> + Clone + Debug + Default>(
+    circuit: C,
+    artifacts: &Artifacts,
+    blinds_path: &str,
+) -> Result<SplitR1CSInstance<E>, Box<dyn Error>> {
+    let (pk, vk) = setup_circuit_keys_no_save(circuit.clone())?;
+
+    let public_inputs = circuit_public_values(&circuit)?;
+    prove_circuit_with_pk(
+        circuit,
+        &pk,
+        public_inputs,
+        &artifacts.instance,
+        &artifacts.witness,
+        &artifacts.proof,
+    )?;
+    verify_circuit_with_loaded_data(&load_proof(&artifacts.proof)?, &vk)?;
+
+    let instance = load_instance(&artifacts.instance)?;
+    let shared_before = shared_commitment(&instance);
+    let witness = load_witness(&artifacts.witness)?;
+    // The first circuit generates the shared blinds and the second reuses them
+    let blinds = if fs::metadata(blinds_path).is_ok() {
+        load_shared_blinds::<E>(blinds_path)?
+    } else {
+        generate_shared_blinds::<E>(blinds_path, instance.num_shared_rows())?
+    };
+
+    reblind_with_loaded_data(
+        C::default(),
+        &pk,
+        instance,
+        witness,
+        &blinds,
+        &artifacts.instance,
+        &artifacts.witness,
+        &artifacts.proof,
+    )?;
+    verify_circuit_with_loaded_data(&load_proof(&artifacts.proof)?, &vk)?;
+
+    let reblinded = load_instance(&artifacts.instance)?;
+    assert_eq!(
+        shared_commitment(&reblinded),
+        shared_before,
+        "reblinding must leave comm_W_shared unchanged"
+    );
+    Ok(reblinded)
+}
+
+#[test]
+fn prepare_and_show_proofs_verify_and_link() -> Result<(), Box<dyn Error>> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("pipeline");
+    fs::create_dir_all(&dir)?;
+    let blinds_path = dir.join("shared_blinds.bin").display().to_string();
+    let _ = fs::remove_file(&blinds_path);
+
+    let prepare_instance = prove_and_reblind(
+        PrepareCircuit::new(Some(PathBuf::from(PREPARE_INPUT))),
+        &Artifacts::new(&dir, "prepare"),
+        &blinds_path,
+    )?;
+    let show_instance = prove_and_reblind(
+        ShowCircuit::new(Some(PathBuf::from(SHOW_INPUT))),
+        &Artifacts::new(&dir, "show"),
+        &blinds_path,
+    )?;
+
+    assert!(prepare_instance.comm_W_shared.is_some());
+    assert_eq!(
+        shared_commitment(&prepare_instance),
+        shared_commitment(&show_instance),
+        "reblinded Prepare and Show proofs must share comm_W_shared"
+    );
+    Ok(())
+}
+
+#[test]
+fn show_nonce_is_supplied_at_prove_time() -> Result<(), Box<dyn Error>> {
+    let circuit = ShowCircuit::new(Some(PathBuf::from(SHOW_INPUT)));
+    let (pk, vk) = setup_circuit_keys_no_save(circuit.clone())?;
+    let nonce = Scalar::from(7u64);
+
+    let proved = prove_circuit_in_memory_with_public_inputs(
+        circuit.clone().with_nonce(nonce),
+        &pk,
+        vec![nonce],
+    )?;
+    verify_with_public_inputs(&proved.proof, &vk, &[nonce])?;
+    assert!(matches!(
+        verify_with_public_inputs(&proved.proof, &vk, &[Scalar::from(8u64)]),
+        Err(L8zkError::PublicInputsMismatch)
+    ));
+
+    // Inputs the circuit was not configured with cannot produce a valid proof
+    assert!(matches!(
+        prove_circuit_in_memory_with_public_inputs(circuit.with_nonce(nonce), &pk, vec![]),
+        Err(L8zkError::PublicInputsMismatch)
+    ));
+    Ok(())
+}
+
+#[test]
+fn reblind_preserves_comm_w_shared_exactly() -> Result<(), Box<dyn Error>> {
+    let circuit = ShowCircuit::new(Some(PathBuf::from(SHOW_INPUT)));
+    let (pk, vk) = setup_circuit_keys_no_save(circuit.clone())?;
+
+    let proved = prove_circuit_in_memory(circuit, &pk)?;
+    verify_circuit_with_loaded_data(&proved.proof, &vk)?;
+    let proof_bytes = bincode::serialize(&proved.proof)?;
+    let shared_bytes = bincode::serialize(&proved.proof.comm_W_shared())?;
+
+    // Fixed blinds, so a failure reproduces
+    let blinds: Vec<Scalar> = (1..=proved.instance.num_shared_rows() as u64)
+        .map(Scalar::from)
+        .collect();
+    let reblinded = reblind_bundle(proved, &pk, &blinds)?;
+    verify_circuit_with_loaded_data(&reblinded.proof, &vk)?;
+
+    assert_eq!(
+        bincode::serialize(&reblinded.proof.comm_W_shared())?,
+        shared_bytes,
+        "reblinding must not change comm_W_shared"
+    );
+    assert_ne!(
+        bincode::serialize(&reblinded.proof)?,
+        proof_bytes,
+        "reblinding must re-randomize the rest of the proof"
+    );
+    Ok(())
+}
+
+#[test]
+fn verify_against_any_finds_the_issuing_key() -> Result<(), Box<dyn Error>> {
+    let circuit = ShowCircuit::new(Some(PathBuf::from(SHOW_INPUT)));
+    // Setup is deterministic, so a second Show setup would give the same key; the Prepare
+    // circuit's key stands in for another issuer's
+    let (_, other_vk) =
+        setup_circuit_keys_no_save(PrepareCircuit::new(Some(PathBuf::from(PREPARE_INPUT))))?;
+    let (pk, vk) = setup_circuit_keys_no_save(circuit.clone())?;
+    let proved = prove_circuit_in_memory(circuit, &pk)?;
+    let vks = [other_vk, vk];
+
+    assert_eq!(verify_against_any(&proved.proof, &vks)?, 1);
+    assert!(matches!(
+        verify_against_any(&proved.proof, &vks[..1]),
+        Err(VerifyError::NoMatchingKey { keys: 1 })
+    ));
+    assert!(matches!(
+        verify_against_any(&proved.proof, &[]),
+        Err(VerifyError::NoMatchingKey { keys: 0 })
+    ));
+    Ok(())
+}
+
+#[test]
+fn prove_within_memory_fails_fast_over_budget() -> Result<(), Box<dyn Error>> {
+    let circuit = ShowCircuit::new(Some(PathBuf::from(SHOW_INPUT)));
+    let (pk, vk) = setup_circuit_keys_no_save(circuit.clone())?;
+
+    assert!(matches!(
+        prove_within_memory(circuit.clone(), &pk, 1024),
+        Err(L8zkError::CommitMemoryExceeded { budget: 1024, .. })
+    ));
+    let proved = prove_within_memory(circuit, &pk, usize::MAX)?;
+    verify_circuit_with_loaded_data(&proved.proof, &vk)?;
+    Ok(())
+}
+
+#[test]
+fn verify_dir_skips_recorded_non_proofs_and_fails_undecodable_files() -> Result<(), Box<dyn Error>>
+{
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("pipeline_verify_dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir)?;
+
+    let circuit = ShowCircuit::new(Some(PathBuf::from(SHOW_INPUT)));
+    let (pk, vk) = setup_circuit_keys_no_save(circuit.clone())?;
+    let vk_path = dir.join("show_verifying.key").display().to_string();
+    save_verifying_key(&vk_path, &vk)?;
+
+    let artifacts = Artifacts::new(&dir, "show");
+    let public_inputs = circuit_public_values(&circuit)?;
+    prove_circuit_with_pk(
+        circuit,
+        &pk,
+        public_inputs,
+        &artifacts.instance,
+        &artifacts.witness,
+        &artifacts.proof,
+    )?;
+    // Not in the manifest, so it is taken for a proof and fails to decode
+    fs::write(dir.join("notes.bin"), b"not a proof")?;
+
+    let report = verify_dir(&dir, &vk_path)?;
+    assert_eq!(report.passed, [PathBuf::from(&artifacts.proof)]);
+    let failed: Vec<&PathBuf> = report.failed.iter().map(|(path, _)| path).collect();
+    assert_eq!(failed, [&dir.join("notes.bin")]);
+    assert_eq!(
+        report.skipped,
+        [
+            PathBuf::from(&artifacts.instance),
+            PathBuf::from(&artifacts.witness),
+        ]
+    );
+
+    // A truncated proof is a failure the CLI exits non-zero on, not a skipped file
+    fs::remove_file(dir.join("notes.bin"))?;
+    let proof_bytes = fs::read(&artifacts.proof)?;
+    fs::write(&artifacts.proof, &proof_bytes[..proof_bytes.len() / 2])?;
+    let report = verify_dir(&dir, &vk_path)?;
+    assert!(report.passed.is_empty() && report.failed.len() == 1);
+    let status = Command::new(env!("CARGO_BIN_EXE_ecdsa-spartan2"))
+        .args([
+            "verify-dir",
+            "--dir",
+            &dir.display().to_string(),
+            "--vk",
+            &vk_path,
+        ])
+        .status()?;
+    assert_eq!(status.code(), Some(1));
+    Ok(())
+}
+
+#[test]
+fn reblind_batch_reblinds_every_proof_with_one_set_of_blinds() -> Result<(), Box<dyn Error>> {
+    let circuit = ShowCircuit::new(Some(PathBuf::from(SHOW_INPUT)));
+    let (pk, vk) = setup_circuit_keys_no_save(circuit.clone())?;
+
+    for parallel in [false, true] {
+        let items = (0..3)
+            .map(|_| {
+                let proved = prove_circuit_in_memory(circuit.clone(), &pk)?;
+                Ok((proved.instance, proved.witness))
+            })
+            .collect::<Result<Vec<_>, L8zkError>>()?;
+        let blinds: Vec<Scalar> = (1..=items[0].0.num_shared_rows() as u64)
+            .map(Scalar::from)
+            .collect();
+
+        let proofs = reblind_batch(&pk, items, &blinds, &vk, parallel)?;
+        assert_eq!(proofs.len(), 3);
+        let shared = bincode::serialize(&proofs[0].comm_W_shared())?;
+        for proof in &proofs {
+            verify_circuit_with_loaded_data(proof, &vk)?;
+            assert_eq!(
+                bincode::serialize(&proof.comm_W_shared())?,
+                shared,
+                "proofs reblinded together must share comm_W_shared (parallel: {parallel})"
+            );
+        }
+    }
+    Ok(())
+}
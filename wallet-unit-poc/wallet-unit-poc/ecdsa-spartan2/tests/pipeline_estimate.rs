@@ -0,0 +1,42 @@
+//! `estimate_pipeline` keeps measured setups as they are and scales the other steps by each
+//! circuit's constraint count.
+
+use ecdsa_spartan2::{estimate_pipeline, CostModel, MeasuredSetup};
+
+#[test]
+fn steps_scale_with_constraint_count() {
+    let model = CostModel {
+        prove_ms_per_constraint: 0.5,
+        reblind_ms_per_constraint: 0.25,
+        verify_ms_per_constraint: 0.125,
+    };
+    let prepare = MeasuredSetup {
+        constraints: 1000,
+        setup_ms: 700.0,
+    };
+    let show = MeasuredSetup {
+        constraints: 100,
+        setup_ms: 30.0,
+    };
+
+    let estimate = estimate_pipeline(&model, prepare, show);
+    let ms = |name: &str| {
+        let step = estimate
+            .steps
+            .iter()
+            .find(|step| step.step == name)
+            .unwrap_or_else(|| panic!("no step named {name}"));
+        (step.ms, step.measured)
+    };
+
+    assert_eq!(ms("Prepare setup"), (700.0, true));
+    assert_eq!(ms("Show setup"), (30.0, true));
+    assert_eq!(ms("Prove Prepare"), (500.0, false));
+    assert_eq!(ms("Reblind Show"), (25.0, false));
+    assert_eq!(ms("Verify Prepare"), (125.0, false));
+    assert_eq!(estimate.steps.len(), 8);
+    assert_eq!(
+        estimate.total_ms(),
+        700.0 + 30.0 + (500.0 + 250.0 + 125.0) + (50.0 + 25.0 + 12.5)
+    );
+}
@@ -0,0 +1,43 @@
+//! `ProofStore` names proofs by their BLAKE3 hash: a hash it does not hold is absent, and a
+//! file whose contents no longer match its name is rejected rather than decoded.
+
+use std::{fs, path::PathBuf};
+
+use ecdsa_spartan2::{ArtifactError, L8zkError, ProofStore};
+
+fn store(name: &str) -> ProofStore {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    ProofStore::new(dir)
+}
+
+#[test]
+fn proofs_are_stored_under_their_blake3_hash() {
+    let store = store("proof_store_paths");
+    let hash = blake3::hash(b"proof bytes");
+
+    assert_eq!(
+        store.path_for(&hash),
+        store.dir().join(format!("{}.bin", hash.to_hex()))
+    );
+    assert!(store
+        .get(&hash)
+        .expect("a missing proof is not an error")
+        .is_none());
+}
+
+#[test]
+fn altered_files_are_rejected() {
+    let store = store("proof_store_altered");
+    let hash = blake3::hash(b"original proof");
+    fs::write(store.path_for(&hash), b"tampered proof").unwrap();
+
+    match store.get(&hash) {
+        Err(L8zkError::Artifact(ArtifactError::ContentHashMismatch { path })) => {
+            assert_eq!(path, store.path_for(&hash).display().to_string())
+        }
+        Err(e) => panic!("expected ContentHashMismatch, got {e}"),
+        Ok(_) => panic!("a tampered proof must not load"),
+    }
+}
@@ -0,0 +1,62 @@
+//! `prove_dir` names proofs deterministically and skips inputs whose proof already exists,
+//! without loading the proving key when nothing is left to prove.
+
+use std::{env, fs, path::PathBuf, process};
+
+use ecdsa_spartan2::{prove_dir, ProofNaming, ShowCircuit};
+use sha2::{Digest, Sha256};
+
+#[test]
+fn proofs_are_named_from_input_content_or_stem() {
+    let dir = env::temp_dir().join(format!("l8zk-proof-naming-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("alice.json");
+    fs::write(&input, r#"{"claim": "a"}"#).unwrap();
+
+    let out_dir = PathBuf::from("proofs");
+    let hashed = ProofNaming::ContentHash
+        .proof_path(&out_dir, &input)
+        .unwrap();
+    let expected = hex::encode(Sha256::digest(br#"{"claim": "a"}"#));
+    assert_eq!(hashed, out_dir.join(format!("{expected}.bin")));
+    assert_eq!(
+        ProofNaming::Stem.proof_path(&out_dir, &input).unwrap(),
+        out_dir.join("alice.bin")
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn inputs_with_existing_proofs_are_skipped() {
+    let root = env::temp_dir().join(format!("l8zk-prove-dir-{}", process::id()));
+    let (inputs, proofs) = (root.join("inputs"), root.join("proofs"));
+    fs::create_dir_all(&inputs).unwrap();
+    fs::create_dir_all(&proofs).unwrap();
+    for name in ["alice", "bob"] {
+        fs::write(inputs.join(format!("{name}.json")), "{}").unwrap();
+        fs::write(proofs.join(format!("{name}.bin")), "proof").unwrap();
+    }
+    fs::write(inputs.join("notes.txt"), "not an input").unwrap();
+
+    let report = prove_dir(
+        &inputs,
+        &proofs,
+        "missing/show_proving.key",
+        ProofNaming::Stem,
+        ShowCircuit::new,
+    )
+    .expect("nothing is left to prove, so the proving key is not needed");
+
+    assert!(report.proved.is_empty());
+    assert!(report.failed.is_empty());
+    assert_eq!(
+        report.skipped,
+        [
+            (inputs.join("alice.json"), proofs.join("alice.bin")),
+            (inputs.join("bob.json"), proofs.join("bob.bin")),
+        ]
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,106 @@
+//! `synthesize_r1cs` borrows the R1CS instead of handing a copy to `circom_scotia::synthesize`,
+//! and must still produce the same constraint system: the same constraint and variable counts
+//! and the same verifying key digest for the compiled Prepare and Show circuits.
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use circom_scotia::{r1cs::R1CS, reader::load_r1cs};
+use ecdsa_spartan2::{
+    circuits::common::synthesize_r1cs, dump_shape, setup_circuit_keys_no_save, PrepareCircuit,
+    Scalar, ShowCircuit, SynthesisCounters, E,
+};
+use spartan2::traits::{circuit::SpartanCircuit, snark::DigestHelperTrait};
+use std::{env, error::Error, path::Path, sync::Arc};
+
+/// Synthesizes an R1CS with upstream `circom_scotia::synthesize` when `upstream`, and with
+/// `synthesize_r1cs` otherwise
+#[derive(Debug, Clone)]
+struct R1csCircuit {
+    r1cs: Arc<R1CS<Scalar>>,
+    upstream: bool,
+}
+
+impl SpartanCircuit<E> for R1csCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        _: &[AllocatedNum<Scalar>],
+        _: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        if self.upstream {
+            circom_scotia::synthesize(cs, (*self.r1cs).clone(), None)?;
+            return Ok(());
+        }
+        synthesize_r1cs(
+            cs,
+            "test",
+            Path::new(""),
+            Some(&self.r1cs),
+            None,
+            &SynthesisCounters::default(),
+        )
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+fn assert_same_shape(r1cs_path: &Path) -> Result<(), Box<dyn Error>> {
+    let r1cs = Arc::new(load_r1cs::<Scalar>(r1cs_path));
+    let upstream = R1csCircuit {
+        r1cs: r1cs.clone(),
+        upstream: true,
+    };
+    let borrowed = R1csCircuit {
+        r1cs,
+        upstream: false,
+    };
+
+    let (upstream_shape, borrowed_shape) = (dump_shape(&upstream)?, dump_shape(&borrowed)?);
+    assert_eq!(
+        (upstream_shape.num_constraints, upstream_shape.num_variables),
+        (borrowed_shape.num_constraints, borrowed_shape.num_variables),
+        "{}",
+        r1cs_path.display()
+    );
+
+    let (_, upstream_vk) = setup_circuit_keys_no_save(upstream)?;
+    let (_, borrowed_vk) = setup_circuit_keys_no_save(borrowed)?;
+    assert_eq!(
+        upstream_vk.digest()?,
+        borrowed_vk.digest()?,
+        "{}",
+        r1cs_path.display()
+    );
+    Ok(())
+}
+
+#[test]
+fn prepare_r1cs_matches_upstream_synthesis() -> Result<(), Box<dyn Error>> {
+    assert_same_shape(&PrepareCircuit::r1cs_path(&env::current_dir()?))
+}
+
+#[test]
+fn show_r1cs_matches_upstream_synthesis() -> Result<(), Box<dyn Error>> {
+    assert_same_shape(&ShowCircuit::r1cs_path(&env::current_dir()?))
+}
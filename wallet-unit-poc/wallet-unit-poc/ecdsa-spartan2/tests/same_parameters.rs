@@ -0,0 +1,99 @@
+//! `assert_same_parameters` rejects a proof checked against another circuit's verifying key
+//! with `WrongParameters`, and proofs committing a different number of shared rows with
+//! `SharedRowsMismatch`.
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ecdsa_spartan2::{
+    assert_same_parameters, prove_circuit_in_memory, setup_circuit_keys_no_save, LinkError, Scalar,
+    E,
+};
+use spartan2::traits::circuit::SpartanCircuit;
+use std::error::Error;
+
+/// Commits `shared` values and squares the first, so circuits differ only in their shared rows
+#[derive(Debug, Clone)]
+struct SharedCircuit {
+    shared: usize,
+}
+
+impl SpartanCircuit<E> for SharedCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<Scalar>],
+        _: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        shared[0].square(cs.namespace(|| "square"))?;
+        Ok(())
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        (0..self.shared)
+            .map(|i| {
+                AllocatedNum::alloc(cs.namespace(|| format!("shared_{i}")), || {
+                    Ok(Scalar::from(i as u64 + 2))
+                })
+            })
+            .collect()
+    }
+
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+#[test]
+fn proofs_are_checked_against_their_own_keys_and_shared_rows() -> Result<(), Box<dyn Error>> {
+    let (small, large) = (
+        SharedCircuit { shared: 1 },
+        SharedCircuit { shared: 1 << 12 },
+    );
+    let (small_pk, small_vk) = setup_circuit_keys_no_save(small.clone())?;
+    let (large_pk, large_vk) = setup_circuit_keys_no_save(large.clone())?;
+    let small_proved = prove_circuit_in_memory(small, &small_pk)?;
+    let large_proved = prove_circuit_in_memory(large, &large_pk)?;
+    let (small_rows, large_rows) = (
+        small_proved.instance.num_shared_rows(),
+        large_proved.instance.num_shared_rows(),
+    );
+    assert_ne!(
+        small_rows, large_rows,
+        "the circuits must differ in shared rows"
+    );
+
+    let (small_proof, large_proof) = (&small_proved.proof, &large_proved.proof);
+    assert_same_parameters(small_proof, small_proof, &small_vk, &small_vk)?;
+
+    match assert_same_parameters(small_proof, large_proof, &large_vk, &large_vk) {
+        Err(LinkError::WrongParameters { circuit, .. }) => assert_eq!(circuit, "Prepare"),
+        other => panic!("expected WrongParameters for Prepare, got {other:?}"),
+    }
+    match assert_same_parameters(small_proof, large_proof, &small_vk, &small_vk) {
+        Err(LinkError::WrongParameters { circuit, .. }) => assert_eq!(circuit, "Show"),
+        other => panic!("expected WrongParameters for Show, got {other:?}"),
+    }
+
+    match assert_same_parameters(small_proof, large_proof, &small_vk, &large_vk) {
+        Err(LinkError::SharedRowsMismatch { prepare, show }) => {
+            assert_eq!((prepare, show), (small_rows, large_rows))
+        }
+        other => panic!("expected SharedRowsMismatch, got {other:?}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,140 @@
+//! `validate_jwt_input_schema` accepts the default Prepare input and reports every violation
+//! of a broken one at once. The exported JSON Schemas cover every field of the default inputs.
+//! `detect_circuit_kind` tells the default inputs apart by their field sets.
+//! `decodeFlags` entries may be numbers or decimal strings; anything else names the field.
+
+use ecdsa_spartan2::{
+    detect_circuit_kind, parse_decode_flags, prepare_input_schema, read_input_json,
+    show_input_schema, validate_jwt_input_schema, InputCircuit, InputError,
+};
+use serde_json::{json, Value};
+use std::path::Path;
+
+const PREPARE_INPUT: &str = "../circom/inputs/jwt/default.json";
+const SHOW_INPUT: &str = "../circom/inputs/show/default.json";
+
+fn default_input() -> Value {
+    read_input_json(Path::new(PREPARE_INPUT), u64::MAX).expect("default Prepare input parses")
+}
+
+#[test]
+fn default_input_is_valid() {
+    if let Err(errors) = validate_jwt_input_schema(&default_input()) {
+        panic!("default input should validate, got {errors:?}");
+    }
+}
+
+#[test]
+fn every_violation_is_reported() {
+    let mut input = default_input();
+    let object = input.as_object_mut().unwrap();
+    object.remove("pubKeyX");
+    object.insert("messageLength".into(), json!(1_000_000));
+    object["claimLengths"].as_array_mut().unwrap().pop();
+    object["matchLength"].as_array_mut().unwrap().pop();
+    object.insert("ageClaimIndex".into(), json!(9));
+
+    let errors = validate_jwt_input_schema(&input).expect_err("broken input must be rejected");
+
+    assert_eq!(errors.len(), 5, "unexpected errors: {errors:?}");
+    assert!(matches!(errors[0], InputError::Synthesis(_)));
+    assert!(errors.iter().any(|e| matches!(
+        e,
+        InputError::MessageLengthOutOfRange {
+            message_length: 1_000_000,
+            max: 1920
+        }
+    )));
+    assert!(errors.iter().any(|e| matches!(
+        e,
+        InputError::ClaimLengthsCountMismatch {
+            claims: 4,
+            claim_lengths: 3
+        }
+    )));
+    assert!(errors.iter().any(|e| matches!(
+        e,
+        InputError::AgeClaimIndexOutOfRange {
+            index: 9,
+            claims: 4
+        }
+    )));
+    assert!(errors
+        .iter()
+        .any(|e| matches!(e, InputError::MatchArraysLengthMismatch { .. })));
+}
+
+#[test]
+fn exported_schemas_require_every_default_input_field() {
+    for (path, schema) in [
+        (PREPARE_INPUT, prepare_input_schema()),
+        (SHOW_INPUT, show_input_schema()),
+    ] {
+        let input = read_input_json(Path::new(path), u64::MAX).expect("default input parses");
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|name| name.as_str().unwrap())
+            .collect();
+
+        let mut fields: Vec<&str> = input
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        fields.sort_unstable();
+        let mut required_sorted = required.clone();
+        required_sorted.sort_unstable();
+        assert_eq!(fields, required_sorted, "{path}");
+    }
+}
+
+#[test]
+fn circuit_kind_is_detected_from_the_input_fields() {
+    let show = read_input_json(Path::new(SHOW_INPUT), u64::MAX).expect("default Show input parses");
+    assert_eq!(
+        detect_circuit_kind(&default_input()),
+        Some(InputCircuit::Prepare)
+    );
+    assert_eq!(detect_circuit_kind(&show), Some(InputCircuit::Show));
+
+    let mut mixed = show.clone();
+    mixed["claims"] = json!([]);
+    assert_eq!(detect_circuit_kind(&mixed), None);
+    assert_eq!(detect_circuit_kind(&json!({ "sig_r": "1" })), None);
+}
+
+#[test]
+fn decode_flags_accept_numbers_and_decimal_strings() {
+    let mut input = default_input();
+    let claims = input["claims"].as_array().unwrap().len();
+    let flags: Vec<Value> = (0..claims)
+        .map(|index| match index % 2 {
+            0 => json!(1),
+            _ => json!("0"),
+        })
+        .collect();
+    input["decodeFlags"] = json!(flags);
+
+    let parsed = parse_decode_flags(&input).expect("numbers and strings both parse");
+    assert_eq!(parsed.len(), claims);
+    assert_eq!(parsed[0], 1);
+    if let Err(errors) = validate_jwt_input_schema(&input) {
+        panic!("mixed decodeFlags should validate, got {errors:?}");
+    }
+
+    input["decodeFlags"][0] = json!("one");
+    match parse_decode_flags(&input) {
+        Err(InputError::MalformedArrayEntry {
+            field,
+            index,
+            value,
+        }) => assert_eq!(
+            (field, index, value.as_str()),
+            ("decodeFlags", 0, "\"one\"")
+        ),
+        other => panic!("expected MalformedArrayEntry, got {other:?}"),
+    }
+}
@@ -0,0 +1,26 @@
+//! Secret buffers clear their values and keep them out of `Debug` output.
+
+use ecdsa_spartan2::{zeroize_scalars, Scalar, SecretScalars};
+use ff::Field;
+use zeroize::Zeroize;
+
+#[test]
+fn zeroized_scalars_are_zero() {
+    let mut scalars = vec![Scalar::from(7u64), -Scalar::ONE];
+    zeroize_scalars(&mut scalars);
+    assert_eq!(scalars, [Scalar::ZERO; 2]);
+}
+
+#[test]
+fn secret_scalars_debug_is_redacted() {
+    let secret = SecretScalars::from(vec![Scalar::from(42u64); 3]);
+    assert_eq!(format!("{secret:?}"), "SecretScalars([REDACTED; 3])");
+    assert_eq!(secret.len(), 3);
+}
+
+#[test]
+fn secret_scalars_zeroize_in_place() {
+    let mut secret = SecretScalars::from(vec![Scalar::from(42u64); 3]);
+    secret.zeroize();
+    assert_eq!(*secret, [Scalar::ZERO; 3]);
+}
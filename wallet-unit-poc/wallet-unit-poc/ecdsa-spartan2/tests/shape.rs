@@ -0,0 +1,88 @@
+//! `dump_shape` reports what each synthesis phase allocated, per namespace, without asking
+//! for witness values.
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ecdsa_spartan2::{dump_shape, NamespaceShape, Scalar, E};
+use spartan2::traits::circuit::SpartanCircuit;
+
+/// One shared value, then `x * x = y` in a "square" namespace
+#[derive(Clone)]
+struct Square;
+
+impl SpartanCircuit<E> for Square {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<Scalar>],
+        _: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let mut cs = cs.namespace(|| "square");
+        let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+            panic!("setup phase must not ask for witness values")
+        })?;
+        let x = &shared[0];
+        cs.enforce(
+            || "x * x = y",
+            |lc| lc + x.get_variable(),
+            |lc| lc + x.get_variable(),
+            |lc| lc + y.get_variable(),
+        );
+        Ok(())
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![AllocatedNum::alloc(cs.namespace(|| "x"), || {
+            panic!("setup phase must not ask for witness values")
+        })?])
+    }
+
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+#[test]
+fn shape_counts_allocations_per_namespace() {
+    let shape = dump_shape(&Square).expect("the circuit synthesizes");
+
+    assert_eq!(shape.num_constraints, 1);
+    assert_eq!(shape.num_aux, 2);
+    assert_eq!(shape.num_inputs, 0);
+    assert_eq!(shape.num_variables, 2);
+    assert_eq!(
+        shape.namespaces,
+        vec![
+            NamespaceShape {
+                path: "shared/x".into(),
+                aux: 1,
+                ..NamespaceShape::default()
+            },
+            NamespaceShape {
+                path: "synthesize/square".into(),
+                constraints: 1,
+                ..NamespaceShape::default()
+            },
+            NamespaceShape {
+                path: "synthesize/square/y".into(),
+                aux: 1,
+                ..NamespaceShape::default()
+            },
+        ]
+    );
+}
@@ -0,0 +1,70 @@
+//! Shared blinds are saved in an envelope naming their engine: they load back for that engine,
+//! blinds declared for another engine are refused, and bare bincode files still load. Seeded
+//! blinds are the same for the same seed.
+
+use std::{fs, path::PathBuf};
+
+use ecdsa_spartan2::{
+    generate_shared_blinds_seeded, load_shared_blinds, save_shared_blinds, ArtifactError,
+    L8zkError, Scalar, SharedBlindsEnvelope, E, SHARED_BLINDS_TAG,
+};
+
+fn blinds_path(name: &str) -> String {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("shared_blinds");
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name).display().to_string()
+}
+
+fn blinds() -> Vec<Scalar> {
+    (1..=4u64).map(Scalar::from).collect()
+}
+
+#[test]
+fn blinds_round_trip_through_the_envelope() {
+    let path = blinds_path("round_trip.bin");
+    save_shared_blinds::<E>(&path, &blinds()).unwrap();
+
+    assert!(fs::read(&path).unwrap().starts_with(SHARED_BLINDS_TAG));
+    assert_eq!(load_shared_blinds::<E>(&path).unwrap(), blinds());
+}
+
+#[test]
+fn blinds_for_another_engine_are_rejected() {
+    let path = blinds_path("other_engine.bin");
+    let envelope = SharedBlindsEnvelope {
+        engine: "OtherEngine".to_string(),
+        ..SharedBlindsEnvelope::for_engine::<E>(4)
+    };
+    let mut bytes = SHARED_BLINDS_TAG.to_vec();
+    bincode::serialize_into(&mut bytes, &envelope).unwrap();
+    bincode::serialize_into(&mut bytes, &blinds()).unwrap();
+    fs::write(&path, bytes).unwrap();
+
+    match load_shared_blinds::<E>(&path) {
+        Err(L8zkError::Artifact(ArtifactError::SharedBlindsEngineMismatch { found, .. })) => {
+            assert_eq!(found, "OtherEngine")
+        }
+        other => panic!("expected SharedBlindsEngineMismatch, got {other:?}"),
+    }
+}
+
+#[test]
+fn untagged_blinds_still_load() {
+    let path = blinds_path("legacy.bin");
+    fs::write(&path, bincode::serialize(&blinds()).unwrap()).unwrap();
+
+    assert_eq!(load_shared_blinds::<E>(&path).unwrap(), blinds());
+}
+
+#[test]
+fn seeded_blinds_are_reproducible() {
+    let path = blinds_path("seeded.bin");
+    let first = generate_shared_blinds_seeded::<E>(&path, 4, b"fixture").unwrap();
+    assert_eq!(load_shared_blinds::<E>(&path).unwrap(), first);
+
+    let again = generate_shared_blinds_seeded::<E>(&path, 4, b"fixture").unwrap();
+    let other = generate_shared_blinds_seeded::<E>(&path, 4, b"other").unwrap();
+    assert_eq!(again, first);
+    assert_ne!(other, first);
+    assert!(first.windows(2).all(|pair| pair[0] != pair[1]));
+}
@@ -0,0 +1,85 @@
+//! A witness generator that panics, returns nothing or returns a witness of the wrong length
+//! fails with `WitnessGenFailed` instead of unwinding through the prover or reaching
+//! synthesis. The expected length is read from the R1CS header alone.
+
+use ecdsa_spartan2::{
+    circuits::common::{guarded_witness, r1cs_wire_count},
+    InputError, L8zkError,
+};
+use rust_witness::BigInt;
+use std::{fs, path::PathBuf};
+
+#[test]
+fn panicking_generator_reports_witness_gen_failed() {
+    let result = guarded_witness("show", 1, || panic!("signal out of range"));
+
+    match result {
+        Err(InputError::WitnessGenFailed { circuit, reason }) => {
+            assert_eq!(circuit, "show");
+            assert!(reason.contains("signal out of range"), "{reason}");
+        }
+        other => panic!("expected WitnessGenFailed, got {other:?}"),
+    }
+}
+
+#[test]
+fn empty_witness_is_rejected() {
+    assert!(matches!(
+        guarded_witness("jwt", 1, Vec::new),
+        Err(InputError::WitnessGenFailed { circuit: "jwt", .. })
+    ));
+    assert_eq!(
+        guarded_witness("jwt", 1, || vec![BigInt::from(1u8)]).unwrap(),
+        [BigInt::from(1u8)]
+    );
+}
+
+#[test]
+fn witness_of_the_wrong_length_is_rejected() {
+    match guarded_witness("jwt", 3, || vec![BigInt::from(1u8); 2]) {
+        Err(InputError::WitnessGenFailed { circuit, reason }) => {
+            assert_eq!(circuit, "jwt");
+            assert!(reason.contains("3 wires"), "{reason}");
+        }
+        other => panic!("expected WitnessGenFailed, got {other:?}"),
+    }
+}
+
+/// An R1CS file whose header, after a constraints section, declares `wires` wires
+fn r1cs_bytes(wires: u32) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&32u32.to_le_bytes());
+    header.extend_from_slice(&[0xff; 32]);
+    header.extend_from_slice(&wires.to_le_bytes());
+    header.extend_from_slice(&[0; 4 * 3 + 8 + 4]);
+
+    let mut bytes = b"r1cs".to_vec();
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&3u64.to_le_bytes());
+    bytes.extend_from_slice(&[0; 3]);
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.extend_from_slice(&(header.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&header);
+    bytes
+}
+
+#[test]
+fn wire_count_is_read_from_the_r1cs_header() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("witness_guard_r1cs");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("circuit.r1cs");
+    fs::write(&path, r1cs_bytes(7)).unwrap();
+    assert_eq!(r1cs_wire_count(&path).unwrap(), 7);
+
+    fs::write(&path, b"not an r1cs file").unwrap();
+    assert!(matches!(r1cs_wire_count(&path), Err(L8zkError::Io(_))));
+
+    assert!(matches!(
+        r1cs_wire_count(&dir.join("missing.r1cs")),
+        Err(L8zkError::MissingArtifact { .. })
+    ));
+}
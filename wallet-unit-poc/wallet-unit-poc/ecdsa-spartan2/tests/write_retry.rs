@@ -0,0 +1,42 @@
+//! `write_with_retry` writes on the first attempt when it can, and reports errors that retrying
+//! cannot fix without waiting out its backoff.
+
+use std::{fs, time::Duration};
+
+use ecdsa_spartan2::{write_with_retry, ArtifactError, WriteRetry};
+
+fn retry() -> WriteRetry {
+    WriteRetry {
+        attempts: 5,
+        initial_backoff: Duration::from_secs(60),
+    }
+}
+
+#[test]
+fn writes_succeed_on_the_first_attempt() {
+    let path = format!("{}/write_retry.bin", env!("CARGO_TARGET_TMPDIR"));
+
+    write_with_retry(&path, b"proof bytes", &retry()).expect("the directory is writable");
+    assert_eq!(fs::read(&path).unwrap(), b"proof bytes");
+}
+
+#[test]
+fn permanent_errors_are_not_retried() {
+    let path = format!(
+        "{}/write_retry_missing/dir/proof.bin",
+        env!("CARGO_TARGET_TMPDIR")
+    );
+
+    // A retry would sleep for a minute, so returning at all shows it was not retried
+    match write_with_retry(&path, b"proof bytes", &retry()) {
+        Err(ArtifactError::WriteFailed {
+            path: failed,
+            attempts,
+            ..
+        }) => {
+            assert_eq!(failed, path);
+            assert_eq!(attempts, 1);
+        }
+        other => panic!("expected WriteFailed, got {other:?}"),
+    }
+}